@@ -257,6 +257,9 @@ pub fn tank_palette<'a>(assets: &'a Assets, team: Team) -> &'a TankPalette {
     match team {
         Team::Red => &assets.tanks.red,
         Team::Blue => &assets.tanks.blue,
+        Team::Green => &assets.tanks.green,
+        Team::Beige => &assets.tanks.beige,
+        Team::Black => &assets.tanks.black,
     }
 }
 
@@ -264,5 +267,9 @@ pub fn bullet_palette<'a>(assets: &'a Assets, team: Team) -> &'a BulletPalette {
     match team {
         Team::Red => &assets.bullets.red,
         Team::Blue => &assets.bullets.blue,
+        Team::Green => &assets.bullets.green,
+        Team::Beige => &assets.bullets.beige,
+        // There are no black bullets; silver reads as the same dark steel.
+        Team::Black => &assets.bullets.silver,
     }
 }
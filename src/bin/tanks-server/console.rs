@@ -0,0 +1,186 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+pub const HELP: &str = "\
+commands:
+  status                  round, score, map and player count
+  players                 connected players, numbered for kick
+  kick NAME|#N [REASON]   disconnect a player
+  restart [SEED]          restart the round on this map, or on map SEED
+  next                    skip to the next map in the rotation
+  maps [SEED,...]         show or replace the map rotation
+  get [KEY]               show a setting, e.g. `get round.time`, or all of them
+  set KEY VALUE           change a setting, e.g. `set round.tanks_per_team 3`
+  quit                    stop the server";
+
+/// One line typed at an admin console.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Help,
+    Status,
+    Players,
+    Kick {
+        target: String,
+        reason: Option<String>,
+    },
+    Restart(Option<u64>),
+    Next,
+    Maps(Option<Vec<u64>>),
+    Get(Option<String>),
+    Set {
+        key: String,
+        value: String,
+    },
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let (word, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let arg = (!rest.is_empty()).then(|| rest.to_string());
+        let seed = |text: &str| {
+            text.trim()
+                .parse::<u64>()
+                .map_err(|_| format!("`{}` is not a map seed", text.trim()))
+        };
+        match word.to_ascii_lowercase().as_str() {
+            "help" | "?" => Ok(Command::Help),
+            "status" => Ok(Command::Status),
+            "players" | "who" => Ok(Command::Players),
+            "kick" => {
+                let (target, reason) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                if target.is_empty() {
+                    return Err("usage: kick NAME|#N [REASON]".to_string());
+                }
+                let reason = reason.trim();
+                Ok(Command::Kick {
+                    target: target.to_string(),
+                    reason: (!reason.is_empty()).then(|| reason.to_string()),
+                })
+            }
+            "restart" => Ok(Command::Restart(arg.as_deref().map(seed).transpose()?)),
+            "next" | "skip" => Ok(Command::Next),
+            "maps" | "rotation" => {
+                let seeds = arg
+                    .map(|list| {
+                        list.split([',', ' '])
+                            .filter(|s| !s.is_empty())
+                            .map(seed)
+                            .collect()
+                    })
+                    .transpose()?;
+                Ok(Command::Maps(seeds))
+            }
+            "get" | "config" => Ok(Command::Get(arg)),
+            "set" => {
+                let (key, value) = rest
+                    .split_once(char::is_whitespace)
+                    .ok_or("usage: set KEY VALUE")?;
+                Ok(Command::Set {
+                    key: key.to_string(),
+                    value: value.trim().to_string(),
+                })
+            }
+            "quit" | "exit" | "shutdown" => Ok(Command::Quit),
+            _ => Err(format!("unknown command `{word}`; try `help`")),
+        }
+    }
+}
+
+/// A command line waiting for the server loop, with somewhere to send the
+/// answer.
+pub struct Request {
+    pub line: String,
+    reply: Sender<String>,
+}
+
+impl Request {
+    pub fn reply(self, text: String) {
+        // The console may have gone away; nothing to do about it.
+        let _ = self.reply.send(text);
+    }
+}
+
+/// Collects admin commands from stdin and, optionally, from TCP
+/// connections, for the server loop to poll without blocking.
+pub struct Console {
+    requests: Receiver<Request>,
+}
+
+impl Console {
+    /// Starts reading stdin, and listens for admin connections on
+    /// `admin_addr` when given.
+    pub fn start(admin_addr: Option<&str>) -> io::Result<Self> {
+        let (sender, requests) = mpsc::channel();
+        let listener = admin_addr.map(TcpListener::bind).transpose()?;
+
+        let stdin_sender = sender.clone();
+        thread::spawn(move || {
+            let stdin = io::stdin();
+            let mut stdout = io::stdout();
+            serve_lines(stdin.lock(), &mut stdout, &stdin_sender, false)
+        });
+        if let Some(listener) = listener {
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let sender = sender.clone();
+                    thread::spawn(move || serve_connection(stream, &sender));
+                }
+            });
+        }
+        Ok(Self { requests })
+    }
+
+    /// The next command typed at any console, if one is waiting.
+    pub fn poll(&self) -> Option<Request> {
+        self.requests.try_recv().ok()
+    }
+}
+
+fn serve_connection(stream: TcpStream, sender: &Sender<Request>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    writeln!(
+        writer,
+        "tanks-server admin console; type `help` for commands"
+    )?;
+    serve_lines(BufReader::new(stream), &mut writer, sender, true)
+}
+
+/// Passes each line read to the server loop and writes back its answer,
+/// until the input ends or the server stops.
+fn serve_lines(
+    input: impl BufRead,
+    output: &mut impl Write,
+    sender: &Sender<Request>,
+    prompt: bool,
+) -> io::Result<()> {
+    if prompt {
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+    for line in input.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            let (reply, answer) = mpsc::channel();
+            if sender.send(Request { line, reply }).is_err() {
+                return Ok(());
+            }
+            let Ok(text) = answer.recv() else {
+                return Ok(());
+            };
+            writeln!(output, "{text}")?;
+        }
+        if prompt {
+            write!(output, "> ")?;
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Timestamped lines on stdout and, optionally, appended to a file.
+pub struct Log {
+    file: Option<File>,
+}
+
+impl Log {
+    pub fn open(path: Option<&Path>) -> io::Result<Self> {
+        let file = path
+            .map(|path| OpenOptions::new().create(true).append(true).open(path))
+            .transpose()?;
+        Ok(Self { file })
+    }
+
+    pub fn line(&mut self, message: impl std::fmt::Display) {
+        let line = format!("[{}] {message}", timestamp());
+        println!("{line}");
+        let written = match &mut self.file {
+            Some(file) => writeln!(file, "{line}"),
+            None => Ok(()),
+        };
+        if let Err(err) = written {
+            eprintln!("failed to write log file: {err}");
+            self.file = None;
+        }
+    }
+}
+
+/// The current UTC time as `YYYY-MM-DD HH:MM:SS`.
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let (days, time) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// Converts days since 1970-01-01 to a proleptic Gregorian date, after
+/// Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,252 @@
+//! Dedicated server: hosts online matches around the clock without a
+//! window, so it runs on machines with no display.
+
+mod console;
+mod log;
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use tanks::config::{ConfigWatcher, DEFAULT_CONFIG_PATH, GameConfig, TICK_RATE};
+use tanks::entities::Team;
+use tanks::net::{DEFAULT_PORT, LinkConditions, Server, ServerEvent};
+
+use console::{Command, Console, HELP};
+use log::Log;
+
+/// Ticks between checks of the tuning file for edits.
+const CONFIG_POLL_TICKS: u64 = TICK_RATE as u64;
+
+/// Dedicated Tanks: Dominion server.
+///
+/// Runs rounds back to back for players joining with `tanks connect`.
+/// Commands typed on stdin (or over `--admin`) manage it while it runs;
+/// type `help` for the list.
+#[derive(Parser)]
+#[command(name = "tanks-server", version)]
+struct Args {
+    /// Address to listen on for players.
+    #[arg(long, default_value = "0.0.0.0")]
+    bind: String,
+    /// UDP port to listen on for players.
+    #[arg(long, default_value_t = DEFAULT_PORT)]
+    port: u16,
+    /// Map seeds to play in turn, one per round, e.g. `--maps 7,42,1999`.
+    /// Every round gets a fresh random map when omitted.
+    #[arg(long, value_name = "SEED,...", value_delimiter = ',')]
+    maps: Vec<u64>,
+    /// Gameplay tuning file [default: tanks.toml if present]. Edits are
+    /// picked up while running.
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Also take admin commands over TCP, e.g. `127.0.0.1:7778`. Anyone who
+    /// can connect gets full control, so keep it off public interfaces.
+    #[arg(long, value_name = "ADDR")]
+    admin: Option<String>,
+    /// Append the log to this file as well as printing it.
+    #[arg(long, value_name = "FILE")]
+    log: Option<PathBuf>,
+}
+
+fn main() {
+    if let Err(err) = run(Args::parse()) {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Args) -> io::Result<()> {
+    let config_path = match &args.config {
+        Some(path) => Some(path.as_path()),
+        None => Some(Path::new(DEFAULT_CONFIG_PATH)).filter(|path| path.exists()),
+    };
+    let config = match config_path {
+        Some(path) => GameConfig::load(path)?,
+        None => GameConfig::default(),
+    };
+    let mut maps = args.maps.clone();
+    let first_map = maps.first().copied().unwrap_or_else(system_seed);
+    // The first map is played now, so the rotation carries on from the second.
+    if !maps.is_empty() {
+        maps.rotate_left(1);
+    }
+
+    let address = format!("{}:{}", args.bind, args.port);
+    let mut server = Server::bind(&address, config, first_map, LinkConditions::default())?;
+    server.set_rotation(maps);
+    let console = Console::start(args.admin.as_deref())?;
+    let mut log = Log::open(args.log.as_deref())?;
+    let mut watcher = config_path.map(ConfigWatcher::new);
+
+    log.line(format_args!("listening on {}", server.local_addr()?));
+    if let Some(admin) = &args.admin {
+        log.line(format_args!("admin console on tcp {admin}"));
+    }
+    let mut ticks = 0u64;
+    server.run(|server, events| {
+        for event in events {
+            match &event {
+                ServerEvent::RoundOver { .. } => {
+                    log.line(format_args!("{event} ({} players)", server.players().len()))
+                }
+                _ => log.line(&event),
+            }
+        }
+
+        ticks += 1;
+        if let Some(watcher) = watcher
+            .as_mut()
+            .filter(|_| ticks.is_multiple_of(CONFIG_POLL_TICKS))
+        {
+            match watcher.poll() {
+                Some(Ok(config)) => match server.set_config(config) {
+                    Ok(()) => log.line(format_args!("reloaded {}", watcher.path().display())),
+                    Err(err) => log.line(format_args!("failed to send settings: {err}")),
+                },
+                Some(Err(err)) => log.line(format_args!("keeping previous config: {err}")),
+                None => {}
+            }
+        }
+
+        while let Some(request) = console.poll() {
+            let command = request.line.parse::<Command>();
+            let quit = command == Ok(Command::Quit);
+            let reply = match command {
+                Ok(command) => execute(server, &mut log, command),
+                Err(err) => err,
+            };
+            request.reply(reply);
+            if quit {
+                return false;
+            }
+        }
+        true
+    })?;
+    log.line("shut down");
+    Ok(())
+}
+
+/// Carries out an admin command and returns the text to show the admin.
+/// Anything that changes the match is logged too.
+fn execute(server: &mut Server, log: &mut Log, command: Command) -> String {
+    match command {
+        Command::Help => HELP.to_string(),
+        Command::Status => status(server),
+        Command::Players => {
+            let players = server.players();
+            if players.is_empty() {
+                return "no players connected".to_string();
+            }
+            players
+                .iter()
+                .enumerate()
+                .map(|(index, player)| {
+                    let seat = match (player.team, player.tank) {
+                        (Some(team), Some(tank)) => format!("{} tank {tank}", team.name()),
+                        _ => "spectating".to_string(),
+                    };
+                    format!("#{} {} ({}) {seat}", index + 1, player.name, player.addr)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        Command::Kick { target, reason } => {
+            let players = server.players();
+            let index = match target.strip_prefix('#') {
+                Some(number) => number
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|number| number.checked_sub(1))
+                    .filter(|index| *index < players.len()),
+                None => players
+                    .iter()
+                    .position(|player| player.name.eq_ignore_ascii_case(&target)),
+            };
+            let Some(index) = index else {
+                return format!("no player `{target}`; see `players`");
+            };
+            let reason = reason.as_deref().unwrap_or("kicked by the admin");
+            match server.kick(index, reason) {
+                Ok(_) => format!("kicked {}", players[index].name),
+                Err(err) => format!(
+                    "kicked {}, but could not tell them: {err}",
+                    players[index].name
+                ),
+            }
+        }
+        Command::Restart(seed) => {
+            let seed = seed.or(server.map_seed());
+            log.line("admin restarted the round");
+            server.restart_round(seed);
+            status(server)
+        }
+        Command::Next => {
+            log.line("admin skipped to the next map");
+            server.restart_round(None);
+            status(server)
+        }
+        Command::Maps(None) => match server.rotation().as_slice() {
+            [] => "no rotation; every round gets a random map".to_string(),
+            seeds => format!(
+                "coming up: {}",
+                seeds
+                    .iter()
+                    .map(u64::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        },
+        Command::Maps(Some(seeds)) => {
+            log.line(format_args!("admin set the rotation to {seeds:?}"));
+            server.set_rotation(seeds);
+            "rotation replaced; it starts with the next round".to_string()
+        }
+        Command::Get(None) => server.game().config().to_toml(),
+        Command::Get(Some(key)) => match server.game().config().value(&key) {
+            Some(value) => format!("{key} = {value}"),
+            None => format!("unknown setting `{key}`"),
+        },
+        Command::Set { key, value } => {
+            let config = match server.game().config().with_value(&key, &value) {
+                Ok(config) => config,
+                Err(err) => return format!("not changed: {err}"),
+            };
+            log.line(format_args!("admin set {key} = {value}"));
+            match server.set_config(config) {
+                Ok(()) => format!("{key} = {value}"),
+                Err(err) => format!("{key} = {value}, but not every client was told: {err}"),
+            }
+        }
+        Command::Quit => "shutting down".to_string(),
+    }
+}
+
+fn status(server: &Server) -> String {
+    let game = server.game();
+    let score = Team::scoreline(game.scores());
+    let phase = if game.is_round_over() {
+        "over".to_string()
+    } else if game.countdown_timer() > 0.0 {
+        "deploying".to_string()
+    } else {
+        let left = game.round_timer().ceil() as u32;
+        format!("{}:{:02} left", left / 60, left % 60)
+    };
+    let map = server
+        .map_seed()
+        .map_or("random map".to_string(), |seed| format!("map {seed}"));
+    format!(
+        "round {} ({phase}), {score}, {map}, {} players",
+        game.round(),
+        server.players().len()
+    )
+}
+
+fn system_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
+}
@@ -0,0 +1,7 @@
+mod observation;
+mod process;
+
+pub use observation::{
+    Action, BulletView, Observation, ObstacleView, PowerupView, SelfView, TankView,
+};
+pub use process::BotProcess;
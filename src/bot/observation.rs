@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{PowerupKind, Tank, Team};
+use crate::game::{Game, PlayerInput};
+use crate::math::Vector2;
+
+/// What an external bot is told about the game each tick.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Observation {
+    pub tick: u64,
+    /// Index of the tank the bot controls.
+    pub tank: usize,
+    pub round_timer: f32,
+    /// Kills this round, one per team playing, Crimson first.
+    pub team_kills: Vec<u32>,
+    #[serde(rename = "self")]
+    pub me: SelfView,
+    /// Living teammates, plus enemies in line of sight.
+    pub tanks: Vec<TankView>,
+    pub bullets: Vec<BulletView>,
+    pub powerups: Vec<PowerupView>,
+    pub obstacles: Vec<ObstacleView>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SelfView {
+    pub team: Team,
+    pub alive: bool,
+    pub pos: Vector2,
+    pub vel: Vector2,
+    pub body_angle: f32,
+    pub turret_angle: f32,
+    pub health: f32,
+    pub max_health: f32,
+    pub fire_cooldown: f32,
+    pub respawn_timer: f32,
+    pub invincible_timer: f32,
+    pub rapid_timer: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TankView {
+    pub index: usize,
+    pub team: Team,
+    pub pos: Vector2,
+    pub vel: Vector2,
+    pub body_angle: f32,
+    pub turret_angle: f32,
+    pub health: f32,
+    pub max_health: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BulletView {
+    pub team: Team,
+    pub pos: Vector2,
+    pub vel: Vector2,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PowerupView {
+    pub kind: PowerupKind,
+    pub pos: Vector2,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObstacleView {
+    pub pos: Vector2,
+    pub radius: f32,
+}
+
+/// A bot's reply: the same controls the human tank has. `turn` and `move`
+/// are clamped to -1..1, `turret` is an absolute angle in radians.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Action {
+    pub turn: f32,
+    #[serde(rename = "move")]
+    pub movement: f32,
+    pub turret: Option<f32>,
+    pub fire: bool,
+}
+
+impl Observation {
+    pub fn new(game: &Game, tank: usize, tick: u64) -> Self {
+        let me = &game.tanks()[tank];
+        let world = game.world();
+        let tanks = game
+            .tanks()
+            .iter()
+            .enumerate()
+            .filter(|(index, other)| {
+                *index != tank
+                    && other.alive
+                    && (other.team == me.team
+                        || (me.alive && world.line_of_sight(me.pos, other.pos, 0.0)))
+            })
+            .map(|(index, other)| TankView::new(index, other))
+            .collect();
+        Self {
+            tick,
+            tank,
+            round_timer: game.round_timer(),
+            team_kills: game.team_kills().to_vec(),
+            me: SelfView {
+                team: me.team,
+                alive: me.alive,
+                pos: me.pos,
+                vel: me.vel,
+                body_angle: me.body_angle,
+                turret_angle: me.turret_angle,
+                health: me.health,
+                max_health: me.max_health,
+                fire_cooldown: me.fire_cooldown,
+                respawn_timer: me.respawn_timer,
+                invincible_timer: me.invincible_timer,
+                rapid_timer: me.rapid_timer,
+            },
+            tanks,
+            bullets: game
+                .bullets()
+                .iter()
+                .map(|bullet| BulletView {
+                    team: bullet.team,
+                    pos: bullet.pos,
+                    vel: bullet.vel,
+                })
+                .collect(),
+            powerups: game
+                .powerups()
+                .iter()
+                .map(|powerup| PowerupView {
+                    kind: powerup.kind,
+                    pos: powerup.pos,
+                })
+                .collect(),
+            obstacles: world
+                .obstacles
+                .iter()
+                .map(|obstacle| ObstacleView {
+                    pos: obstacle.pos,
+                    radius: obstacle.radius,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TankView {
+    fn new(index: usize, tank: &Tank) -> Self {
+        Self {
+            index,
+            team: tank.team,
+            pos: tank.pos,
+            vel: tank.vel,
+            body_angle: tank.body_angle,
+            turret_angle: tank.turret_angle,
+            health: tank.health,
+            max_health: tank.max_health,
+        }
+    }
+}
+
+impl Action {
+    pub fn to_input(self) -> PlayerInput {
+        PlayerInput {
+            turn: self.turn.clamp(-1.0, 1.0),
+            movement: self.movement.clamp(-1.0, 1.0),
+            aim_angle: self.turret,
+            wants_fire: self.fire,
+        }
+    }
+}
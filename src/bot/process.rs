@@ -0,0 +1,144 @@
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use super::{Action, Observation};
+
+/// How long a bot may take to answer one observation before it is given up
+/// on, generous enough for a slow first reply.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An external bot speaking line-delimited JSON: one [`Observation`] on its
+/// stdin per tick, one [`Action`] back on its stdout. Its stderr is passed
+/// through for logging.
+pub struct BotProcess {
+    command: String,
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    /// Lines from the bot's stdout, read on a helper thread so a bot that
+    /// hangs cannot block the game.
+    replies: Receiver<io::Result<String>>,
+    timeout: Duration,
+}
+
+impl BotProcess {
+    /// Starts `command`, split on whitespace into a program and arguments.
+    pub fn spawn(command: &str) -> io::Result<Self> {
+        let mut parts = command.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty bot command"))?;
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| io::Error::new(err.kind(), format!("{command}: {err}")))?;
+        let stdin = BufWriter::new(child.stdin.take().expect("piped stdin"));
+        let stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            command: command.to_string(),
+            child,
+            stdin,
+            replies,
+            timeout: REPLY_TIMEOUT,
+        })
+    }
+
+    /// Sends one observation and waits for the bot's action, failing with
+    /// [`io::ErrorKind::TimedOut`] if none comes in time.
+    pub fn exchange(&mut self, observation: &Observation) -> io::Result<Action> {
+        serde_json::to_writer(&mut self.stdin, observation)?;
+        self.stdin.write_all(b"\n")?;
+        self.stdin.flush()?;
+
+        let line = match self.replies.recv_timeout(self.timeout) {
+            Ok(line) => line?,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "bot `{}` did not answer within {}s",
+                        self.command,
+                        self.timeout.as_secs_f32()
+                    ),
+                ));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("bot `{}` closed its output", self.command),
+                ));
+            }
+        };
+        serde_json::from_str(line.trim()).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("bad action from bot `{}`: {err}", self.command),
+            )
+        })
+    }
+}
+
+impl Drop for BotProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use crate::game::Game;
+
+    use super::*;
+
+    fn observation() -> Observation {
+        Observation::new(&Game::new(1), 0, 0)
+    }
+
+    #[test]
+    fn a_silent_bot_times_out() {
+        let mut bot = BotProcess::spawn("sleep 30").unwrap();
+        bot.timeout = Duration::from_millis(200);
+        let err = bot.exchange(&observation()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(err.to_string().contains("sleep 30"), "{err}");
+    }
+
+    #[test]
+    fn a_bot_that_exits_closes_its_output() {
+        let mut bot = BotProcess::spawn("true").unwrap();
+        let err = bot.exchange(&observation());
+        // Depending on timing the write or the read notices first.
+        let kind = err.unwrap_err().kind();
+        assert!(
+            matches!(
+                kind,
+                io::ErrorKind::UnexpectedEof | io::ErrorKind::BrokenPipe
+            ),
+            "{kind:?}"
+        );
+    }
+
+    #[test]
+    fn answers_are_parsed_as_actions() {
+        // Answers every observation with the same action.
+        let mut bot = BotProcess::spawn(r#"sed -u s/.*/{"move":1.0,"fire":true}/"#).unwrap();
+        for _ in 0..3 {
+            let action = bot.exchange(&observation()).unwrap();
+            assert_eq!(action.movement, 1.0);
+            assert!(action.fire);
+        }
+    }
+}
@@ -0,0 +1,559 @@
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use tanks::config::{
+    DEFAULT_CONFIG_PATH, Difficulty, GameConfig, GameMode, TeamSkill, WINDOW_HEIGHT, WINDOW_WIDTH,
+};
+use tanks::entities::Team;
+use tanks::game::LocalPlayer;
+use tanks::net::{DEFAULT_PORT, LinkConditions};
+
+/// Top-down squad tank skirmish.
+///
+/// Running without a subcommand is the same as `tanks play`.
+#[derive(Parser)]
+#[command(name = "tanks", version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub play: PlayArgs,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Open a window and play.
+    Play(PlayArgs),
+    /// Run AI-vs-AI rounds without a window and report balance statistics.
+    Simulate(SimulateArgs),
+    /// Run rounds without a window with some tanks driven by external
+    /// processes over stdin/stdout.
+    Bot(BotArgs),
+    /// Watch a recorded .tankreplay file.
+    Replay(ReplayArgs),
+    /// Render a single frame to a PNG and exit.
+    Render(RenderArgs),
+    /// Generate a map and print it without opening a window.
+    Mapgen(MapgenArgs),
+    /// Host a match over UDP without a window. Players join with `connect`.
+    Serve(ServeArgs),
+    /// Join a match hosted with `serve`.
+    Connect(ConnectArgs),
+    /// Play a 1v1 duel peer to peer, with rollback instead of a server.
+    Duel(DuelArgs),
+    /// Find or host a game on the local network, pick teams and ready up.
+    Lobby(LobbyArgs),
+}
+
+/// Options that shape the simulated game.
+#[derive(Args, Clone, Debug)]
+pub struct GameArgs {
+    /// World and spawn seed; random when omitted.
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Gameplay tuning file [default: tanks.toml if present].
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+    /// Map width in tiles.
+    #[arg(long, value_name = "TILES")]
+    pub map_width: Option<i32>,
+    /// Map height in tiles.
+    #[arg(long, value_name = "TILES")]
+    pub map_height: Option<i32>,
+    /// Tanks on each team.
+    #[arg(long, value_name = "COUNT")]
+    pub team_size: Option<usize>,
+    /// Number of teams, 2 to 5.
+    #[arg(long, value_name = "COUNT")]
+    pub teams: Option<usize>,
+    /// Free-for-all: every tank is its own team, with `--teams` tanks in
+    /// all (so at most five).
+    #[arg(long)]
+    pub ffa: bool,
+    /// How rounds are won: deathmatch or ctf (capture the flag).
+    #[arg(long, value_name = "MODE")]
+    pub mode: Option<GameMode>,
+    /// Round length in seconds.
+    #[arg(long, value_name = "SECONDS")]
+    pub round_time: Option<f32>,
+    /// AI skill for every bot: recruit, veteran or ace.
+    #[arg(long, value_name = "LEVEL")]
+    pub difficulty: Option<Difficulty>,
+    /// AI skill for Crimson bots, overriding `--difficulty`.
+    #[arg(long, value_name = "LEVEL")]
+    pub red_difficulty: Option<Difficulty>,
+    /// AI skill for Azure bots, overriding `--difficulty`.
+    #[arg(long, value_name = "LEVEL")]
+    pub blue_difficulty: Option<Difficulty>,
+}
+
+#[derive(Args, Clone, Copy, Debug)]
+pub struct WindowArgs {
+    /// Window width in pixels.
+    #[arg(long, default_value_t = WINDOW_WIDTH, value_parser = clap::value_parser!(i32).range(320..))]
+    pub width: i32,
+    /// Window height in pixels.
+    #[arg(long, default_value_t = WINDOW_HEIGHT, value_parser = clap::value_parser!(i32).range(240..))]
+    pub height: i32,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct PlayArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+    #[command(flatten)]
+    pub window: WindowArgs,
+    /// Record every tick of input to a replay file.
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+    /// Resume from a .tanksave file instead of starting fresh. The save
+    /// keeps its own seed and settings, so `--seed`, `--config` and the
+    /// other game options are refused alongside it.
+    #[arg(long, value_name = "FILE", conflicts_with_all = [
+        "seed", "config", "map_width", "map_height", "team_size", "teams", "ffa", "mode",
+        "round_time", "difficulty", "red_difficulty", "blue_difficulty",
+    ])]
+    pub load: Option<PathBuf>,
+    /// Seat a local player, e.g. `--player red:keyboard --player
+    /// blue:gamepad0`. Repeat for up to 4 players in split screen; devices
+    /// are `keyboard`, `gamepadN` or `auto` (keyboard or the first gamepad).
+    /// Defaults to one Crimson player on `auto`.
+    #[arg(long = "player", value_name = "TEAM[:DEVICE]")]
+    pub players: Vec<LocalPlayer>,
+    /// Watch a bots-only match instead of playing. Left/Right or Q/E cycle
+    /// the tank followed, Tab toggles the overhead view.
+    #[arg(long, conflicts_with = "players")]
+    pub spectate: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct SimulateArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+    /// Number of AI-vs-AI rounds. Pairs of rounds share a seed, counting up
+    /// from `--seed`, with the spawn sides swapped.
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+    pub rounds: u32,
+    /// Report format.
+    #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+    pub format: ReportFormat,
+    /// Write the report to a file instead of stdout.
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct BotArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+    /// Hand a tank to an external program, e.g. `--bot "0=python3 agent.py"`.
+    /// Tanks are numbered from 0, Crimson first. Repeat for more bots.
+    #[arg(long = "bot", value_name = "TANK=COMMAND", required = true)]
+    pub bots: Vec<BotSpec>,
+    /// Rounds to play back to back.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub rounds: u32,
+    /// Record the match to a replay file.
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+}
+
+/// A tank index and the command that drives it.
+#[derive(Clone, Debug)]
+pub struct BotSpec {
+    pub tank: usize,
+    pub command: String,
+}
+
+impl FromStr for BotSpec {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (tank, command) = value
+            .split_once('=')
+            .ok_or_else(|| format!("expected TANK=COMMAND, got `{value}`"))?;
+        let tank = tank
+            .trim()
+            .parse()
+            .map_err(|_| format!("`{tank}` is not a tank index"))?;
+        let command = command.trim();
+        if command.is_empty() {
+            return Err("missing bot command".to_string());
+        }
+        Ok(Self {
+            tank,
+            command: command.to_string(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Human-readable summary.
+    Text,
+    /// One row per round.
+    Csv,
+    /// Summary plus every round.
+    Json,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ReplayArgs {
+    /// Replay file to play back.
+    pub file: PathBuf,
+    #[command(flatten)]
+    pub window: WindowArgs,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct RenderArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+    #[command(flatten)]
+    pub window: WindowArgs,
+    /// Where to write the screenshot.
+    #[arg(long, short, value_name = "FILE", default_value = "debug_frame.png")]
+    pub output: PathBuf,
+    /// Seconds of play to simulate before capturing; 0 shows the title screen.
+    #[arg(long, default_value_t = 0.0, value_name = "SECONDS")]
+    pub time: f32,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct MapgenArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = MapFormat::Ascii)]
+    pub format: MapFormat,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ServeArgs {
+    #[command(flatten)]
+    pub game: GameArgs,
+    /// Address to listen on.
+    #[arg(long, default_value = "0.0.0.0")]
+    pub bind: String,
+    /// UDP port to listen on.
+    #[arg(long, default_value_t = DEFAULT_PORT)]
+    pub port: u16,
+    #[command(flatten)]
+    pub link: LinkArgs,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ConnectArgs {
+    /// Server to join, as HOST or HOST:PORT.
+    pub address: String,
+    /// Name shown to the server.
+    #[arg(long, default_value = "")]
+    pub name: String,
+    /// Team to ask for; the server balances teams when omitted.
+    #[arg(long, value_enum)]
+    pub team: Option<TeamChoice>,
+    /// Watch instead of taking a tank. Left/Right or Q/E cycle the tank
+    /// followed, Tab toggles the overhead view.
+    #[arg(long, conflicts_with = "team")]
+    pub spectate: bool,
+    #[command(flatten)]
+    pub window: WindowArgs,
+    #[command(flatten)]
+    pub link: LinkArgs,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct DuelArgs {
+    #[command(subcommand)]
+    pub command: DuelCommand,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum DuelCommand {
+    /// Wait for an opponent and play Crimson.
+    Host(DuelHostArgs),
+    /// Join a hosted duel and play Azure.
+    Join(DuelJoinArgs),
+    /// Run two headless peers with scripted input in separate processes
+    /// over a simulated bad link, and check they stay in sync.
+    Test(DuelTestArgs),
+    /// One headless peer, as started by `duel test`.
+    #[command(hide = true)]
+    Peer(DuelPeerArgs),
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct DuelHostArgs {
+    /// Match options; the team size defaults to 1.
+    #[command(flatten)]
+    pub game: GameArgs,
+    /// UDP port to wait on.
+    #[arg(long, default_value_t = DEFAULT_PORT)]
+    pub port: u16,
+    #[command(flatten)]
+    pub window: WindowArgs,
+    #[command(flatten)]
+    pub link: LinkArgs,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct DuelJoinArgs {
+    /// Host to join, as HOST or HOST:PORT.
+    pub address: String,
+    #[command(flatten)]
+    pub window: WindowArgs,
+    #[command(flatten)]
+    pub link: LinkArgs,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct DuelTestArgs {
+    /// Seconds of play.
+    #[arg(long, default_value_t = 20.0)]
+    pub seconds: f32,
+    /// Match seed; random when omitted.
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// One-way delay added by each peer, in milliseconds.
+    #[arg(long, default_value_t = 60.0, value_name = "MS", value_parser = parse_millis)]
+    pub latency: f32,
+    /// Extra random delay of up to this many milliseconds.
+    #[arg(long, default_value_t = 30.0, value_name = "MS", value_parser = parse_millis)]
+    pub jitter: f32,
+    /// Fraction of packets each peer drops, 0 to 1.
+    #[arg(long, default_value_t = 0.02, value_name = "FRACTION", value_parser = parse_fraction)]
+    pub loss: f32,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct DuelPeerArgs {
+    /// Host on this port.
+    #[arg(long, conflicts_with = "join", required_unless_present = "join")]
+    pub port: Option<u16>,
+    /// Join the host at this address.
+    #[arg(long)]
+    pub join: Option<String>,
+    /// Match seed when hosting; also varies the scripted input.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Seconds of play.
+    #[arg(long)]
+    pub seconds: f32,
+    #[command(flatten)]
+    pub link: LinkArgs,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct LobbyArgs {
+    /// Name shown to the other players.
+    #[arg(long, default_value = "")]
+    pub name: String,
+    /// Tuning for matches you host. The lobby's own settings decide the
+    /// map seed, round time and team size.
+    #[command(flatten)]
+    pub game: GameArgs,
+    /// UDP port lobbies listen on and are searched for.
+    #[arg(long, default_value_t = DEFAULT_PORT)]
+    pub port: u16,
+    /// Host a lobby straight away instead of searching.
+    #[arg(long, conflicts_with = "join")]
+    pub host: bool,
+    /// Join the lobby at HOST or HOST:PORT straight away.
+    #[arg(long, value_name = "ADDRESS")]
+    pub join: Option<String>,
+    #[command(flatten)]
+    pub window: WindowArgs,
+    #[command(flatten)]
+    pub link: LinkArgs,
+}
+
+/// Simulated network trouble on packets this process sends.
+#[derive(Args, Clone, Copy, Debug)]
+pub struct LinkArgs {
+    /// Extra one-way delay in milliseconds.
+    #[arg(long, default_value_t = 0.0, value_name = "MS", value_parser = parse_millis)]
+    pub latency: f32,
+    /// Extra random delay of up to this many milliseconds.
+    #[arg(long, default_value_t = 0.0, value_name = "MS", value_parser = parse_millis)]
+    pub jitter: f32,
+    /// Fraction of packets to drop, 0 to 1.
+    #[arg(long, default_value_t = 0.0, value_name = "FRACTION", value_parser = parse_fraction)]
+    pub loss: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TeamChoice {
+    #[value(alias = "crimson")]
+    Red,
+    #[value(alias = "azure")]
+    Blue,
+    #[value(alias = "verdant")]
+    Green,
+    #[value(alias = "sandstone")]
+    Beige,
+    #[value(alias = "onyx")]
+    Black,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum MapFormat {
+    /// One character per tile, with obstacles and spawn zones marked.
+    Ascii,
+    /// The full generated `World` as JSON.
+    Json,
+}
+
+impl GameArgs {
+    /// Loads the tuning file and applies the command-line overrides on top.
+    pub fn config(&self) -> io::Result<GameConfig> {
+        let config = match self.config_path() {
+            Some(path) => GameConfig::load(path)?,
+            None => GameConfig::default(),
+        };
+        self.apply_overrides(config)
+    }
+
+    /// Re-applies the command-line overrides, e.g. to a freshly reloaded file.
+    pub fn apply_overrides(&self, mut config: GameConfig) -> io::Result<GameConfig> {
+        if let Some(width) = self.map_width {
+            config.map.width = width;
+        }
+        if let Some(height) = self.map_height {
+            config.map.height = height;
+        }
+        if let Some(team_size) = self.team_size {
+            config.round.tanks_per_team = team_size;
+        }
+        if let Some(teams) = self.teams {
+            config.round.teams = teams;
+        }
+        if self.ffa {
+            config.round.free_for_all = true;
+        }
+        if let Some(mode) = self.mode {
+            config.round.mode = mode;
+        }
+        if let Some(round_time) = self.round_time {
+            config.round.time = round_time;
+        }
+        if let Some(difficulty) = self.difficulty {
+            config.ai.difficulty = difficulty;
+        }
+        if let Some(difficulty) = self.red_difficulty {
+            config.ai.red = TeamSkill {
+                difficulty: Some(difficulty),
+                roster: Vec::new(),
+            };
+        }
+        if let Some(difficulty) = self.blue_difficulty {
+            config.ai.blue = TeamSkill {
+                difficulty: Some(difficulty),
+                roster: Vec::new(),
+            };
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// The tuning file in use: `--config`, or `tanks.toml` when it exists.
+    pub fn config_path(&self) -> Option<&Path> {
+        match &self.config {
+            Some(path) => Some(path),
+            None => Some(Path::new(DEFAULT_CONFIG_PATH)).filter(|path| path.exists()),
+        }
+    }
+}
+
+impl ServeArgs {
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.bind, self.port)
+    }
+}
+
+impl ConnectArgs {
+    /// The server address, with the default port added when none is given.
+    pub fn address(&self) -> String {
+        with_default_port(&self.address)
+    }
+}
+
+impl DuelJoinArgs {
+    /// The host address, with the default port added when none is given.
+    pub fn address(&self) -> String {
+        with_default_port(&self.address)
+    }
+}
+
+impl LobbyArgs {
+    /// The `--join` address, with the default port added when none is
+    /// given.
+    pub fn join_address(&self) -> Option<String> {
+        self.join.as_deref().map(with_default_port)
+    }
+}
+
+fn with_default_port(address: &str) -> String {
+    if let Ok(ip) = address.parse::<IpAddr>() {
+        return SocketAddr::new(ip, DEFAULT_PORT).to_string();
+    }
+    let has_port = address
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+    if has_port {
+        address.to_string()
+    } else {
+        format!("{address}:{DEFAULT_PORT}")
+    }
+}
+
+/// A delay in milliseconds, zero or more.
+fn parse_millis(value: &str) -> Result<f32, String> {
+    let millis: f32 = value
+        .parse()
+        .map_err(|_| format!("`{value}` is not a number"))?;
+    if millis.is_finite() && millis >= 0.0 {
+        Ok(millis)
+    } else {
+        Err(format!(
+            "{value} is not a delay (expected 0 or more milliseconds)"
+        ))
+    }
+}
+
+/// A fraction from 0 to 1.
+fn parse_fraction(value: &str) -> Result<f32, String> {
+    let fraction: f32 = value
+        .parse()
+        .map_err(|_| format!("`{value}` is not a number"))?;
+    if (0.0..=1.0).contains(&fraction) {
+        Ok(fraction)
+    } else {
+        Err(format!("{value} is out of range (expected 0 to 1)"))
+    }
+}
+
+impl LinkArgs {
+    pub fn conditions(&self) -> LinkConditions {
+        LinkConditions {
+            latency: self.latency / 1000.0,
+            jitter: self.jitter / 1000.0,
+            loss: self.loss,
+        }
+    }
+}
+
+impl TeamChoice {
+    pub fn team(self) -> Team {
+        match self {
+            TeamChoice::Red => Team::Red,
+            TeamChoice::Blue => Team::Blue,
+            TeamChoice::Green => Team::Green,
+            TeamChoice::Beige => Team::Beige,
+            TeamChoice::Black => Team::Black,
+        }
+    }
+}
@@ -0,0 +1,24 @@
+mod skill;
+mod tuning;
+mod watch;
+
+pub use skill::{Difficulty, SkillProfile, TeamSkill};
+#[cfg(test)]
+pub(crate) use tuning::MIN_MULTI_TEAM_MAP;
+pub use tuning::{
+    AiConfig, BulletConfig, FlagConfig, GameConfig, GameMode, MapConfig, MapSide, PowerupConfig,
+    RoundConfig, TankConfig,
+};
+pub use watch::ConfigWatcher;
+
+pub const WINDOW_WIDTH: i32 = 1280;
+pub const WINDOW_HEIGHT: i32 = 720;
+pub const TICK_RATE: u32 = 60;
+pub const TICK_DT: f32 = 1.0 / TICK_RATE as f32;
+pub const MAX_FRAME_TIME: f32 = 0.25;
+pub const TILE_SIZE: f32 = 128.0;
+pub const TANK_RADIUS: f32 = 28.0;
+pub const BULLET_RADIUS: f32 = 6.0;
+pub const TRACK_LIFE: f32 = 8.0;
+pub const DEFAULT_CONFIG_PATH: &str = "tanks.toml";
+pub const MAX_TANKS_PER_TEAM: usize = 16;
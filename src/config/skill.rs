@@ -0,0 +1,161 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Bot skill presets, from forgiving to punishing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Recruit,
+    #[default]
+    Veteran,
+    Ace,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Recruit, Difficulty::Veteran, Difficulty::Ace];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Recruit => "recruit",
+            Difficulty::Veteran => "veteran",
+            Difficulty::Ace => "ace",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(value))
+            .ok_or_else(|| {
+                format!("unknown difficulty `{value}` (expected recruit, veteran or ace)")
+            })
+    }
+}
+
+/// How one difficulty plays.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SkillProfile {
+    /// Seconds before reacting to a newly acquired or newly visible target.
+    pub reaction_time: f32,
+    /// Largest random turret offset per shot, in radians.
+    pub aim_error: f32,
+    /// How close the turret must be to the aim point before firing, in radians.
+    pub aim_tolerance: f32,
+    /// Multiplier on `tank.turret_rot_speed`.
+    pub turret_speed: f32,
+    /// 0 keeps its distance and retreats when hurt, 1 presses in close.
+    pub aggression: f32,
+    /// 0 aims at where the target is, 1 at where it will be when the shot
+    /// arrives.
+    pub leading: f32,
+}
+
+impl SkillProfile {
+    pub fn recruit() -> Self {
+        Self {
+            reaction_time: 0.6,
+            aim_error: 0.12,
+            aim_tolerance: 0.3,
+            turret_speed: 0.6,
+            aggression: 0.3,
+            leading: 0.2,
+        }
+    }
+
+    pub fn veteran() -> Self {
+        Self {
+            reaction_time: 0.3,
+            aim_error: 0.05,
+            aim_tolerance: 0.22,
+            turret_speed: 1.0,
+            aggression: 0.5,
+            leading: 0.6,
+        }
+    }
+
+    pub fn ace() -> Self {
+        Self {
+            reaction_time: 0.12,
+            aim_error: 0.015,
+            aim_tolerance: 0.12,
+            turret_speed: 1.4,
+            aggression: 0.85,
+            leading: 1.0,
+        }
+    }
+
+    /// Scale applied to the AI engagement distances.
+    pub fn range_scale(&self) -> f32 {
+        1.5 - self.aggression
+    }
+
+    /// Health fraction below which the bot backs away from its target.
+    pub fn retreat_health(&self) -> f32 {
+        (0.5 - self.aggression).max(0.0)
+    }
+}
+
+/// A `[ai.<preset>]` section as written. Anything left out keeps the value
+/// of the preset the section belongs to, not of some other preset.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileOverrides {
+    reaction_time: Option<f32>,
+    aim_error: Option<f32>,
+    aim_tolerance: Option<f32>,
+    turret_speed: Option<f32>,
+    aggression: Option<f32>,
+    leading: Option<f32>,
+}
+
+impl ProfileOverrides {
+    fn apply(self, preset: SkillProfile) -> SkillProfile {
+        SkillProfile {
+            reaction_time: self.reaction_time.unwrap_or(preset.reaction_time),
+            aim_error: self.aim_error.unwrap_or(preset.aim_error),
+            aim_tolerance: self.aim_tolerance.unwrap_or(preset.aim_tolerance),
+            turret_speed: self.turret_speed.unwrap_or(preset.turret_speed),
+            aggression: self.aggression.unwrap_or(preset.aggression),
+            leading: self.leading.unwrap_or(preset.leading),
+        }
+    }
+}
+
+pub(super) fn recruit_profile<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SkillProfile, D::Error> {
+    Ok(ProfileOverrides::deserialize(deserializer)?.apply(SkillProfile::recruit()))
+}
+
+pub(super) fn veteran_profile<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SkillProfile, D::Error> {
+    Ok(ProfileOverrides::deserialize(deserializer)?.apply(SkillProfile::veteran()))
+}
+
+pub(super) fn ace_profile<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SkillProfile, D::Error> {
+    Ok(ProfileOverrides::deserialize(deserializer)?.apply(SkillProfile::ace()))
+}
+
+/// Per-team skill overrides. `roster` assigns difficulties to the team's
+/// tanks in spawn order; tanks past its end use `difficulty`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TeamSkill {
+    pub difficulty: Option<Difficulty>,
+    pub roster: Vec<Difficulty>,
+}
@@ -0,0 +1,663 @@
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::entities::Team;
+
+use super::skill::{ace_profile, recruit_profile, veteran_profile};
+use super::{Difficulty, MAX_TANKS_PER_TEAM, SkillProfile, TeamSkill};
+
+/// Smallest map, in tiles, with room for the spawn zones of three or more
+/// teams.
+pub(crate) const MIN_MULTI_TEAM_MAP: (i32, i32) = (36, 24);
+
+/// Gameplay tuning loaded from a TOML file. Every section and field is
+/// optional; anything left out keeps the built-in default.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub round: RoundConfig,
+    pub map: MapConfig,
+    pub tank: TankConfig,
+    pub bullet: BulletConfig,
+    pub powerup: PowerupConfig,
+    pub flag: FlagConfig,
+    pub ai: AiConfig,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoundConfig {
+    pub time: f32,
+    pub countdown: f32,
+    pub intro_time: f32,
+    pub tanks_per_team: usize,
+    pub respawn_time: f32,
+    /// How many teams play, from 2 to 5. Crimson and Azure come first,
+    /// then Verdant, Sandstone and Onyx.
+    pub teams: usize,
+    /// Every tank is its own side: `teams` tanks, one per team, and
+    /// `tanks_per_team` is ignored. Each tank needs a colour and spawn zone
+    /// of its own, so a free-for-all has at most five tanks.
+    pub free_for_all: bool,
+    /// How rounds are scored and won.
+    pub mode: GameMode,
+}
+
+impl Default for RoundConfig {
+    fn default() -> Self {
+        Self {
+            time: 120.0,
+            countdown: 3.0,
+            intro_time: 4.0,
+            tanks_per_team: 4,
+            respawn_time: 3.0,
+            teams: 2,
+            free_for_all: false,
+            mode: GameMode::Deathmatch,
+        }
+    }
+}
+
+impl RoundConfig {
+    /// The teams that deploy, in spawn order.
+    pub fn playing_teams(&self) -> &'static [Team] {
+        &Team::ALL[..self.teams.clamp(2, Team::ALL.len())]
+    }
+
+    /// Tanks each team fields: one apiece in a free-for-all.
+    pub fn team_size(&self) -> usize {
+        if self.free_for_all {
+            1
+        } else {
+            self.tanks_per_team
+        }
+    }
+}
+
+/// Map size in tiles and which side Crimson deploys on in a two-team
+/// round.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MapConfig {
+    pub width: i32,
+    pub height: i32,
+    pub red_side: MapSide,
+}
+
+impl Default for MapConfig {
+    fn default() -> Self {
+        Self {
+            width: 50,
+            height: 30,
+            red_side: MapSide::Left,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MapSide {
+    #[default]
+    Left,
+    Right,
+}
+
+impl MapConfig {
+    /// Most teams whose spawn zones fit on the map.
+    pub fn max_teams(&self) -> usize {
+        let (min_width, min_height) = MIN_MULTI_TEAM_MAP;
+        if self.width < min_width || self.height < min_height {
+            2
+        } else {
+            Team::ALL.len()
+        }
+    }
+}
+
+impl MapSide {
+    pub fn opposite(self) -> MapSide {
+        match self {
+            MapSide::Left => MapSide::Right,
+            MapSide::Right => MapSide::Left,
+        }
+    }
+}
+
+/// The rules a round is played under.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Most kills when the clock runs out wins.
+    #[default]
+    #[serde(rename = "deathmatch")]
+    Deathmatch,
+    /// Most enemy flags brought home wins, early once a team reaches
+    /// `flag.captures_to_win`.
+    #[serde(rename = "ctf")]
+    CaptureTheFlag,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 2] = [GameMode::Deathmatch, GameMode::CaptureTheFlag];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Deathmatch => "deathmatch",
+            GameMode::CaptureTheFlag => "ctf",
+        }
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        GameMode::ALL
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(value))
+            .ok_or_else(|| format!("unknown mode `{value}` (expected deathmatch or ctf)"))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TankConfig {
+    pub speed: f32,
+    pub body_rot_speed: f32,
+    pub turret_rot_speed: f32,
+    pub fire_cooldown: f32,
+    pub max_health: f32,
+    pub health_flash_time: f32,
+}
+
+impl Default for TankConfig {
+    fn default() -> Self {
+        Self {
+            speed: 130.0,
+            body_rot_speed: 2.6,
+            turret_rot_speed: 3.4,
+            fire_cooldown: 1.1,
+            max_health: 100.0,
+            health_flash_time: 5.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BulletConfig {
+    pub speed: f32,
+    pub life: f32,
+    pub damage: f32,
+}
+
+impl Default for BulletConfig {
+    fn default() -> Self {
+        Self {
+            speed: 520.0,
+            life: 2.2,
+            damage: 25.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PowerupConfig {
+    pub max_count: usize,
+    pub base_spawn: f32,
+    pub min_spawn: f32,
+    pub max_spawn: f32,
+    pub duration: f32,
+}
+
+impl Default for PowerupConfig {
+    fn default() -> Self {
+        Self {
+            max_count: 3,
+            base_spawn: 10.0,
+            min_spawn: 5.0,
+            max_spawn: 14.0,
+            duration: 20.0,
+        }
+    }
+}
+
+/// Capture-the-flag rules.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FlagConfig {
+    /// Seconds a dropped flag lies on the ground before going home.
+    pub return_time: f32,
+    /// Captures that end the round early; 0 plays to the clock.
+    pub captures_to_win: u32,
+}
+
+impl Default for FlagConfig {
+    fn default() -> Self {
+        Self {
+            return_time: 15.0,
+            captures_to_win: 3,
+        }
+    }
+}
+
+/// Distances the AI uses to pick between closing in, circling, backing off
+/// and opening fire, plus the skill each bot plays at.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AiConfig {
+    pub target_far: f32,
+    pub target_near: f32,
+    pub fire_range: f32,
+    /// Skill of every bot without a team or roster override.
+    pub difficulty: Difficulty,
+    pub red: TeamSkill,
+    pub blue: TeamSkill,
+    pub green: TeamSkill,
+    pub beige: TeamSkill,
+    pub black: TeamSkill,
+    #[serde(deserialize_with = "recruit_profile")]
+    pub recruit: SkillProfile,
+    #[serde(deserialize_with = "veteran_profile")]
+    pub veteran: SkillProfile,
+    #[serde(deserialize_with = "ace_profile")]
+    pub ace: SkillProfile,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            target_far: 260.0,
+            target_near: 180.0,
+            fire_range: 900.0,
+            difficulty: Difficulty::Veteran,
+            red: TeamSkill::default(),
+            blue: TeamSkill::default(),
+            green: TeamSkill::default(),
+            beige: TeamSkill::default(),
+            black: TeamSkill::default(),
+            recruit: SkillProfile::recruit(),
+            veteran: SkillProfile::veteran(),
+            ace: SkillProfile::ace(),
+        }
+    }
+}
+
+impl AiConfig {
+    pub fn profile(&self, difficulty: Difficulty) -> &SkillProfile {
+        match difficulty {
+            Difficulty::Recruit => &self.recruit,
+            Difficulty::Veteran => &self.veteran,
+            Difficulty::Ace => &self.ace,
+        }
+    }
+
+    pub fn team_skill(&self, team: Team) -> &TeamSkill {
+        match team {
+            Team::Red => &self.red,
+            Team::Blue => &self.blue,
+            Team::Green => &self.green,
+            Team::Beige => &self.beige,
+            Team::Black => &self.black,
+        }
+    }
+
+    /// Difficulty for the `slot`-th tank spawned on `team`.
+    pub fn difficulty_for(&self, team: Team, slot: usize) -> Difficulty {
+        let skill = self.team_skill(team);
+        skill
+            .roster
+            .get(slot)
+            .copied()
+            .or(skill.difficulty)
+            .unwrap_or(self.difficulty)
+    }
+}
+
+impl GameConfig {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        Self::from_toml(&text)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))
+    }
+
+    pub fn from_toml(text: &str) -> io::Result<Self> {
+        let config: Self = toml::from_str(text)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("config is always representable as TOML")
+    }
+
+    /// Looks up a setting by its dotted path, e.g. `round.time`.
+    pub fn value(&self, key: &str) -> Option<toml::Value> {
+        let mut value = toml::Value::try_from(self).ok()?;
+        for part in key.split('.') {
+            value = value.get(part)?.clone();
+        }
+        Some(value)
+    }
+
+    /// Returns a copy with the setting at dotted path `key` replaced by
+    /// `value`, written as in the TOML file (a bare word counts as a
+    /// string). The result is validated like a loaded file.
+    pub fn with_value(&self, key: &str, value: &str) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+        let new = toml::from_str::<toml::Table>(&format!("value = {value}"))
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(value.to_string()));
+
+        let mut root = toml::Value::try_from(self).map_err(|err| invalid(err.to_string()))?;
+        let (parents, last) = match key.rsplit_once('.') {
+            Some((parents, last)) => (Some(parents), last),
+            None => (None, key),
+        };
+        let mut table = &mut root;
+        for part in parents.into_iter().flat_map(|parents| parents.split('.')) {
+            table = table
+                .get_mut(part)
+                .ok_or_else(|| invalid(format!("unknown setting `{key}`")))?;
+        }
+        let slot = table
+            .get_mut(last)
+            .ok_or_else(|| invalid(format!("unknown setting `{key}`")))?;
+        if slot.is_table() {
+            return Err(invalid(format!("`{key}` is a section, not a setting")));
+        }
+        *slot = new;
+
+        let config: Self = root
+            .try_into()
+            .map_err(|err: toml::de::Error| invalid(format!("{key}: {}", err.message())))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks every value against its allowed range and reports all
+    /// problems at once.
+    pub fn validate(&self) -> io::Result<()> {
+        let mut check = Checker::default();
+
+        let round = &self.round;
+        check.positive("round.time", round.time);
+        check.non_negative("round.countdown", round.countdown);
+        check.non_negative("round.intro_time", round.intro_time);
+        check.range(
+            "round.tanks_per_team",
+            round.tanks_per_team,
+            1,
+            MAX_TANKS_PER_TEAM,
+        );
+        check.non_negative("round.respawn_time", round.respawn_time);
+        check.range("round.teams", round.teams, 2, Team::ALL.len());
+
+        check.range("map.width", self.map.width, 24, 256);
+        check.range("map.height", self.map.height, 12, 256);
+        if round.teams > self.map.max_teams() {
+            let (min_width, min_height) = MIN_MULTI_TEAM_MAP;
+            check.errors.push(format!(
+                "more than two teams need a map of at least {min_width}x{min_height} tiles (got {}x{})",
+                self.map.width, self.map.height
+            ));
+        }
+
+        let tank = &self.tank;
+        check.range("tank.speed", tank.speed, 20.0, 1000.0);
+        check.positive("tank.body_rot_speed", tank.body_rot_speed);
+        check.positive("tank.turret_rot_speed", tank.turret_rot_speed);
+        check.positive("tank.fire_cooldown", tank.fire_cooldown);
+        check.positive("tank.max_health", tank.max_health);
+        check.non_negative("tank.health_flash_time", tank.health_flash_time);
+
+        check.positive("bullet.speed", self.bullet.speed);
+        check.positive("bullet.life", self.bullet.life);
+        check.positive("bullet.damage", self.bullet.damage);
+
+        let powerup = &self.powerup;
+        check.range("powerup.max_count", powerup.max_count, 0, 16);
+        check.positive("powerup.base_spawn", powerup.base_spawn);
+        check.positive("powerup.min_spawn", powerup.min_spawn);
+        check.positive("powerup.max_spawn", powerup.max_spawn);
+        check.positive("powerup.duration", powerup.duration);
+        check.ordered(
+            "powerup.min_spawn",
+            powerup.min_spawn,
+            "powerup.max_spawn",
+            powerup.max_spawn,
+        );
+
+        check.positive("flag.return_time", self.flag.return_time);
+        check.range("flag.captures_to_win", self.flag.captures_to_win, 0, 99);
+
+        let ai = &self.ai;
+        check.non_negative("ai.target_near", ai.target_near);
+        check.positive("ai.target_far", ai.target_far);
+        check.positive("ai.fire_range", ai.fire_range);
+        check.ordered(
+            "ai.target_near",
+            ai.target_near,
+            "ai.target_far",
+            ai.target_far,
+        );
+        for difficulty in Difficulty::ALL {
+            let profile = ai.profile(difficulty);
+            let name = |field: &str| format!("ai.{difficulty}.{field}");
+            check.non_negative(&name("reaction_time"), profile.reaction_time);
+            check.range(&name("aim_error"), profile.aim_error, 0.0, 1.0);
+            check.positive(&name("aim_tolerance"), profile.aim_tolerance);
+            check.positive(&name("turret_speed"), profile.turret_speed);
+            check.range(&name("aggression"), profile.aggression, 0.0, 1.0);
+            check.range(&name("leading"), profile.leading, 0.0, 1.0);
+        }
+
+        check.finish()
+    }
+}
+
+#[derive(Default)]
+struct Checker {
+    errors: Vec<String>,
+}
+
+impl Checker {
+    fn positive(&mut self, name: &str, value: f32) {
+        if !(value.is_finite() && value > 0.0) {
+            self.errors
+                .push(format!("{name} must be greater than 0 (got {value})"));
+        }
+    }
+
+    fn non_negative(&mut self, name: &str, value: f32) {
+        if !(value.is_finite() && value >= 0.0) {
+            self.errors
+                .push(format!("{name} must be 0 or greater (got {value})"));
+        }
+    }
+
+    fn range<T: PartialOrd + Display>(&mut self, name: &str, value: T, min: T, max: T) {
+        if !(value >= min && value <= max) {
+            self.errors.push(format!(
+                "{name} must be between {min} and {max} (got {value})"
+            ));
+        }
+    }
+
+    fn ordered(&mut self, low_name: &str, low: f32, high_name: &str, high: f32) {
+        if low > high {
+            self.errors.push(format!(
+                "{low_name} ({low}) must not be greater than {high_name} ({high})"
+            ));
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid config: {}", self.errors.join("; ")),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_shipped_tuning_file_loads_as_the_defaults() {
+        let config = GameConfig::from_toml(include_str!("../../tanks.toml")).unwrap();
+        assert_eq!(config, GameConfig::default());
+    }
+
+    #[test]
+    fn missing_values_keep_their_defaults() {
+        let config = GameConfig::from_toml("[round]\ntime = 60.0\n").unwrap();
+        assert_eq!(config.round.time, 60.0);
+        assert_eq!(config.round.countdown, RoundConfig::default().countdown);
+        assert_eq!(config.tank, TankConfig::default());
+    }
+
+    #[test]
+    fn a_partial_skill_preset_keeps_its_own_defaults() {
+        let config = GameConfig::from_toml("[ai.recruit]\naim_error = 0.2\n").unwrap();
+        assert_eq!(
+            config.ai.recruit,
+            SkillProfile {
+                aim_error: 0.2,
+                ..SkillProfile::recruit()
+            }
+        );
+        assert_eq!(config.ai.recruit.reaction_time, 0.6);
+        assert_eq!(config.ai.recruit.aim_tolerance, 0.3);
+        assert_eq!(config.ai.recruit.turret_speed, 0.6);
+        assert_eq!(config.ai.recruit.aggression, 0.3);
+        assert_eq!(config.ai.recruit.leading, 0.2);
+
+        let config = GameConfig::from_toml("[ai.ace]\nleading = 0.5\n").unwrap();
+        assert_eq!(
+            config.ai.ace.reaction_time,
+            SkillProfile::ace().reaction_time
+        );
+        assert_eq!(config.ai.ace.leading, 0.5);
+        assert_eq!(config.ai.veteran, SkillProfile::veteran());
+
+        assert!(GameConfig::from_toml("[ai.ace]\nlead = 0.5\n").is_err());
+
+        let changed = GameConfig::default()
+            .with_value("ai.recruit.aim_error", "0.2")
+            .unwrap();
+        assert_eq!(changed.ai.recruit.aim_error, 0.2);
+        assert_eq!(changed.ai.recruit.leading, 0.2);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let err = GameConfig::from_toml("[round]\nrespawn_tme = 2.0\n").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("respawn_tme"), "{err}");
+        assert!(GameConfig::from_toml("[rounds]\ntime = 60.0\n").is_err());
+    }
+
+    #[test]
+    fn out_of_range_values_are_all_reported() {
+        let err = GameConfig::from_toml(
+            "[round]\nrespawn_time = -1.0\nteams = 9\n[powerup]\nmin_spawn = 20.0\n",
+        )
+        .unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("round.respawn_time must be 0 or greater"),
+            "{message}"
+        );
+        assert!(
+            message.contains("round.teams must be between 2 and 5"),
+            "{message}"
+        );
+        assert!(
+            message.contains("powerup.min_spawn (20) must not be greater than powerup.max_spawn"),
+            "{message}"
+        );
+
+        let mut config = GameConfig::default();
+        config.bullet.damage = f32::NAN;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn more_teams_need_a_bigger_map() {
+        let small = "[round]\nteams = 3\n[map]\nwidth = 30\nheight = 20\n";
+        let err = GameConfig::from_toml(small).unwrap_err();
+        assert!(err.to_string().contains("at least 36x24"), "{err}");
+        assert!(GameConfig::from_toml("[round]\nteams = 3\n").is_ok());
+    }
+
+    #[test]
+    fn settings_round_trip_by_dotted_key() {
+        let config = GameConfig::default();
+        let changed = config.with_value("round.time", "45").unwrap();
+        assert_eq!(changed.round.time, 45.0);
+        assert_eq!(changed.value("round.time"), Some(toml::Value::Float(45.0)));
+
+        let changed = changed.with_value("round.mode", "ctf").unwrap();
+        assert_eq!(changed.round.mode, GameMode::CaptureTheFlag);
+        assert_eq!(
+            changed.value("round.mode"),
+            Some(toml::Value::String("ctf".to_string()))
+        );
+
+        let same = config
+            .with_value(
+                "tank.speed",
+                &config.value("tank.speed").unwrap().to_string(),
+            )
+            .unwrap();
+        assert_eq!(same, config);
+    }
+
+    #[test]
+    fn bad_settings_are_refused_by_key() {
+        let config = GameConfig::default();
+        assert!(config.value("round.nope").is_none());
+        let unknown = config.with_value("round.nope", "1").unwrap_err();
+        assert!(unknown.to_string().contains("unknown setting"), "{unknown}");
+        let section = config.with_value("round", "1").unwrap_err();
+        assert!(section.to_string().contains("is a section"), "{section}");
+        assert!(config.with_value("round.time", "-5").is_err());
+        assert!(config.with_value("round.time", "soon").is_err());
+    }
+
+    #[test]
+    fn playing_teams_stay_between_two_and_five() {
+        let teams = |teams| {
+            RoundConfig {
+                teams,
+                ..RoundConfig::default()
+            }
+            .playing_teams()
+            .to_vec()
+        };
+        assert_eq!(teams(0), [Team::Red, Team::Blue]);
+        assert_eq!(teams(2), [Team::Red, Team::Blue]);
+        assert_eq!(teams(3), [Team::Red, Team::Blue, Team::Green]);
+        assert_eq!(teams(5), Team::ALL);
+        assert_eq!(teams(12), Team::ALL);
+    }
+}
@@ -0,0 +1,266 @@
+use std::io;
+use std::net::UdpSocket;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use tanks::assets::Assets;
+use tanks::config::{TICK_DT, TICK_RATE};
+use tanks::game::{CameraView, Game, PlayerInput};
+use tanks::math::GameRng;
+use tanks::net::{DuelInput, DuelSession, DuelStats, DuelStatus};
+
+use crate::cli::{DuelArgs, DuelCommand, DuelPeerArgs, DuelTestArgs, WindowArgs};
+use crate::{open_window, system_seed};
+
+/// What a `duel peer` process reports when it finishes, one JSON line on
+/// stdout.
+#[derive(Serialize, Deserialize)]
+struct PeerReport {
+    status: String,
+    stats: DuelStats,
+    checksums: Vec<(u32, u64)>,
+}
+
+pub fn run_duel(args: DuelArgs) -> io::Result<()> {
+    match args.command {
+        DuelCommand::Host(args) => {
+            let mut config = args.game.config()?;
+            if args.game.team_size.is_none() {
+                config.round.tanks_per_team = 1;
+            }
+            let seed = args.game.seed.unwrap_or_else(system_seed);
+            let session =
+                DuelSession::host(("0.0.0.0", args.port), seed, config, args.link.conditions())?;
+            let waiting = format!("Waiting for an opponent on port {}...", args.port);
+            play(session, args.window, &waiting)
+        }
+        DuelCommand::Join(args) => {
+            let address = args.address();
+            let session = DuelSession::join(&address, args.link.conditions())?;
+            play(session, args.window, &format!("Joining {address}..."))
+        }
+        DuelCommand::Test(args) => run_test(args),
+        DuelCommand::Peer(args) => run_peer(args),
+    }
+}
+
+fn play(mut session: DuelSession, window: WindowArgs, waiting: &str) -> io::Result<()> {
+    let (mut rl, thread) = open_window(window);
+    let assets = Assets::load(&mut rl, &thread);
+    while !rl.window_should_close() {
+        let screen_width = rl.get_screen_width();
+        let screen_height = rl.get_screen_height();
+        let sampled = session
+            .game_mut()
+            .sample_input(&rl, screen_width, screen_height);
+        let input = DuelInput {
+            input: sampled
+                .tanks
+                .first()
+                .map_or(PlayerInput::default(), |tank| tank.input),
+            start: sampled.start_pressed,
+        };
+        session.update(rl.get_frame_time(), input)?;
+
+        let mut d = rl.begin_drawing(&thread);
+        let notice = match session.status() {
+            DuelStatus::Running => None,
+            DuelStatus::Waiting => Some(waiting.to_string()),
+            DuelStatus::Desynced { frame } => Some(format!("Out of sync at frame {frame}")),
+            DuelStatus::Disconnected(reason) => Some(format!("Disconnected: {reason}")),
+        };
+        match notice {
+            Some(notice) => Game::draw_notice(&mut d, &notice, screen_width, screen_height),
+            None => session.game().draw(
+                &mut d,
+                &assets,
+                screen_width,
+                screen_height,
+                session.blend(),
+                CameraView::Player,
+            ),
+        }
+    }
+    session.disconnect();
+    Ok(())
+}
+
+/// Starts a host and a guest peer as child processes, lets them play, and
+/// compares the checksums of the frames both confirmed.
+fn run_test(args: DuelTestArgs) -> io::Result<()> {
+    let seed = args.seed.unwrap_or_else(system_seed);
+    // Borrow a free port from the OS for the host.
+    let port = UdpSocket::bind("127.0.0.1:0")?.local_addr()?.port();
+    let exe = std::env::current_exe()?;
+    let common = [
+        "--seconds".to_string(),
+        args.seconds.to_string(),
+        "--latency".to_string(),
+        args.latency.to_string(),
+        "--jitter".to_string(),
+        args.jitter.to_string(),
+        "--loss".to_string(),
+        args.loss.to_string(),
+    ];
+    let spawn = |role: &[String]| {
+        Command::new(&exe)
+            .args(["duel", "peer"])
+            .args(role)
+            .args(&common)
+            .stdout(Stdio::piped())
+            .spawn()
+    };
+    println!(
+        "seed {seed}, {}ms +{}ms latency, {}% loss each way, {}s",
+        args.latency,
+        args.jitter,
+        args.loss * 100.0,
+        args.seconds
+    );
+    let host = spawn(&[
+        "--port".to_string(),
+        port.to_string(),
+        "--seed".to_string(),
+        seed.to_string(),
+    ])?;
+    let guest = spawn(&["--join".to_string(), format!("127.0.0.1:{port}")])?;
+
+    let mut reports = Vec::new();
+    for (name, child) in [("host", host), ("guest", guest)] {
+        let output = child.wait_with_output()?;
+        let report = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .last()
+            .and_then(|line| serde_json::from_str::<PeerReport>(line).ok())
+            .ok_or_else(|| io::Error::other(format!("the {name} peer gave no report")))?;
+        let stats = &report.stats;
+        println!(
+            "{name}: {} frames, {} rollbacks ({} frames resimulated, deepest {}), {} stalls, {} skipped, {}",
+            stats.frames,
+            stats.rollbacks,
+            stats.resimulated,
+            stats.max_depth,
+            stats.stalls,
+            stats.skipped,
+            report.status
+        );
+        reports.push(report);
+    }
+
+    let compared: Vec<_> = reports[0]
+        .checksums
+        .iter()
+        .filter_map(|(frame, ours)| {
+            let theirs = reports[1]
+                .checksums
+                .iter()
+                .find(|(other, _)| other == frame)?;
+            Some((*frame, *ours == theirs.1))
+        })
+        .collect();
+    let mismatch = compared.iter().find(|(_, same)| !same);
+    println!("{} confirmed frames compared", compared.len());
+    match mismatch {
+        Some((frame, _)) => Err(io::Error::other(format!(
+            "desync: the peers disagree at frame {frame}"
+        ))),
+        None if compared.is_empty() => Err(io::Error::other(
+            "the peers never confirmed a frame in common",
+        )),
+        None => {
+            println!("in sync");
+            Ok(())
+        }
+    }
+}
+
+/// A headless peer that plays random but repeatable input in real time,
+/// then prints a [`PeerReport`].
+fn run_peer(args: DuelPeerArgs) -> io::Result<()> {
+    let conditions = args.link.conditions();
+    let mut session = match (&args.join, args.port) {
+        (Some(host), _) => DuelSession::join(host, conditions)?,
+        (None, Some(port)) => DuelSession::host(
+            ("127.0.0.1", port),
+            args.seed,
+            duel_test_config(),
+            conditions,
+        )?,
+        (None, None) => unreachable!("clap requires --port or --join"),
+    };
+    let mut script = ScriptedInput::new(args.seed ^ session.team() as u64);
+    let frames = (args.seconds * TICK_RATE as f32) as u32;
+    let started = Instant::now();
+    let mut last = Instant::now();
+    while session.frame() < frames {
+        let now = Instant::now();
+        let frame_dt = (now - last).as_secs_f32();
+        last = now;
+        session.update(frame_dt, script.next())?;
+        match session.status() {
+            DuelStatus::Running => {}
+            DuelStatus::Waiting if started.elapsed().as_secs_f32() < 10.0 => {}
+            _ => break,
+        }
+        thread::sleep(Duration::from_secs_f32(TICK_DT / 2.0));
+    }
+    // Give the peer a moment to confirm our last frames before leaving.
+    let linger = Instant::now();
+    while linger.elapsed() < Duration::from_millis(500) && session.status() == &DuelStatus::Running
+    {
+        session.update(0.0, script.next())?;
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    let report = PeerReport {
+        status: format!("{:?}", session.status()),
+        stats: session.stats(),
+        checksums: session.checksums().collect(),
+    };
+    session.disconnect();
+    println!("{}", serde_json::to_string(&report)?);
+    Ok(())
+}
+
+fn duel_test_config() -> tanks::config::GameConfig {
+    let mut config = tanks::config::GameConfig::default();
+    config.round.tanks_per_team = 1;
+    config
+}
+
+/// Held, randomly changing controls, the same on every run for a seed.
+struct ScriptedInput {
+    rng: GameRng,
+    current: DuelInput,
+    hold: u32,
+}
+
+impl ScriptedInput {
+    fn new(seed: u64) -> Self {
+        Self {
+            rng: GameRng::seed_from_u64(seed),
+            current: DuelInput::default(),
+            hold: 0,
+        }
+    }
+
+    fn next(&mut self) -> DuelInput {
+        if self.hold == 0 {
+            self.hold = self.rng.random_range(4..40);
+            self.current = DuelInput {
+                input: PlayerInput {
+                    turn: self.rng.random_range(-1.0..=1.0),
+                    movement: self.rng.random_range(-0.5..=1.0),
+                    aim_angle: Some(self.rng.random_range(0.0..std::f32::consts::TAU)),
+                    wants_fire: self.rng.random_bool(0.4),
+                },
+                start: true,
+            };
+        }
+        self.hold -= 1;
+        self.current
+    }
+}
@@ -1,16 +1,33 @@
-use raylib::prelude::{Color, Vector2};
+use std::str::FromStr;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg(feature = "graphics")]
+use raylib::prelude::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Difficulty;
+use crate::math::{Vector2, lerp_angle, vec2_lerp};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Team {
     Red,
     Blue,
+    Green,
+    Beige,
+    Black,
 }
 
 impl Team {
+    /// Every team, in the order they join a match: a round with `n` teams
+    /// plays the first `n`.
+    pub const ALL: [Team; 5] = [Team::Red, Team::Blue, Team::Green, Team::Beige, Team::Black];
+
     pub fn index(self) -> usize {
         match self {
             Team::Red => 0,
             Team::Blue => 1,
+            Team::Green => 2,
+            Team::Beige => 3,
+            Team::Black => 4,
         }
     }
 
@@ -18,25 +35,82 @@ impl Team {
         match self {
             Team::Red => "Crimson",
             Team::Blue => "Azure",
+            Team::Green => "Verdant",
+            Team::Beige => "Sandstone",
+            Team::Black => "Onyx",
         }
     }
 
+    #[cfg(feature = "graphics")]
     pub fn color(self) -> Color {
         match self {
             Team::Red => Color::new(224, 70, 70, 255),
             Team::Blue => Color::new(70, 140, 232, 255),
+            Team::Green => Color::new(96, 190, 84, 255),
+            Team::Beige => Color::new(222, 196, 138, 255),
+            Team::Black => Color::new(150, 150, 162, 255),
         }
     }
 
-    pub fn enemy(self) -> Team {
-        match self {
-            Team::Red => Team::Blue,
-            Team::Blue => Team::Red,
+    /// The team with strictly the highest count, indexed by
+    /// [`Team::index`], or `None` on a tie for first.
+    pub fn leader(counts: &[u32]) -> Option<Team> {
+        let best = *counts.iter().max()?;
+        let mut leaders = Team::ALL
+            .iter()
+            .zip(counts)
+            .filter(|(_, count)| **count == best);
+        match (leaders.next(), leaders.next()) {
+            (Some((team, _)), None) => Some(*team),
+            _ => None,
+        }
+    }
+
+    /// Team names joined with "or", for error messages.
+    fn choices() -> String {
+        let names: Vec<_> = Team::ALL
+            .iter()
+            .map(|team| format!("{team:?}").to_ascii_lowercase())
+            .collect();
+        format!(
+            "{} or {}",
+            names[..names.len() - 1].join(", "),
+            names[names.len() - 1]
+        )
+    }
+
+    /// Scores indexed by team, e.g. "Crimson 3 - 2 Azure", or
+    /// "Crimson 3, Azure 2, Verdant 4" with more than two teams.
+    pub fn scoreline(scores: &[u32]) -> String {
+        match scores {
+            [red, blue] => format!("Crimson {red} - {blue} Azure"),
+            _ => Team::ALL
+                .iter()
+                .zip(scores)
+                .map(|(team, score)| format!("{} {score}", team.name()))
+                .collect::<Vec<_>>()
+                .join(", "),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+impl FromStr for Team {
+    type Err = String;
+
+    /// Parses a colour or a team name, e.g. `red`, `crimson` or `onyx`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_ascii_lowercase();
+        Team::ALL
+            .into_iter()
+            .find(|team| {
+                format!("{team:?}").eq_ignore_ascii_case(&value)
+                    || team.name().eq_ignore_ascii_case(&value)
+            })
+            .ok_or_else(|| format!("unknown team `{value}` (expected {})", Team::choices()))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SmokeColor {
     Orange,
     Yellow,
@@ -44,17 +118,30 @@ pub enum SmokeColor {
     White,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Tank {
     pub team: Team,
     pub pos: Vector2,
     pub body_angle: f32,
     pub turret_angle: f32,
+    pub prev_pos: Vector2,
+    pub prev_body_angle: f32,
+    pub prev_turret_angle: f32,
+    /// Displacement over the last tick, per second.
+    pub vel: Vector2,
     pub speed: f32,
     pub fire_cooldown: f32,
     pub alive: bool,
     pub respawn_timer: f32,
     pub waypoint: Vector2,
+    /// Remaining A* waypoints, next one second to last; the last is where
+    /// the current leg began.
+    pub path: Vec<Vector2>,
+    /// Goal `path` was planned towards.
+    pub path_goal: Option<Vector2>,
+    /// Time left before the tank tries again to plan a route to a goal that
+    /// had none.
+    pub path_retry: f32,
     pub track_distance: f32,
     pub tread_phase: f32,
     pub health: f32,
@@ -62,40 +149,135 @@ pub struct Tank {
     pub health_flash: f32,
     pub invincible_timer: f32,
     pub rapid_timer: f32,
+    /// Skill the AI plays this tank at; ignored while a human drives it.
+    pub skill: Difficulty,
+    pub ai: AiState,
+}
+
+/// What an AI tank is currently trying to do.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BehaviourKind {
+    #[default]
+    Patrol,
+    Engage,
+    Flank,
+    RetreatToHeal,
+    GrabPowerup,
+    GuardSpawn,
+    Regroup,
+    AttackFlag,
+    EscortCarrier,
+    DefendFlag,
+}
+
+/// Per-bot memory carried between ticks.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AiState {
+    pub behaviour: BehaviourKind,
+    /// Index of the tank being engaged.
+    pub target: Option<usize>,
+    pub target_visible: bool,
+    /// Time left before the bot responds to its current target.
+    pub reaction_timer: f32,
+    /// Random turret offset applied to the next shot.
+    pub aim_offset: f32,
+}
+
+impl Tank {
+    /// Copies the current transform into the `prev_*` fields so rendering can
+    /// blend between the last two simulation ticks.
+    pub fn store_previous(&mut self) {
+        self.prev_pos = self.pos;
+        self.prev_body_angle = self.body_angle;
+        self.prev_turret_angle = self.turret_angle;
+    }
+
+    pub fn render_pos(&self, blend: f32) -> Vector2 {
+        vec2_lerp(self.prev_pos, self.pos, blend)
+    }
+
+    pub fn render_body_angle(&self, blend: f32) -> f32 {
+        lerp_angle(self.prev_body_angle, self.body_angle, blend)
+    }
+
+    pub fn render_turret_angle(&self, blend: f32) -> f32 {
+        lerp_angle(self.prev_turret_angle, self.turret_angle, blend)
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Bullet {
     pub pos: Vector2,
+    pub prev_pos: Vector2,
     pub vel: Vector2,
     pub team: Team,
+    /// Index of the tank that fired it.
+    pub owner: Option<usize>,
     pub life: f32,
 }
 
-#[derive(Clone, Debug)]
+impl Bullet {
+    pub fn render_pos(&self, blend: f32) -> Vector2 {
+        vec2_lerp(self.prev_pos, self.pos, blend)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TrackMark {
     pub pos: Vector2,
     pub rotation: f32,
     pub age: f32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Explosion {
     pub pos: Vector2,
     pub color: SmokeColor,
     pub age: f32,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PowerupKind {
     Invincible,
     RapidRange,
     Heal,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Powerup {
     pub kind: PowerupKind,
     pub pos: Vector2,
     pub age: f32,
 }
+
+/// A team's flag in a capture-the-flag round.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Flag {
+    pub team: Team,
+    /// Where the flag stands at the start of the round and goes back to.
+    pub home: Vector2,
+    pub pos: Vector2,
+    pub state: FlagState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FlagState {
+    Home,
+    /// Carried by the tank at this index.
+    Carried(usize),
+    /// Lying where its carrier was destroyed, until someone picks it up or
+    /// the timer sends it home.
+    Dropped {
+        return_timer: f32,
+    },
+}
+
+impl Flag {
+    /// The tank carrying the flag, if any.
+    pub fn carrier(&self) -> Option<usize> {
+        match self.state {
+            FlagState::Carried(tank) => Some(tank),
+            _ => None,
+        }
+    }
+}
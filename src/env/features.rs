@@ -0,0 +1,180 @@
+use crate::entities::PowerupKind;
+use crate::game::Game;
+use crate::math::{Vector2, vec2_distance, vec2_sub};
+
+const SELF_FEATURES: usize = 13;
+const ROUND_FEATURES: usize = 3;
+const ENEMY_SLOTS: usize = 3;
+const ENEMY_FEATURES: usize = 7;
+const ALLY_SLOTS: usize = 3;
+const ALLY_FEATURES: usize = 4;
+const BULLET_SLOTS: usize = 4;
+const BULLET_FEATURES: usize = 5;
+const POWERUP_SLOTS: usize = 3;
+const POWERUP_FEATURES: usize = 6;
+
+/// Length of the vector built by [`features`]:
+///
+/// - the agent: position over the map size, cos/sin of body and turret
+///   angles, health fraction, fire cooldown fraction, alive, invincible and
+///   rapid timer fractions, velocity over tank speed;
+/// - the round: time elapsed fraction, own and enemy kills / 10;
+/// - the 3 nearest enemies: relative position, velocity, health fraction,
+///   in line of sight, present;
+/// - the 3 nearest teammates: relative position, health fraction, present;
+/// - the 4 nearest enemy bullets: relative position, velocity over bullet
+///   speed, present;
+/// - the 3 nearest powerups: relative position, one-hot kind, present.
+///
+/// Relative positions are divided by the longer map side. Empty slots are
+/// all zeros.
+pub const FEATURE_COUNT: usize = SELF_FEATURES
+    + ROUND_FEATURES
+    + ENEMY_SLOTS * ENEMY_FEATURES
+    + ALLY_SLOTS * ALLY_FEATURES
+    + BULLET_SLOTS * BULLET_FEATURES
+    + POWERUP_SLOTS * POWERUP_FEATURES;
+
+pub(super) fn features(game: &Game, agent: usize) -> Vec<f32> {
+    let config = game.config();
+    let me = &game.tanks()[agent];
+    let bounds = game.world().world_bounds();
+    let extent = bounds.width.max(bounds.height);
+    let relative = |pos: Vector2| {
+        let offset = vec2_sub(pos, me.pos);
+        [offset.x / extent, offset.y / extent]
+    };
+    let mut out = Vec::with_capacity(FEATURE_COUNT);
+
+    out.extend([
+        me.pos.x / bounds.width,
+        me.pos.y / bounds.height,
+        me.body_angle.cos(),
+        me.body_angle.sin(),
+        me.turret_angle.cos(),
+        me.turret_angle.sin(),
+        me.health / me.max_health,
+        me.fire_cooldown / config.tank.fire_cooldown,
+        flag(me.alive),
+        me.invincible_timer / config.powerup.duration,
+        me.rapid_timer / config.powerup.duration,
+        me.vel.x / config.tank.speed,
+        me.vel.y / config.tank.speed,
+    ]);
+
+    // Against several teams, the best of them is the one to beat.
+    let kills = game.team_kills();
+    let own_kills = kills.get(me.team.index()).copied().unwrap_or(0);
+    let enemy_kills = kills
+        .iter()
+        .enumerate()
+        .filter(|(team, _)| *team != me.team.index())
+        .map(|(_, kills)| *kills)
+        .max()
+        .unwrap_or(0);
+    out.extend([
+        1.0 - game.round_timer() / config.round.time,
+        own_kills as f32 * 0.1,
+        enemy_kills as f32 * 0.1,
+    ]);
+
+    let others = || {
+        game.tanks()
+            .iter()
+            .enumerate()
+            .filter(|(index, other)| *index != agent && other.alive)
+            .map(|(_, other)| other)
+    };
+    push_nearest::<ENEMY_FEATURES>(
+        &mut out,
+        ENEMY_SLOTS,
+        others().filter(|other| other.team != me.team).map(|other| {
+            let [x, y] = relative(other.pos);
+            let visible = me.alive && game.world().line_of_sight(me.pos, other.pos, 0.0);
+            (
+                vec2_distance(other.pos, me.pos),
+                [
+                    x,
+                    y,
+                    other.vel.x / config.tank.speed,
+                    other.vel.y / config.tank.speed,
+                    other.health / other.max_health,
+                    flag(visible),
+                    1.0,
+                ],
+            )
+        }),
+    );
+    push_nearest::<ALLY_FEATURES>(
+        &mut out,
+        ALLY_SLOTS,
+        others().filter(|other| other.team == me.team).map(|other| {
+            let [x, y] = relative(other.pos);
+            (
+                vec2_distance(other.pos, me.pos),
+                [x, y, other.health / other.max_health, 1.0],
+            )
+        }),
+    );
+    push_nearest::<BULLET_FEATURES>(
+        &mut out,
+        BULLET_SLOTS,
+        game.bullets()
+            .iter()
+            .filter(|bullet| bullet.team != me.team)
+            .map(|bullet| {
+                let [x, y] = relative(bullet.pos);
+                (
+                    vec2_distance(bullet.pos, me.pos),
+                    [
+                        x,
+                        y,
+                        bullet.vel.x / config.bullet.speed,
+                        bullet.vel.y / config.bullet.speed,
+                        1.0,
+                    ],
+                )
+            }),
+    );
+    push_nearest::<POWERUP_FEATURES>(
+        &mut out,
+        POWERUP_SLOTS,
+        game.powerups().iter().map(|powerup| {
+            let [x, y] = relative(powerup.pos);
+            (
+                vec2_distance(powerup.pos, me.pos),
+                [
+                    x,
+                    y,
+                    flag(powerup.kind == PowerupKind::Invincible),
+                    flag(powerup.kind == PowerupKind::RapidRange),
+                    flag(powerup.kind == PowerupKind::Heal),
+                    1.0,
+                ],
+            )
+        }),
+    );
+
+    debug_assert_eq!(out.len(), FEATURE_COUNT);
+    out
+}
+
+/// Appends the `slots` nearest entries, padding with zeros.
+fn push_nearest<const N: usize>(
+    out: &mut Vec<f32>,
+    slots: usize,
+    entries: impl Iterator<Item = (f32, [f32; N])>,
+) {
+    let mut entries: Vec<_> = entries.collect();
+    entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for slot in 0..slots {
+        match entries.get(slot) {
+            Some((_, values)) => out.extend(values),
+            None => out.extend([0.0; N]),
+        }
+    }
+}
+
+fn flag(value: bool) -> f32 {
+    if value { 1.0 } else { 0.0 }
+}
@@ -0,0 +1,72 @@
+use crate::game::Game;
+use crate::math::Vector2;
+use crate::world::NavGrid;
+
+use super::Observation;
+
+/// Layers of the grid observation, in order: cells the agent cannot drive
+/// through (obstacles and the enemy spawn zone), the agent, living
+/// teammates, living enemies, enemy bullets and powerups.
+pub const GRID_CHANNELS: usize = 6;
+
+const BLOCKED: usize = 0;
+const AGENT: usize = 1;
+const ALLIES: usize = 2;
+const ENEMIES: usize = 3;
+const BULLETS: usize = 4;
+const POWERUPS: usize = 5;
+
+/// The whole map as `[GRID_CHANNELS, height, width]` in navigation cells.
+/// Entity layers count how many of that entity occupy each cell.
+pub(super) fn grid(game: &Game, agent: usize) -> Observation {
+    let me = &game.tanks()[agent];
+    let nav = game.world().nav();
+    let (width, height) = (nav.width() as usize, nav.height() as usize);
+    let mut data = vec![0.0; GRID_CHANNELS * width * height];
+    let layer_size = width * height;
+
+    for y in 0..height {
+        for x in 0..width {
+            if !nav.is_walkable(me.team, x as i32, y as i32) {
+                data[BLOCKED * layer_size + y * width + x] = 1.0;
+            }
+        }
+    }
+
+    let mut mark = |channel: usize, pos: Vector2| {
+        data[channel * layer_size + cell_index(nav, pos)] += 1.0;
+    };
+    for (index, tank) in game.tanks().iter().enumerate() {
+        if !tank.alive {
+            continue;
+        }
+        let channel = if index == agent {
+            AGENT
+        } else if tank.team == me.team {
+            ALLIES
+        } else {
+            ENEMIES
+        };
+        mark(channel, tank.pos);
+    }
+    for bullet in game
+        .bullets()
+        .iter()
+        .filter(|bullet| bullet.team != me.team)
+    {
+        mark(BULLETS, bullet.pos);
+    }
+    for powerup in game.powerups() {
+        mark(POWERUPS, powerup.pos);
+    }
+
+    Observation {
+        shape: vec![GRID_CHANNELS, height, width],
+        data,
+    }
+}
+
+fn cell_index(nav: &NavGrid, pos: Vector2) -> usize {
+    let (x, y) = nav.cell_at(pos);
+    y as usize * nav.width() as usize + x as usize
+}
@@ -0,0 +1,202 @@
+mod features;
+mod grid;
+
+use serde::{Deserialize, Serialize};
+
+use crate::bot::Action;
+use crate::config::{GameConfig, TICK_DT};
+use crate::game::{Game, GameEvent, TankInput, TickInput};
+
+pub use features::FEATURE_COUNT;
+pub use grid::GRID_CHANNELS;
+
+/// How each agent sees the game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObservationMode {
+    /// A flat vector of [`FEATURE_COUNT`] values relative to the agent.
+    #[default]
+    Features,
+    /// The map rasterized at navigation-cell resolution, one layer per
+    /// [`GRID_CHANNELS`].
+    Grid,
+}
+
+/// Reward for each event, summed per agent over a step.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RewardConfig {
+    pub kill: f32,
+    /// Per point of damage the agent's bullets deal.
+    pub damage_dealt: f32,
+    pub death: f32,
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        Self {
+            kill: 1.0,
+            damage_dealt: 0.01,
+            death: -1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvConfig {
+    pub game: GameConfig,
+    /// Tank indices the agents control; every other tank is AI.
+    pub agents: Vec<usize>,
+    pub rewards: RewardConfig,
+    pub observation: ObservationMode,
+    /// Simulation ticks each action is held for.
+    pub frame_skip: u32,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            game: GameConfig::default(),
+            agents: vec![0],
+            rewards: RewardConfig::default(),
+            observation: ObservationMode::default(),
+            frame_skip: 4,
+        }
+    }
+}
+
+/// One agent's view as a dense row-major tensor.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Observation {
+    pub shape: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+/// The outcome of [`TankEnv::step`], indexed like `EnvConfig::agents`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Step {
+    pub observations: Vec<Observation>,
+    pub rewards: Vec<f32>,
+    /// The round is over; call [`TankEnv::reset`] to play another.
+    pub done: bool,
+}
+
+/// A reinforcement learning environment over one round of play at a time.
+/// Runs without a window as fast as the simulation allows.
+pub struct TankEnv {
+    config: EnvConfig,
+    game: Game,
+}
+
+impl TankEnv {
+    pub fn new(config: EnvConfig) -> Self {
+        let game = Game::with_config(0, config.game.clone());
+        Self { config, game }
+    }
+
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Starts a new round from `seed`, skipping the pre-round countdown.
+    ///
+    /// Panics if an agent's tank index does not exist in the round.
+    pub fn reset(&mut self, seed: u64) -> Vec<Observation> {
+        self.game = Game::with_config(seed, self.config.game.clone());
+        self.game.set_ai_only(true);
+        self.game.start_round();
+        let tank_count = self.game.tanks().len();
+        for agent in &self.config.agents {
+            assert!(
+                *agent < tank_count,
+                "agent tank {agent} does not exist (the round has {tank_count} tanks)"
+            );
+        }
+        while self.game.is_playing() && self.game.countdown_timer() > 0.0 {
+            self.game.update(TICK_DT, &TickInput::default());
+        }
+        self.observe()
+    }
+
+    /// Applies one action per agent for `frame_skip` ticks.
+    ///
+    /// Panics if `actions` has a different length from `EnvConfig::agents`.
+    pub fn step(&mut self, actions: &[Action]) -> Step {
+        assert_eq!(
+            actions.len(),
+            self.config.agents.len(),
+            "expected one action per agent"
+        );
+        let input = TickInput {
+            tanks: self
+                .config
+                .agents
+                .iter()
+                .zip(actions)
+                .map(|(tank, action)| TankInput {
+                    tank: *tank,
+                    input: action.to_input(),
+                })
+                .collect(),
+            ..TickInput::default()
+        };
+
+        let mut rewards = vec![0.0; self.config.agents.len()];
+        for _ in 0..self.config.frame_skip.max(1) {
+            if !self.game.is_playing() {
+                break;
+            }
+            self.game.update(TICK_DT, &input);
+            for event in self.game.events() {
+                self.reward(event, &mut rewards);
+            }
+        }
+
+        Step {
+            observations: self.observe(),
+            rewards,
+            done: !self.game.is_playing(),
+        }
+    }
+
+    fn reward(&self, event: &GameEvent, rewards: &mut [f32]) {
+        let shaping = &self.config.rewards;
+        for (agent, reward) in self.config.agents.iter().zip(rewards.iter_mut()) {
+            match *event {
+                GameEvent::Damage {
+                    shooter, amount, ..
+                } if shooter == Some(*agent) => *reward += shaping.damage_dealt * amount,
+                GameEvent::Kill {
+                    victim, shooter, ..
+                } => {
+                    if shooter == Some(*agent) {
+                        *reward += shaping.kill;
+                    }
+                    if victim == *agent {
+                        *reward += shaping.death;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn observe(&self) -> Vec<Observation> {
+        self.config
+            .agents
+            .iter()
+            .map(|agent| match self.config.observation {
+                ObservationMode::Features => Observation {
+                    shape: vec![FEATURE_COUNT],
+                    data: features::features(&self.game, *agent),
+                },
+                ObservationMode::Grid => grid::grid(&self.game, *agent),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,179 @@
+#[cfg(feature = "graphics")]
+use raylib::prelude::Camera2D;
+
+use crate::config::{TILE_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::math::{Rectangle, Vector2, vec2, vec2_add, vec2_scale};
+
+use super::Game;
+#[cfg(feature = "graphics")]
+use super::constants::HUD_BAR_HEIGHT;
+
+/// What the camera looks at when the world is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraView {
+    /// Follows the local players, one viewport each, falling back to the
+    /// centre of the action.
+    Player,
+    /// A fixed world position and zoom that no tank moves.
+    Free { center: Vector2, zoom: f32 },
+    /// Follows one tank of either team, wherever it is.
+    Follow { tank: usize },
+    /// The whole world at once, zoomed out to fit.
+    Overhead,
+}
+
+impl CameraView {
+    pub const DEFAULT_ZOOM: f32 = 0.55;
+
+    /// Whether this view watches the match without a tank of its own, and
+    /// so gets the spectator HUD.
+    pub fn is_spectating(self) -> bool {
+        matches!(self, CameraView::Follow { .. } | CameraView::Overhead)
+    }
+}
+
+/// Gap kept around the map in the overhead view, in pixels.
+#[cfg(feature = "graphics")]
+const OVERHEAD_MARGIN: f32 = 16.0;
+
+/// A region of the screen the world is drawn into.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub rect: Rectangle,
+    /// The local player this viewport follows, `None` for an overview.
+    pub slot: Option<usize>,
+    /// Multiplier applied to the camera zoom so the view fits the window.
+    pub scale: f32,
+}
+
+impl Viewport {
+    /// The whole screen.
+    pub fn full(screen_width: i32, screen_height: i32, slot: Option<usize>) -> Self {
+        Self {
+            rect: Rectangle::new(0.0, 0.0, screen_width as f32, screen_height as f32),
+            slot,
+            scale: screen_scale(screen_width, screen_height),
+        }
+    }
+
+    /// Pixel bounds for scissoring.
+    pub fn bounds(&self) -> (i32, i32, i32, i32) {
+        (
+            self.rect.x as i32,
+            self.rect.y as i32,
+            self.rect.width as i32,
+            self.rect.height as i32,
+        )
+    }
+}
+
+fn screen_scale(screen_width: i32, screen_height: i32) -> f32 {
+    (screen_width as f32 / WINDOW_WIDTH as f32).min(screen_height as f32 / WINDOW_HEIGHT as f32)
+}
+
+impl Game {
+    /// Splits the screen between the local players: side by side for two,
+    /// quarters for three or four. A spare quarter shows an overview.
+    pub fn viewports(&self, screen_width: i32, screen_height: i32) -> Vec<Viewport> {
+        let players = self.seated_players();
+        if players <= 1 {
+            return vec![Viewport::full(
+                screen_width,
+                screen_height,
+                (players == 1).then_some(0),
+            )];
+        }
+
+        let (columns, rows) = if players == 2 { (2, 1) } else { (2, 2) };
+        let width = screen_width as f32 / columns as f32;
+        let height = screen_height as f32 / rows as f32;
+        // Shrink by the square root of the screen share so each player sees
+        // a little less of the map instead of everything at a tiny size.
+        let scale =
+            screen_scale(screen_width, screen_height) * (1.0 / (columns * rows) as f32).sqrt();
+        (0..columns * rows)
+            .map(|cell| Viewport {
+                rect: Rectangle::new(
+                    (cell % columns) as f32 * width,
+                    (cell / columns) as f32 * height,
+                    width,
+                    height,
+                ),
+                slot: (cell < players).then_some(cell),
+                scale,
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "graphics")]
+    pub fn camera(&self, viewport: &Viewport, blend: f32, view: CameraView) -> Camera2D {
+        let rect = viewport.rect;
+        let (target, zoom) = match view {
+            CameraView::Player => (
+                self.player_focus(viewport.slot, blend),
+                CameraView::DEFAULT_ZOOM,
+            ),
+            CameraView::Free { center, zoom } => (center, zoom),
+            CameraView::Follow { tank } => match self.tanks.get(tank) {
+                Some(tank) => (tank.render_pos(blend), CameraView::DEFAULT_ZOOM),
+                None => (self.player_focus(None, blend), CameraView::DEFAULT_ZOOM),
+            },
+            CameraView::Overhead => {
+                // Fit the map below the score bar, leaving a small margin.
+                let (width, height) = self.world_size();
+                let fit = ((rect.width - OVERHEAD_MARGIN * 2.0) / width)
+                    .min((rect.height - HUD_BAR_HEIGHT - OVERHEAD_MARGIN * 2.0) / height);
+                let center = vec2(width * 0.5, height * 0.5);
+                return Camera2D {
+                    target: center,
+                    offset: vec2(
+                        rect.x + rect.width * 0.5,
+                        rect.y + HUD_BAR_HEIGHT + (rect.height - HUD_BAR_HEIGHT) * 0.5,
+                    ),
+                    rotation: 0.0,
+                    zoom: fit,
+                };
+            }
+        };
+
+        Camera2D {
+            target,
+            offset: vec2(rect.x + rect.width * 0.5, rect.y + rect.height * 0.55),
+            rotation: 0.0,
+            zoom: zoom * viewport.scale,
+        }
+    }
+
+    /// The world's size in pixels.
+    fn world_size(&self) -> (f32, f32) {
+        (
+            self.world.width as f32 * TILE_SIZE,
+            self.world.height as f32 * TILE_SIZE,
+        )
+    }
+
+    /// The point a player camera centres on: that local player's tank while
+    /// it is alive, otherwise the average of the living tanks or the map
+    /// centre.
+    pub fn player_focus(&self, slot: Option<usize>, blend: f32) -> Vector2 {
+        let player = slot.and_then(|slot| self.player_tank(slot));
+        if let Some(tank) = player.filter(|tank| tank.alive) {
+            return tank.render_pos(blend);
+        }
+
+        let mut sum = vec2(0.0, 0.0);
+        let mut count = 0.0;
+        for tank in &self.tanks {
+            if tank.alive {
+                sum = vec2_add(sum, tank.render_pos(blend));
+                count += 1.0;
+            }
+        }
+        if count > 0.0 {
+            vec2_scale(sum, 1.0 / count)
+        } else {
+            let (width, height) = self.world_size();
+            vec2(width * 0.5, height * 0.5)
+        }
+    }
+}
@@ -1,7 +1,11 @@
+#[cfg(feature = "graphics")]
 pub(super) const SPRITE_ROT_OFFSET_DEG: f32 = 90.0;
 pub(super) const BARREL_LENGTH: f32 = 46.0;
 pub(super) const TRACK_STEP_DISTANCE: f32 = 40.0;
 pub(super) const TRACK_OFFSET: f32 = 18.0;
-pub(super) const AI_TARGET_FAR: f32 = 260.0;
-pub(super) const AI_TARGET_NEAR: f32 = 180.0;
-pub(super) const AI_FIRE_RANGE: f32 = 900.0;
+/// Height of the score bar along the top of each viewport.
+#[cfg(feature = "graphics")]
+pub(super) const HUD_BAR_HEIGHT: f32 = 48.0;
+/// Height of a capture-the-flag flag in world units, pole included.
+#[cfg(feature = "graphics")]
+pub(super) const FLAG_HEIGHT: f32 = 48.0;
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entities::{PowerupKind, Team};
+
+/// Something notable that happened during the last [`super::Game::update`].
+/// Tank fields are indices into [`super::Game::tanks`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    Damage {
+        victim: usize,
+        attacker: Team,
+        /// The tank that fired, when known.
+        shooter: Option<usize>,
+        amount: f32,
+    },
+    Kill {
+        victim: usize,
+        killer: Team,
+        shooter: Option<usize>,
+    },
+    Pickup {
+        tank: usize,
+        kind: PowerupKind,
+    },
+    /// `tank` picked up `flag`'s flag, from its stand or the ground.
+    FlagTaken {
+        flag: Team,
+        tank: usize,
+    },
+    /// `flag`'s carrier was destroyed and the flag fell where it stood.
+    FlagDropped {
+        flag: Team,
+    },
+    /// `flag`'s flag went back to its stand, brought by `tank` or, without
+    /// one, because it lay on the ground too long.
+    FlagReturned {
+        flag: Team,
+        tank: Option<usize>,
+    },
+    /// `tank` brought `flag`'s flag home and scored for its team.
+    FlagCaptured {
+        flag: Team,
+        tank: usize,
+    },
+}
@@ -1,189 +1,314 @@
-use raylib::prelude::{
-    GamepadAxis, GamepadButton, KeyboardKey, MouseButton, RaylibHandle, Vector2,
-};
-
-use crate::math::{vec2, vec2_length, vec2_normalize, vec2_scale};
-
-const GAMEPAD_ID: i32 = 0;
-const STICK_DEADZONE: f32 = 0.2;
-const AIM_DEADZONE: f32 = 0.25;
-const TRIGGER_THRESHOLD: f32 = 0.4;
-const TRIGGER_ACTIVE: f32 = 0.1;
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum InputDevice {
-    KeyboardMouse,
-    Gamepad,
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::str::FromStr;
+
+const DEFAULT_GAMEPAD: i32 = 0;
+
+/// The device a local player drives their tank with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputBinding {
+    /// Keyboard and mouse or the first gamepad, whichever was used last.
+    #[default]
+    Auto,
+    /// Keyboard and mouse only.
+    Keyboard,
+    /// Only the gamepad with this index.
+    Gamepad(i32),
 }
 
-#[derive(Clone, Copy, Debug)]
+impl fmt::Display for InputBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputBinding::Auto => f.write_str("auto"),
+            InputBinding::Keyboard => f.write_str("keyboard"),
+            InputBinding::Gamepad(id) => write!(f, "gamepad{id}"),
+        }
+    }
+}
+
+impl FromStr for InputBinding {
+    type Err = String;
+
+    /// Accepts `auto`, `keyboard`, `gamepad` (the first one) or `gamepadN`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_ascii_lowercase();
+        match value.as_str() {
+            "auto" => Ok(InputBinding::Auto),
+            "keyboard" | "kb" => Ok(InputBinding::Keyboard),
+            "gamepad" | "pad" => Ok(InputBinding::Gamepad(DEFAULT_GAMEPAD)),
+            _ => value
+                .strip_prefix("gamepad")
+                .or_else(|| value.strip_prefix("pad"))
+                .and_then(|id| id.parse().ok())
+                .filter(|id| *id >= 0)
+                .map(InputBinding::Gamepad)
+                .ok_or_else(|| {
+                    format!("unknown device `{value}` (expected keyboard, auto or gamepadN)")
+                }),
+        }
+    }
+}
+
+/// Device-agnostic controls for one human tank. `aim_angle` is an absolute
+/// world-space turret angle; `None` leaves the turret where it is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct PlayerInput {
     pub turn: f32,
     pub movement: f32,
-    pub aim_dir: Option<Vector2>,
+    pub aim_angle: Option<f32>,
     pub wants_fire: bool,
-    pub use_mouse_aim: bool,
 }
 
-pub struct InputState {
-    last_device: InputDevice,
-    gamepad_available: bool,
-    gamepad_id: i32,
+/// Everything the simulation reads from the outside world for one update.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TickInput {
+    pub start_pressed: bool,
+    /// Controls for tanks driven from outside the simulation: local players
+    /// and external bots. Every other tank is driven by the AI.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tanks: Vec<TankInput>,
 }
 
-impl InputState {
-    pub fn new() -> Self {
-        Self {
-            last_device: InputDevice::KeyboardMouse,
-            gamepad_available: false,
-            gamepad_id: GAMEPAD_ID,
-        }
-    }
+/// Controls for the tank at `tank` in [`Game::tanks`](super::Game::tanks).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TankInput {
+    pub tank: usize,
+    pub input: PlayerInput,
+}
+
+#[cfg(feature = "graphics")]
+pub use devices::{InputDevice, InputState};
 
-    pub fn last_device(&self) -> InputDevice {
-        self.last_device
+/// Tracking and reading each player's keyboard, mouse and gamepads, which
+/// needs a window.
+#[cfg(feature = "graphics")]
+mod devices {
+    use raylib::prelude::{
+        Camera2D, GamepadAxis, GamepadButton, KeyboardKey, MouseButton, RaylibHandle, Vector2,
+    };
+
+    use crate::math::{vec2, vec2_angle, vec2_length, vec2_normalize, vec2_scale, vec2_sub};
+
+    use super::{DEFAULT_GAMEPAD, InputBinding, PlayerInput};
+
+    const STICK_DEADZONE: f32 = 0.2;
+    const AIM_DEADZONE: f32 = 0.25;
+    const TRIGGER_THRESHOLD: f32 = 0.4;
+    const TRIGGER_ACTIVE: f32 = 0.1;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum InputDevice {
+        KeyboardMouse,
+        Gamepad,
     }
 
-    pub fn gamepad_available(&self) -> bool {
-        self.gamepad_available
+    /// Device tracking for one local player.
+    #[derive(Clone)]
+    pub struct InputState {
+        binding: InputBinding,
+        last_device: InputDevice,
+        gamepad_available: bool,
+        gamepad_id: i32,
     }
 
-    pub fn start_pressed(&mut self, rl: &RaylibHandle) -> bool {
-        self.refresh_gamepad(rl);
-        let mut pressed = false;
-        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) || rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-            self.last_device = InputDevice::KeyboardMouse;
-            pressed = true;
+    impl InputState {
+        pub fn new(binding: InputBinding) -> Self {
+            let (last_device, gamepad_id) = match binding {
+                InputBinding::Auto | InputBinding::Keyboard => {
+                    (InputDevice::KeyboardMouse, DEFAULT_GAMEPAD)
+                }
+                InputBinding::Gamepad(id) => (InputDevice::Gamepad, id),
+            };
+            Self {
+                binding,
+                last_device,
+                gamepad_available: false,
+                gamepad_id,
+            }
         }
 
-        if self.gamepad_available
-            && (rl.is_gamepad_button_pressed(
-                self.gamepad_id,
-                GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT,
-            ) || rl.is_gamepad_button_pressed(
-                self.gamepad_id,
-                GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
-            ))
-        {
-            self.last_device = InputDevice::Gamepad;
-            pressed = true;
+        pub fn binding(&self) -> InputBinding {
+            self.binding
         }
-        pressed
-    }
-
-    pub fn player_input(&mut self, rl: &RaylibHandle) -> PlayerInput {
-        self.refresh_gamepad(rl);
-        let keyboard = sample_keyboard_mouse(rl);
-        let gamepad = if self.gamepad_available {
-            sample_gamepad(rl, self.gamepad_id)
-        } else {
-            GamepadSample::default()
-        };
 
-        if gamepad.active && !keyboard.active {
-            self.last_device = InputDevice::Gamepad;
-        } else if keyboard.active && !gamepad.active {
-            self.last_device = InputDevice::KeyboardMouse;
+        pub fn last_device(&self) -> InputDevice {
+            self.last_device
         }
 
-        let turn = pick_axis(self.last_device, keyboard.turn, gamepad.turn);
-        let movement = pick_axis(self.last_device, keyboard.movement, gamepad.movement);
-        let aim_dir = if self.last_device == InputDevice::Gamepad {
-            gamepad.aim_dir
-        } else {
-            None
-        };
-        let wants_fire = keyboard.wants_fire || gamepad.wants_fire;
+        pub fn gamepad_available(&self) -> bool {
+            self.gamepad_available
+        }
 
-        PlayerInput {
-            turn,
-            movement,
-            aim_dir,
-            wants_fire,
-            use_mouse_aim: self.last_device == InputDevice::KeyboardMouse,
+        fn uses_keyboard(&self) -> bool {
+            !matches!(self.binding, InputBinding::Gamepad(_))
         }
-    }
 
-    fn refresh_gamepad(&mut self, rl: &RaylibHandle) {
-        self.gamepad_available = rl.is_gamepad_available(self.gamepad_id);
-        if !self.gamepad_available && self.last_device == InputDevice::Gamepad {
-            self.last_device = InputDevice::KeyboardMouse;
+        pub fn start_pressed(&mut self, rl: &RaylibHandle) -> bool {
+            self.refresh_gamepad(rl);
+            let mut pressed = false;
+            if self.uses_keyboard()
+                && (rl.is_key_pressed(KeyboardKey::KEY_ENTER)
+                    || rl.is_key_pressed(KeyboardKey::KEY_SPACE))
+            {
+                self.last_device = InputDevice::KeyboardMouse;
+                pressed = true;
+            }
+
+            if self.gamepad_available
+                && (rl.is_gamepad_button_pressed(
+                    self.gamepad_id,
+                    GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT,
+                ) || rl.is_gamepad_button_pressed(
+                    self.gamepad_id,
+                    GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
+                ))
+            {
+                self.last_device = InputDevice::Gamepad;
+                pressed = true;
+            }
+            pressed
         }
-    }
-}
 
-#[derive(Clone, Copy, Debug, Default)]
-struct KeyboardMouseSample {
-    turn: f32,
-    movement: f32,
-    wants_fire: bool,
-    active: bool,
-}
+        /// Samples this player's devices. `camera` maps the mouse into the world
+        /// and should be the one their viewport is drawn with.
+        pub fn player_input(
+            &mut self,
+            rl: &RaylibHandle,
+            camera: Camera2D,
+            player_pos: Option<Vector2>,
+        ) -> PlayerInput {
+            self.refresh_gamepad(rl);
+            let keyboard = if self.uses_keyboard() {
+                sample_keyboard_mouse(rl)
+            } else {
+                KeyboardMouseSample::default()
+            };
+            let gamepad = if self.gamepad_available && self.binding != InputBinding::Keyboard {
+                sample_gamepad(rl, self.gamepad_id)
+            } else {
+                GamepadSample::default()
+            };
 
-#[derive(Clone, Copy, Debug, Default)]
-struct GamepadSample {
-    turn: f32,
-    movement: f32,
-    aim_dir: Option<Vector2>,
-    wants_fire: bool,
-    active: bool,
-}
+            if gamepad.active && !keyboard.active {
+                self.last_device = InputDevice::Gamepad;
+            } else if keyboard.active && !gamepad.active {
+                self.last_device = InputDevice::KeyboardMouse;
+            }
+
+            let turn = pick_axis(self.last_device, keyboard.turn, gamepad.turn);
+            let movement = pick_axis(self.last_device, keyboard.movement, gamepad.movement);
+            let aim_angle = match self.last_device {
+                InputDevice::Gamepad => gamepad.aim_dir.map(vec2_angle),
+                InputDevice::KeyboardMouse => player_pos.and_then(|pos| {
+                    let mouse_world = rl.get_screen_to_world2D(rl.get_mouse_position(), camera);
+                    let turret_target = vec2_sub(mouse_world, pos);
+                    (vec2_length(turret_target) > 0.01).then(|| vec2_angle(turret_target))
+                }),
+            };
+            let wants_fire = keyboard.wants_fire || gamepad.wants_fire;
+
+            PlayerInput {
+                turn,
+                movement,
+                aim_angle,
+                wants_fire,
+            }
+        }
 
-fn sample_keyboard_mouse(rl: &RaylibHandle) -> KeyboardMouseSample {
-    let mut turn: f32 = 0.0;
-    if rl.is_key_down(KeyboardKey::KEY_A) || rl.is_key_down(KeyboardKey::KEY_LEFT) {
-        turn -= 1.0;
+        fn refresh_gamepad(&mut self, rl: &RaylibHandle) {
+            self.gamepad_available =
+                self.binding != InputBinding::Keyboard && rl.is_gamepad_available(self.gamepad_id);
+            if !self.gamepad_available
+                && self.last_device == InputDevice::Gamepad
+                && self.uses_keyboard()
+            {
+                self.last_device = InputDevice::KeyboardMouse;
+            }
+        }
     }
-    if rl.is_key_down(KeyboardKey::KEY_D) || rl.is_key_down(KeyboardKey::KEY_RIGHT) {
-        turn += 1.0;
+
+    impl Default for InputState {
+        fn default() -> Self {
+            Self::new(InputBinding::Auto)
+        }
     }
 
-    let mut movement: f32 = 0.0;
-    if rl.is_key_down(KeyboardKey::KEY_W) || rl.is_key_down(KeyboardKey::KEY_UP) {
-        movement += 1.0;
+    #[derive(Clone, Copy, Debug, Default)]
+    struct KeyboardMouseSample {
+        turn: f32,
+        movement: f32,
+        wants_fire: bool,
+        active: bool,
     }
-    if rl.is_key_down(KeyboardKey::KEY_S) || rl.is_key_down(KeyboardKey::KEY_DOWN) {
-        movement -= 1.0;
+
+    #[derive(Clone, Copy, Debug, Default)]
+    struct GamepadSample {
+        turn: f32,
+        movement: f32,
+        aim_dir: Option<Vector2>,
+        wants_fire: bool,
+        active: bool,
     }
 
-    let mouse_delta = rl.get_mouse_delta();
-    let mouse_moved = mouse_delta.x.abs() > 0.0 || mouse_delta.y.abs() > 0.0;
-    let wants_fire = rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT)
-        || rl.is_key_down(KeyboardKey::KEY_SPACE);
-    let active = turn.abs() > 0.01 || movement.abs() > 0.01 || wants_fire || mouse_moved;
-
-    KeyboardMouseSample {
-        turn,
-        movement,
-        wants_fire,
-        active,
+    fn sample_keyboard_mouse(rl: &RaylibHandle) -> KeyboardMouseSample {
+        let mut turn: f32 = 0.0;
+        if rl.is_key_down(KeyboardKey::KEY_A) || rl.is_key_down(KeyboardKey::KEY_LEFT) {
+            turn -= 1.0;
+        }
+        if rl.is_key_down(KeyboardKey::KEY_D) || rl.is_key_down(KeyboardKey::KEY_RIGHT) {
+            turn += 1.0;
+        }
+
+        let mut movement: f32 = 0.0;
+        if rl.is_key_down(KeyboardKey::KEY_W) || rl.is_key_down(KeyboardKey::KEY_UP) {
+            movement += 1.0;
+        }
+        if rl.is_key_down(KeyboardKey::KEY_S) || rl.is_key_down(KeyboardKey::KEY_DOWN) {
+            movement -= 1.0;
+        }
+
+        let mouse_delta = rl.get_mouse_delta();
+        let mouse_moved = mouse_delta.x.abs() > 0.0 || mouse_delta.y.abs() > 0.0;
+        let wants_fire = rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT)
+            || rl.is_key_down(KeyboardKey::KEY_SPACE);
+        let active = turn.abs() > 0.01 || movement.abs() > 0.01 || wants_fire || mouse_moved;
+
+        KeyboardMouseSample {
+            turn,
+            movement,
+            wants_fire,
+            active,
+        }
     }
-}
 
-fn sample_gamepad(rl: &RaylibHandle, gamepad: i32) -> GamepadSample {
-    let left_raw = vec2(
-        rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_LEFT_X),
-        rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_LEFT_Y),
-    );
-    let right_raw = vec2(
-        rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_RIGHT_X),
-        rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_RIGHT_Y),
-    );
-
-    let left = apply_radial_deadzone(left_raw, STICK_DEADZONE);
-    let right = apply_radial_deadzone(right_raw, AIM_DEADZONE);
-
-    let dpad_turn = if rl
-        .is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT)
-    {
-        -1.0
-    } else if rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT) {
-        1.0
-    } else {
-        0.0
-    };
-    let dpad_move =
-        if rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP) {
+    fn sample_gamepad(rl: &RaylibHandle, gamepad: i32) -> GamepadSample {
+        let left_raw = vec2(
+            rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_LEFT_X),
+            rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_LEFT_Y),
+        );
+        let right_raw = vec2(
+            rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_RIGHT_X),
+            rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_RIGHT_Y),
+        );
+
+        let left = apply_radial_deadzone(left_raw, STICK_DEADZONE);
+        let right = apply_radial_deadzone(right_raw, AIM_DEADZONE);
+
+        let dpad_turn = if rl
+            .is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT)
+        {
+            -1.0
+        } else if rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT)
+        {
+            1.0
+        } else {
+            0.0
+        };
+        let dpad_move = if rl
+            .is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP)
+        {
             1.0
         } else if rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN) {
             -1.0
@@ -191,75 +316,76 @@ fn sample_gamepad(rl: &RaylibHandle, gamepad: i32) -> GamepadSample {
             0.0
         };
 
-    let turn = (left.x + dpad_turn).clamp(-1.0, 1.0);
-    let movement = (-left.y + dpad_move).clamp(-1.0, 1.0);
+        let turn = (left.x + dpad_turn).clamp(-1.0, 1.0);
+        let movement = (-left.y + dpad_move).clamp(-1.0, 1.0);
 
-    let aim_dir = if vec2_length(right) > 0.01 {
-        Some(right)
-    } else {
-        None
-    };
+        let aim_dir = if vec2_length(right) > 0.01 {
+            Some(right)
+        } else {
+            None
+        };
 
-    let right_trigger = normalize_trigger(
-        rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER),
-    );
-    let wants_fire = right_trigger > TRIGGER_THRESHOLD
-        || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1)
-        || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2)
-        || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN);
-
-    let active = vec2_length(left_raw) > STICK_DEADZONE
-        || vec2_length(right_raw) > AIM_DEADZONE
-        || right_trigger > TRIGGER_ACTIVE
-        || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1)
-        || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2)
-        || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)
-        || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT)
-        || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT)
-        || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP)
-        || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN);
-
-    GamepadSample {
-        turn,
-        movement,
-        aim_dir,
-        wants_fire,
-        active,
+        let right_trigger = normalize_trigger(
+            rl.get_gamepad_axis_movement(gamepad, GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER),
+        );
+        let wants_fire = right_trigger > TRIGGER_THRESHOLD
+            || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1)
+            || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2)
+            || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN);
+
+        let active = vec2_length(left_raw) > STICK_DEADZONE
+            || vec2_length(right_raw) > AIM_DEADZONE
+            || right_trigger > TRIGGER_ACTIVE
+            || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1)
+            || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2)
+            || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)
+            || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT)
+            || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT)
+            || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP)
+            || rl.is_gamepad_button_down(gamepad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN);
+
+        GamepadSample {
+            turn,
+            movement,
+            aim_dir,
+            wants_fire,
+            active,
+        }
     }
-}
 
-fn apply_radial_deadzone(value: Vector2, deadzone: f32) -> Vector2 {
-    let len = vec2_length(value);
-    if len <= deadzone {
-        vec2(0.0, 0.0)
-    } else {
-        let scaled = (len - deadzone) / (1.0 - deadzone);
-        vec2_scale(vec2_normalize(value), scaled)
+    fn apply_radial_deadzone(value: Vector2, deadzone: f32) -> Vector2 {
+        let len = vec2_length(value);
+        if len <= deadzone {
+            vec2(0.0, 0.0)
+        } else {
+            let scaled = (len - deadzone) / (1.0 - deadzone);
+            vec2_scale(vec2_normalize(value), scaled)
+        }
     }
-}
 
-fn normalize_trigger(axis: f32) -> f32 {
-    if axis < -0.2 {
-        ((axis + 1.0) * 0.5).clamp(0.0, 1.0)
-    } else {
-        axis.clamp(0.0, 1.0)
+    fn normalize_trigger(axis: f32) -> f32 {
+        if axis < -0.2 {
+            ((axis + 1.0) * 0.5).clamp(0.0, 1.0)
+        } else {
+            axis.clamp(0.0, 1.0)
+        }
     }
-}
 
-fn pick_axis(device: InputDevice, keyboard: f32, gamepad: f32) -> f32 {
-    match device {
-        InputDevice::KeyboardMouse => {
-            if keyboard.abs() > 0.01 {
-                keyboard
-            } else {
-                gamepad
+    fn pick_axis(device: InputDevice, keyboard: f32, gamepad: f32) -> f32 {
+        match device {
+            InputDevice::KeyboardMouse => {
+                if keyboard.abs() > 0.01 {
+                    keyboard
+                } else {
+                    gamepad
+                }
             }
-        }
-        InputDevice::Gamepad => {
-            if gamepad.abs() > 0.01 {
-                gamepad
-            } else {
-                keyboard
+            InputDevice::Gamepad => {
+                if gamepad.abs() > 0.01 {
+                    gamepad
+                } else {
+                    keyboard
+                }
             }
         }
     }
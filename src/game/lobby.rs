@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entities::Team;
+
+use super::{Game, ScreenState};
+
+/// A human waiting in a lobby.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LobbyPlayer {
+    pub name: String,
+    pub team: Team,
+    pub ready: bool,
+}
+
+/// What the lobby screen shows. The game only draws it; whoever runs the
+/// lobby fills it in and calls [`Game::show_lobby`] again as it changes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LobbyView {
+    pub heading: String,
+    /// Lines to pick from, such as the games found or the match settings.
+    pub menu: Vec<String>,
+    /// Highlighted line of `menu`.
+    pub selected: Option<usize>,
+    pub players: Vec<LobbyPlayer>,
+    /// The viewer's own line in `players`.
+    pub you: Option<usize>,
+    /// Key help along the bottom.
+    pub footer: String,
+}
+
+impl Game {
+    /// Switches to the lobby screen, or refreshes it, showing `view`. The
+    /// simulation stands still until a round is started.
+    pub fn show_lobby(&mut self, view: LobbyView) {
+        self.lobby = Some(view);
+        self.state = ScreenState::Lobby;
+    }
+
+    pub fn is_in_lobby(&self) -> bool {
+        self.state == ScreenState::Lobby
+    }
+}
@@ -1,30 +1,52 @@
+mod camera;
 mod constants;
+mod events;
 mod input;
+mod lobby;
+mod modes;
+mod players;
 mod powerups;
+#[cfg(feature = "graphics")]
 mod render;
+mod save;
+mod snapshot;
+mod spectator;
 mod tanks;
+mod timestep;
 mod update;
 
-use rand::{SeedableRng, rngs::SmallRng};
-use raylib::prelude::Camera2D;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
 
-use crate::config::{
-    PLAYER_INTRO_TIME, POWERUP_BASE_SPAWN, ROUND_COUNTDOWN, ROUND_TIME, TILE_SIZE, WINDOW_HEIGHT,
-    WINDOW_WIDTH,
-};
-use crate::entities::{Bullet, Explosion, Powerup, Tank, Team, TrackMark};
-use crate::math::{vec2, vec2_add, vec2_scale};
+use crate::config::{GameConfig, GameMode};
+use crate::entities::{Bullet, Explosion, Flag, Powerup, Tank, Team, TrackMark};
+use crate::math::GameRng;
 use crate::world::World;
+#[cfg(feature = "graphics")]
 use input::InputState;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum ScreenState {
+pub use camera::{CameraView, Viewport};
+pub use events::GameEvent;
+pub use input::{InputBinding, PlayerInput, TankInput, TickInput};
+pub use lobby::{LobbyPlayer, LobbyView};
+pub use players::{LocalPlayer, MAX_LOCAL_PLAYERS, validate_players};
+pub use save::{SAVE_EXTENSION, SAVE_FORMAT_VERSION};
+pub use snapshot::{Snapshot, SnapshotDelta};
+pub use spectator::Spectator;
+pub use timestep::FixedTimestep;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum ScreenState {
     Title,
+    /// Gathering players for a networked match before it starts.
+    Lobby,
     Playing,
     RoundOver,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Game {
+    config: GameConfig,
     state: ScreenState,
     world: World,
     tanks: Vec<Tank>,
@@ -32,22 +54,43 @@ pub struct Game {
     tracks: Vec<TrackMark>,
     explosions: Vec<Explosion>,
     powerups: Vec<Powerup>,
-    rng: SmallRng,
+    rng: GameRng,
     round_timer: f32,
     countdown_timer: f32,
     intro_timer: f32,
     powerup_spawn_timer: f32,
-    team_kills: [u32; 2],
+    team_kills: Vec<u32>,
     last_winner: Option<Team>,
-    player_index: usize,
-    input_state: InputState,
+    /// Fixed for the round from `config.round.mode`.
+    mode: GameMode,
+    flags: Vec<Flag>,
+    /// Flags brought home this round, indexed by [`Team::index`].
+    captures: Vec<u32>,
+    /// Counts up each time a round is set up, so clients can tell rounds
+    /// apart.
+    round: u32,
+    players: Vec<LocalPlayer>,
+    /// The tank each local player drives this round.
+    player_tanks: Vec<Option<usize>>,
+    ai_only: bool,
+    #[serde(skip)]
+    lobby: Option<LobbyView>,
+    #[serde(skip)]
+    events: Vec<GameEvent>,
+    #[serde(skip)]
+    #[cfg(feature = "graphics")]
+    input_states: Vec<InputState>,
 }
 
 impl Game {
     pub fn new(seed: u64) -> Self {
-        let mut rng = SmallRng::seed_from_u64(seed);
-        let world = World::new(&mut rng);
-        let tanks = tanks::spawn_tanks(&mut rng, &world);
+        Self::with_config(seed, GameConfig::default())
+    }
+
+    pub fn with_config(seed: u64, config: GameConfig) -> Self {
+        let mut rng = GameRng::seed_from_u64(seed);
+        let world = World::new(&mut rng, &config.map, config.round.playing_teams());
+        let tanks = tanks::spawn_tanks(&mut rng, &world, &config);
         let mut game = Self {
             state: ScreenState::Title,
             world,
@@ -57,78 +100,151 @@ impl Game {
             explosions: Vec::new(),
             powerups: Vec::new(),
             rng,
-            round_timer: ROUND_TIME,
-            countdown_timer: ROUND_COUNTDOWN,
-            intro_timer: PLAYER_INTRO_TIME,
-            powerup_spawn_timer: POWERUP_BASE_SPAWN,
-            team_kills: [0, 0],
+            round_timer: config.round.time,
+            countdown_timer: config.round.countdown,
+            intro_timer: config.round.intro_time,
+            powerup_spawn_timer: config.powerup.base_spawn,
+            team_kills: Vec::new(),
             last_winner: None,
-            player_index: 0,
-            input_state: InputState::new(),
+            mode: GameMode::Deathmatch,
+            flags: Vec::new(),
+            captures: Vec::new(),
+            round: 0,
+            players: vec![LocalPlayer {
+                team: Team::Red,
+                binding: InputBinding::Auto,
+            }],
+            player_tanks: Vec::new(),
+            ai_only: false,
+            lobby: None,
+            events: Vec::new(),
+            #[cfg(feature = "graphics")]
+            input_states: vec![InputState::new(InputBinding::Auto)],
+            config,
         };
         game.reset_round();
         game.state = ScreenState::Title;
         game
     }
 
+    /// Skips the title screen and deploys a fresh round immediately.
+    pub fn start_round(&mut self) {
+        self.reset_round();
+        self.state = ScreenState::Playing;
+    }
+
+    /// Swaps in new tuning values. Timers and rates take effect immediately;
+    /// map size, team size and per-tank stats apply from the next round.
+    pub fn set_config(&mut self, config: GameConfig) {
+        self.config = config;
+    }
+
+    /// Restarts the random generator, so the next round's map and spawns
+    /// come from `seed`. A round set up right after this matches the one
+    /// `Game::with_config(seed, ..)` shows first.
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = GameRng::seed_from_u64(seed);
+    }
+
+    /// Hands the local players' tanks over to the AI, now and in later
+    /// rounds.
+    pub fn set_ai_only(&mut self, ai_only: bool) {
+        self.ai_only = ai_only;
+        self.assign_players();
+    }
+
     fn reset_round(&mut self) {
-        self.world = World::new(&mut self.rng);
-        self.tanks = tanks::spawn_tanks(&mut self.rng, &self.world);
+        self.world = World::new(
+            &mut self.rng,
+            &self.config.map,
+            self.config.round.playing_teams(),
+        );
+        self.tanks = tanks::spawn_tanks(&mut self.rng, &self.world, &self.config);
         self.bullets.clear();
         self.tracks.clear();
         self.explosions.clear();
         self.powerups.clear();
-        self.round_timer = ROUND_TIME;
-        self.countdown_timer = ROUND_COUNTDOWN;
-        self.intro_timer = PLAYER_INTRO_TIME;
-        self.powerup_spawn_timer = POWERUP_BASE_SPAWN;
-        self.team_kills = [0, 0];
+        self.round_timer = self.config.round.time;
+        self.countdown_timer = self.config.round.countdown;
+        self.intro_timer = self.config.round.intro_time;
+        self.powerup_spawn_timer = self.config.powerup.base_spawn;
+        self.team_kills = vec![0; self.world.spawn_zones.len()];
         self.last_winner = None;
-        self.player_index = self
-            .tanks
-            .iter()
-            .position(|tank| tank.team == Team::Red)
-            .unwrap_or(0);
-    }
-
-    fn camera(&self, screen_width: i32, screen_height: i32) -> Camera2D {
-        let scale = (screen_width as f32 / WINDOW_WIDTH as f32)
-            .min(screen_height as f32 / WINDOW_HEIGHT as f32);
-        let mut center = self
-            .tanks
-            .get(self.player_index)
-            .filter(|tank| tank.alive)
-            .map(|tank| tank.pos);
-
-        if center.is_none() {
-            let mut sum = vec2(0.0, 0.0);
-            let mut count = 0.0;
-            for tank in &self.tanks {
-                if tank.alive {
-                    sum = vec2_add(sum, tank.pos);
-                    count += 1.0;
-                }
-            }
-            if count > 0.0 {
-                center = Some(vec2_scale(sum, 1.0 / count));
-            } else {
-                center = Some(vec2(
-                    self.world.width as f32 * TILE_SIZE * 0.5,
-                    self.world.height as f32 * TILE_SIZE * 0.5,
-                ));
-            }
-        }
-
-        Camera2D {
-            target: center.unwrap_or_else(|| {
-                vec2(
-                    self.world.width as f32 * TILE_SIZE * 0.5,
-                    self.world.height as f32 * TILE_SIZE * 0.5,
-                )
-            }),
-            offset: vec2(screen_width as f32 * 0.5, screen_height as f32 * 0.55),
-            rotation: 0.0,
-            zoom: 0.55 * scale,
-        }
+        self.reset_mode();
+        self.round += 1;
+        self.assign_players();
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn tanks(&self) -> &[Tank] {
+        &self.tanks
+    }
+
+    pub fn bullets(&self) -> &[Bullet] {
+        &self.bullets
+    }
+
+    pub fn powerups(&self) -> &[Powerup] {
+        &self.powerups
+    }
+
+    pub fn tracks(&self) -> &[TrackMark] {
+        &self.tracks
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn tanks_mut(&mut self) -> &mut Vec<Tank> {
+        &mut self.tanks
+    }
+
+    pub fn bullets_mut(&mut self) -> &mut Vec<Bullet> {
+        &mut self.bullets
+    }
+
+    pub fn powerups_mut(&mut self) -> &mut Vec<Powerup> {
+        &mut self.powerups
+    }
+
+    /// Kills this round, indexed by [`Team::index`], one per team playing.
+    pub fn team_kills(&self) -> &[u32] {
+        &self.team_kills
+    }
+
+    pub fn round_timer(&self) -> f32 {
+        self.round_timer
+    }
+
+    /// Seconds left before tanks may move at the start of a round.
+    pub fn countdown_timer(&self) -> f32 {
+        self.countdown_timer
+    }
+
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    /// Events raised by the most recent [`Game::update`].
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.state == ScreenState::Playing
+    }
+
+    pub fn is_round_over(&self) -> bool {
+        self.state == ScreenState::RoundOver
+    }
+
+    /// Winner of the most recent round, `None` for a stalemate or while the
+    /// round is still running.
+    pub fn last_winner(&self) -> Option<Team> {
+        self.last_winner
     }
 }
@@ -0,0 +1,335 @@
+use crate::config::{TANK_RADIUS, TILE_SIZE};
+use crate::entities::{Flag, FlagState};
+use crate::math::{Vector2, vec2_add, vec2_distance, vec2_normalize, vec2_scale, vec2_sub};
+use crate::world::{SpawnZone, World};
+
+use super::super::Game;
+use super::super::events::GameEvent;
+use super::Mode;
+
+/// How far past the edge of its spawn zone a flag stands, towards the
+/// middle of the map. Enemies cannot enter the zone itself.
+const FLAG_SETBACK: f32 = TILE_SIZE * 0.75;
+/// Room a flag keeps from obstacles, so every tank can reach it.
+const FLAG_CLEARANCE: f32 = TANK_RADIUS * 2.0;
+/// How close a tank has to get to a flag to pick it up.
+const FLAG_REACH: f32 = TANK_RADIUS + 20.0;
+
+/// Each team defends a flag in front of its spawn zone. Carrying an enemy
+/// flag into your own zone scores a capture, but only while your own flag
+/// is at home; a carrier that is destroyed
+/// drops the flag, which its own team can return by touching it and which
+/// goes home by itself after `flag.return_time`.
+pub(super) struct CaptureTheFlag;
+
+impl Mode for CaptureTheFlag {
+    fn reset(&self, game: &mut Game) {
+        game.captures = vec![0; game.world.spawn_zones.len()];
+        game.flags = game
+            .world
+            .spawn_zones
+            .iter()
+            .map(|zone| {
+                let home = flag_home(&game.world, zone);
+                Flag {
+                    team: zone.team,
+                    home,
+                    pos: home,
+                    state: FlagState::Home,
+                }
+            })
+            .collect();
+    }
+
+    fn update(&self, game: &mut Game, dt: f32) {
+        for index in 0..game.flags.len() {
+            match game.flags[index].state {
+                FlagState::Carried(tank) => carry(game, index, tank),
+                FlagState::Dropped { return_timer } if return_timer <= dt => {
+                    send_home(game, index, None);
+                }
+                FlagState::Dropped { return_timer } => {
+                    game.flags[index].state = FlagState::Dropped {
+                        return_timer: return_timer - dt,
+                    };
+                }
+                FlagState::Home => {}
+            }
+            if game.flags[index].carrier().is_none() {
+                touch(game, index);
+            }
+        }
+    }
+
+    fn scores<'a>(&self, game: &'a Game) -> &'a [u32] {
+        &game.captures
+    }
+
+    fn is_decided(&self, game: &Game) -> bool {
+        let target = game.config.flag.captures_to_win;
+        target > 0 && game.captures.iter().any(|&captures| captures >= target)
+    }
+}
+
+/// Moves a carried flag with its tank, dropping it if the tank is gone and
+/// scoring it once the tank is home and so is its own team's flag.
+fn carry(game: &mut Game, index: usize, tank: usize) {
+    let flag = game.flags[index].team;
+    let carrier = game.tanks.get(tank).filter(|carrier| carrier.alive);
+    let Some(carrier) = carrier else {
+        if let Some(wreck) = game.tanks.get(tank) {
+            game.flags[index].pos = wreck.pos;
+        }
+        game.flags[index].state = FlagState::Dropped {
+            return_timer: game.config.flag.return_time,
+        };
+        game.events.push(GameEvent::FlagDropped { flag });
+        return;
+    };
+    game.flags[index].pos = carrier.pos;
+    let own_flag_home = game
+        .flags
+        .iter()
+        .any(|own| own.team == carrier.team && own.state == FlagState::Home);
+    if own_flag_home && game.world.spawn_zone(carrier.team).contains(carrier.pos) {
+        game.captures[carrier.team.index()] += 1;
+        game.flags[index].state = FlagState::Home;
+        game.flags[index].pos = game.flags[index].home;
+        game.events.push(GameEvent::FlagCaptured { flag, tank });
+    }
+}
+
+/// Hands a flag that is not being carried to the first tank touching it:
+/// an enemy with its hands free takes it, a teammate returns it if it was
+/// dropped.
+fn touch(game: &mut Game, index: usize) {
+    let flag = &game.flags[index];
+    let dropped = flag.state != FlagState::Home;
+    let toucher = game.tanks.iter().enumerate().find(|(tank, other)| {
+        let reach = other.alive && vec2_distance(other.pos, flag.pos) < FLAG_REACH;
+        let takes = other.team != flag.team && game.carried_flag(*tank).is_none();
+        let returns = other.team == flag.team && dropped;
+        reach && (takes || returns)
+    });
+    let Some((tank, toucher)) = toucher else {
+        return;
+    };
+    if toucher.team == flag.team {
+        send_home(game, index, Some(tank));
+    } else {
+        let flag = flag.team;
+        game.flags[index].state = FlagState::Carried(tank);
+        game.events.push(GameEvent::FlagTaken { flag, tank });
+    }
+}
+
+fn send_home(game: &mut Game, index: usize, tank: Option<usize>) {
+    let flag = &mut game.flags[index];
+    flag.state = FlagState::Home;
+    flag.pos = flag.home;
+    game.events.push(GameEvent::FlagReturned {
+        flag: flag.team,
+        tank,
+    });
+}
+
+/// The flag's stand, just outside `zone` on the side facing the middle of
+/// the map, stepped further out while an obstacle is in the way.
+fn flag_home(world: &World, zone: &SpawnZone) -> Vector2 {
+    let forward = vec2_normalize(vec2_sub(world.center(), zone.center()));
+    // Distance from the zone's centre to its edge along `forward`.
+    let edge =
+        (zone.rect.width * 0.5 / forward.x.abs()).min(zone.rect.height * 0.5 / forward.y.abs());
+    let stand = |setback: f32| vec2_add(zone.center(), vec2_scale(forward, edge + setback));
+    (0..8)
+        .map(|step| stand(FLAG_SETBACK + step as f32 * TILE_SIZE * 0.5))
+        .find(|&pos| {
+            world.obstacles.iter().all(|obstacle| {
+                vec2_distance(obstacle.pos, pos) >= obstacle.radius + FLAG_CLEARANCE
+            })
+        })
+        .unwrap_or_else(|| stand(FLAG_SETBACK))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{GameConfig, GameMode};
+    use crate::entities::Team;
+    use crate::math::{Rectangle, vec2};
+
+    use super::*;
+
+    const RED: usize = 0;
+    const BLUE: usize = 1;
+
+    fn tile(x: f32, y: f32) -> Vector2 {
+        vec2(x * TILE_SIZE, y * TILE_SIZE)
+    }
+
+    /// A one-on-one flag round on an open 20x10 tile map, Crimson's zone
+    /// on the left and Azure's on the right, both tanks mid-field.
+    fn game() -> Game {
+        let mut config = GameConfig::default();
+        config.round.mode = GameMode::CaptureTheFlag;
+        config.round.tanks_per_team = 1;
+        let mut game = Game::with_config(1, config);
+        let zone = |x, team| SpawnZone {
+            rect: Rectangle {
+                x: x * TILE_SIZE,
+                y: 3.0 * TILE_SIZE,
+                width: 2.0 * TILE_SIZE,
+                height: 4.0 * TILE_SIZE,
+            },
+            team,
+        };
+        game.world = World::open(
+            20,
+            10,
+            Vec::new(),
+            vec![zone(0.0, Team::Red), zone(18.0, Team::Blue)],
+        );
+        assert_eq!(game.tanks.len(), 2);
+        assert_eq!(game.tanks[RED].team, Team::Red);
+        game.tanks[RED].pos = tile(8.0, 5.0);
+        game.tanks[BLUE].pos = tile(12.0, 5.0);
+        CaptureTheFlag.reset(&mut game);
+        game
+    }
+
+    /// Runs the mode for one tick with `tank` moved to `pos` first.
+    fn step(game: &mut Game, tank: usize, pos: Vector2) {
+        game.tanks[tank].pos = pos;
+        game.events.clear();
+        CaptureTheFlag.update(game, 0.1);
+    }
+
+    #[test]
+    fn flags_stand_just_in_front_of_their_zones() {
+        let game = game();
+        assert_eq!(game.flags[RED].home, tile(2.75, 5.0));
+        assert_eq!(game.flags[BLUE].home, tile(17.25, 5.0));
+        assert!(game.flags.iter().all(|flag| flag.state == FlagState::Home));
+        assert_eq!(game.captures, [0, 0]);
+    }
+
+    #[test]
+    fn an_enemy_flag_is_picked_up_and_carried() {
+        let mut game = game();
+        let blue_home = game.flags[BLUE].home;
+        step(&mut game, RED, blue_home);
+        assert_eq!(game.flags[BLUE].state, FlagState::Carried(RED));
+        assert_eq!(
+            game.events,
+            [GameEvent::FlagTaken {
+                flag: Team::Blue,
+                tank: RED
+            }]
+        );
+
+        step(&mut game, RED, tile(10.0, 4.0));
+        assert_eq!(game.flags[BLUE].pos, tile(10.0, 4.0));
+        assert!(game.events.is_empty());
+
+        // Touching your own flag at home does nothing.
+        step(&mut game, BLUE, blue_home);
+        assert_eq!(game.flags[BLUE].state, FlagState::Carried(RED));
+    }
+
+    #[test]
+    fn a_destroyed_carrier_drops_the_flag_until_it_times_out() {
+        let mut game = game();
+        let blue_home = game.flags[BLUE].home;
+        step(&mut game, RED, blue_home);
+        game.tanks[RED].alive = false;
+        step(&mut game, RED, tile(10.0, 4.0));
+        let return_time = game.config.flag.return_time;
+        assert_eq!(game.flags[BLUE].pos, tile(10.0, 4.0));
+        assert_eq!(
+            game.flags[BLUE].state,
+            FlagState::Dropped {
+                return_timer: return_time
+            }
+        );
+        assert_eq!(game.events, [GameEvent::FlagDropped { flag: Team::Blue }]);
+
+        game.events.clear();
+        CaptureTheFlag.update(&mut game, return_time);
+        assert_eq!(game.flags[BLUE].state, FlagState::Home);
+        assert_eq!(game.flags[BLUE].pos, blue_home);
+        assert_eq!(
+            game.events,
+            [GameEvent::FlagReturned {
+                flag: Team::Blue,
+                tank: None
+            }]
+        );
+    }
+
+    #[test]
+    fn touching_your_own_dropped_flag_returns_it() {
+        let mut game = game();
+        let blue_home = game.flags[BLUE].home;
+        step(&mut game, RED, blue_home);
+        game.tanks[RED].alive = false;
+        step(&mut game, RED, tile(10.0, 4.0));
+
+        step(&mut game, BLUE, tile(10.0, 4.3));
+        assert_eq!(game.flags[BLUE].state, FlagState::Home);
+        assert_eq!(game.flags[BLUE].pos, blue_home);
+        assert_eq!(
+            game.events,
+            [GameEvent::FlagReturned {
+                flag: Team::Blue,
+                tank: Some(BLUE)
+            }]
+        );
+    }
+
+    #[test]
+    fn a_capture_needs_your_own_flag_at_home() {
+        let mut game = game();
+        let (red_home, blue_home) = (game.flags[RED].home, game.flags[BLUE].home);
+        step(&mut game, RED, blue_home);
+        step(&mut game, BLUE, red_home);
+        assert_eq!(game.flags[RED].state, FlagState::Carried(BLUE));
+
+        // Home with the enemy flag, but Crimson's own flag is away.
+        let red_zone = tile(1.0, 5.0);
+        step(&mut game, RED, red_zone);
+        assert_eq!(game.captures, [0, 0]);
+        assert_eq!(game.flags[BLUE].state, FlagState::Carried(RED));
+
+        // Once Azure's carrier is stopped and the flag returned, it counts.
+        game.tanks[BLUE].alive = false;
+        step(&mut game, BLUE, tile(10.0, 5.0));
+        game.tanks[RED].pos = tile(10.0, 5.2);
+        game.events.clear();
+        CaptureTheFlag.update(&mut game, 0.1);
+        assert_eq!(game.flags[RED].state, FlagState::Home);
+
+        step(&mut game, RED, red_zone);
+        assert_eq!(game.captures, [1, 0]);
+        assert_eq!(game.flags[BLUE].state, FlagState::Home);
+        assert_eq!(game.flags[BLUE].pos, blue_home);
+        assert!(game.events.contains(&GameEvent::FlagCaptured {
+            flag: Team::Blue,
+            tank: RED
+        }));
+    }
+
+    #[test]
+    fn the_round_is_decided_at_the_capture_target() {
+        let mut game = game();
+        game.config.flag.captures_to_win = 3;
+        assert!(!CaptureTheFlag.is_decided(&game));
+        game.captures = vec![2, 2];
+        assert!(!CaptureTheFlag.is_decided(&game));
+        game.captures = vec![2, 3];
+        assert!(CaptureTheFlag.is_decided(&game));
+
+        // With no target the round plays to the clock.
+        game.config.flag.captures_to_win = 0;
+        game.captures = vec![9, 0];
+        assert!(!CaptureTheFlag.is_decided(&game));
+    }
+}
@@ -0,0 +1,11 @@
+use super::super::Game;
+use super::Mode;
+
+/// Kills score, and the round always runs to the clock.
+pub(super) struct Deathmatch;
+
+impl Mode for Deathmatch {
+    fn scores<'a>(&self, game: &'a Game) -> &'a [u32] {
+        &game.team_kills
+    }
+}
@@ -0,0 +1,77 @@
+mod ctf;
+mod deathmatch;
+
+use crate::config::GameMode;
+use crate::entities::{Flag, Team};
+
+use super::Game;
+use ctf::CaptureTheFlag;
+use deathmatch::Deathmatch;
+
+/// The rules of one [`GameMode`]: what a round starts with, how it is
+/// scored and whether it is won before the clock runs out. Modes keep
+/// their state on [`Game`], so rounds still clone, save and travel in
+/// snapshots whatever the mode.
+pub(super) trait Mode {
+    /// Sets up the mode's pieces for a fresh round, after the map and the
+    /// tanks.
+    fn reset(&self, _game: &mut Game) {}
+
+    /// Applies the mode's rules after the tanks and bullets have moved.
+    fn update(&self, _game: &mut Game, _dt: f32) {}
+
+    /// Each team's score this round, indexed by [`Team::index`].
+    fn scores<'a>(&self, game: &'a Game) -> &'a [u32];
+
+    /// Whether a team has already won, ending the round early.
+    fn is_decided(&self, _game: &Game) -> bool {
+        false
+    }
+}
+
+fn rules(mode: GameMode) -> &'static dyn Mode {
+    match mode {
+        GameMode::Deathmatch => &Deathmatch,
+        GameMode::CaptureTheFlag => &CaptureTheFlag,
+    }
+}
+
+impl Game {
+    pub(super) fn rules(&self) -> &'static dyn Mode {
+        rules(self.mode)
+    }
+
+    /// The mode this round is played under. Changing the configured mode
+    /// takes effect from the next round.
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    /// Points each team has this round under its mode, kills or captures,
+    /// indexed by [`Team::index`].
+    pub fn scores(&self) -> &[u32] {
+        self.rules().scores(self)
+    }
+
+    /// The team ahead on [`Game::scores`], `None` on a tie.
+    pub(super) fn leader(&self) -> Option<Team> {
+        Team::leader(self.scores())
+    }
+
+    /// Every team's flag in a capture-the-flag round, empty otherwise.
+    pub fn flags(&self) -> &[Flag] {
+        &self.flags
+    }
+
+    /// The flag tank `index` is carrying.
+    pub fn carried_flag(&self, index: usize) -> Option<&Flag> {
+        self.flags.iter().find(|flag| flag.carrier() == Some(index))
+    }
+
+    pub(super) fn reset_mode(&mut self) {
+        self.mode = self.config.round.mode;
+        self.flags.clear();
+        self.captures.clear();
+        self.rules().reset(self);
+    }
+}
@@ -0,0 +1,222 @@
+use std::io;
+use std::str::FromStr;
+
+#[cfg(feature = "graphics")]
+use raylib::prelude::RaylibHandle;
+use serde::{Deserialize, Serialize};
+
+use crate::config::RoundConfig;
+use crate::entities::{Tank, Team};
+
+use super::Game;
+use super::input::InputBinding;
+#[cfg(feature = "graphics")]
+use super::input::InputState;
+#[cfg(feature = "graphics")]
+use super::{CameraView, TankInput, TickInput};
+
+pub const MAX_LOCAL_PLAYERS: usize = 4;
+
+/// A human at this machine: the team they fight for and the device they
+/// drive with. Each takes the first free tank of their team every round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalPlayer {
+    pub team: Team,
+    #[serde(default)]
+    pub binding: InputBinding,
+}
+
+impl FromStr for LocalPlayer {
+    type Err = String;
+
+    /// Parses `TEAM[:DEVICE]`, e.g. `red`, `blue:gamepad1` or
+    /// `crimson:keyboard`. The device defaults to `auto`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (team, binding) = match value.split_once(':') {
+            Some((team, device)) => (team, device.parse()?),
+            None => (value, InputBinding::Auto),
+        };
+        Ok(Self {
+            team: team.parse()?,
+            binding,
+        })
+    }
+}
+
+/// Checks that a lineup can be seated: no more than [`MAX_LOCAL_PLAYERS`],
+/// one keyboard, one player per gamepad, only teams that play and enough
+/// tanks on each.
+pub fn validate_players(players: &[LocalPlayer], round: &RoundConfig) -> io::Result<()> {
+    let invalid = |message: String| Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    if players.len() > MAX_LOCAL_PLAYERS {
+        return invalid(format!(
+            "at most {MAX_LOCAL_PLAYERS} local players are supported"
+        ));
+    }
+    let keyboards = players
+        .iter()
+        .filter(|player| !matches!(player.binding, InputBinding::Gamepad(_)))
+        .count();
+    if keyboards > 1 {
+        return invalid("only one local player can use the keyboard".to_string());
+    }
+    for (slot, player) in players.iter().enumerate() {
+        let gamepad = match player.binding {
+            InputBinding::Gamepad(id) => id,
+            InputBinding::Auto => 0,
+            InputBinding::Keyboard => continue,
+        };
+        let shared = players[..slot].iter().any(|other| match other.binding {
+            InputBinding::Gamepad(id) => id == gamepad,
+            InputBinding::Auto => gamepad == 0,
+            InputBinding::Keyboard => false,
+        });
+        if shared {
+            return invalid(format!(
+                "gamepad {gamepad} is bound to more than one player \
+                 (bind the keyboard player to `keyboard` rather than `auto`)"
+            ));
+        }
+    }
+    let teams = round.playing_teams();
+    if let Some(player) = players.iter().find(|player| !teams.contains(&player.team)) {
+        return invalid(format!(
+            "{} is not playing with round.teams = {}",
+            player.team.name(),
+            teams.len()
+        ));
+    }
+    let team_size = round.team_size();
+    for &team in teams {
+        let seated = players.iter().filter(|player| player.team == team).count();
+        if seated > team_size {
+            return invalid(format!(
+                "{seated} players on {} but it only has {team_size} tanks",
+                team.name()
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl Game {
+    /// Replaces the local lineup, seating the players from this round on.
+    pub fn set_local_players(&mut self, players: Vec<LocalPlayer>) {
+        #[cfg(feature = "graphics")]
+        {
+            self.input_states = players
+                .iter()
+                .map(|player| InputState::new(player.binding))
+                .collect();
+        }
+        self.players = players;
+        self.assign_players();
+    }
+
+    pub fn local_players(&self) -> &[LocalPlayer] {
+        &self.players
+    }
+
+    /// The tank local player `slot` drives this round, if they have one.
+    pub fn player_index(&self, slot: usize) -> Option<usize> {
+        self.player_tanks.get(slot).copied().flatten()
+    }
+
+    /// Puts local player `slot` in a specific tank, e.g. the one a server
+    /// assigned. Lasts until the next round is set up locally.
+    pub fn seat_player(&mut self, slot: usize, tank: Option<usize>) {
+        if self.player_tanks.len() <= slot {
+            self.player_tanks.resize(slot + 1, None);
+        }
+        self.player_tanks[slot] = tank;
+    }
+
+    pub(super) fn player_tank(&self, slot: usize) -> Option<&Tank> {
+        self.player_index(slot)
+            .and_then(|index| self.tanks.get(index))
+    }
+
+    /// Local players who have a tank, i.e. all of them unless the game is
+    /// AI only.
+    pub(super) fn seated_players(&self) -> usize {
+        if self.ai_only { 0 } else { self.players.len() }
+    }
+
+    #[cfg(feature = "graphics")]
+    pub(super) fn input_state(&self, slot: usize) -> Option<&InputState> {
+        self.input_states.get(slot)
+    }
+
+    #[cfg(feature = "graphics")]
+    pub(super) fn any_gamepad_available(&self) -> bool {
+        self.input_states
+            .iter()
+            .any(|state| state.gamepad_available())
+    }
+
+    pub(super) fn assign_players(&mut self) {
+        let mut taken = Vec::new();
+        self.player_tanks = self
+            .players
+            .iter()
+            .map(|player| {
+                if self.ai_only {
+                    return None;
+                }
+                let index = self.tanks.iter().enumerate().position(|(index, tank)| {
+                    tank.team == player.team && !taken.contains(&index)
+                })?;
+                taken.push(index);
+                Some(index)
+            })
+            .collect();
+    }
+
+    /// Samples every local player's devices into the input consumed by
+    /// [`Game::update`]. Mouse aim is mapped through the keyboard player's
+    /// own viewport.
+    #[cfg(feature = "graphics")]
+    pub fn sample_input(
+        &mut self,
+        rl: &RaylibHandle,
+        screen_width: i32,
+        screen_height: i32,
+    ) -> TickInput {
+        // States are not saved, so rebuild them after a load.
+        if self.input_states.len() != self.players.len() {
+            self.input_states = self
+                .players
+                .iter()
+                .map(|player| InputState::new(player.binding))
+                .collect();
+        }
+
+        let mut input = TickInput::default();
+        for viewport in self.viewports(screen_width, screen_height) {
+            let Some(slot) = viewport.slot else {
+                continue;
+            };
+            let camera = self.camera(&viewport, 1.0, CameraView::Player);
+            let tank = self.player_index(slot);
+            let player_pos = self
+                .player_tank(slot)
+                .filter(|tank| tank.alive)
+                .map(|tank| tank.pos);
+            let state = &mut self.input_states[slot];
+            input.start_pressed |= state.start_pressed(rl);
+            let player = state.player_input(rl, camera, player_pos);
+            if let Some(tank) = tank {
+                input.tanks.push(TankInput {
+                    tank,
+                    input: player,
+                });
+            }
+        }
+        if self.seated_players() == 0 {
+            for state in &mut self.input_states {
+                input.start_pressed |= state.start_pressed(rl);
+            }
+        }
+        input
+    }
+}
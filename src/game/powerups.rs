@@ -1,14 +1,11 @@
 use rand::Rng;
-use raylib::prelude::Vector2;
 
-use crate::config::{
-    HEALTH_FLASH_TIME, POWERUP_BASE_SPAWN, POWERUP_DURATION, POWERUP_MAX_COUNT, POWERUP_MAX_SPAWN,
-    POWERUP_MIN_SPAWN, ROUND_TIME, TANK_RADIUS,
-};
+use crate::config::{GameConfig, TANK_RADIUS};
 use crate::entities::{Powerup, PowerupKind, Tank};
-use crate::math::{vec2, vec2_distance};
+use crate::math::{Vector2, vec2, vec2_distance};
 
 use super::Game;
+use super::events::GameEvent;
 
 impl Game {
     pub(super) fn update_powerups(&mut self, dt: f32) {
@@ -17,7 +14,7 @@ impl Game {
         }
 
         self.powerup_spawn_timer -= dt;
-        if self.powerup_spawn_timer <= 0.0 && self.powerups.len() < POWERUP_MAX_COUNT {
+        if self.powerup_spawn_timer <= 0.0 && self.powerups.len() < self.config.powerup.max_count {
             if let Some(pos) = self.find_powerup_spawn() {
                 let kind = match self.rng.random_range(0..3) {
                     0 => PowerupKind::Invincible,
@@ -44,9 +41,13 @@ impl Game {
         }
         let mut remaining = Vec::with_capacity(self.powerups.len());
         'outer: for powerup in self.powerups.drain(..) {
-            for tank in &mut self.tanks {
+            for (index, tank) in self.tanks.iter_mut().enumerate() {
                 if tank.alive && vec2_distance(powerup.pos, tank.pos) < TANK_RADIUS + 22.0 {
-                    apply_powerup(tank, powerup.kind);
+                    apply_powerup(tank, powerup.kind, &self.config);
+                    self.events.push(GameEvent::Pickup {
+                        tank: index,
+                        kind: powerup.kind,
+                    });
                     continue 'outer;
                 }
             }
@@ -56,10 +57,12 @@ impl Game {
     }
 
     fn next_powerup_spawn_delay(&mut self) -> f32 {
-        let progress = (1.0 - self.round_timer / ROUND_TIME).clamp(0.0, 1.0);
+        let config = &self.config;
+        let progress = (1.0 - self.round_timer / config.round.time).clamp(0.0, 1.0);
         let edge = (progress - 0.5).abs() * 2.0;
         let boost = 0.7 + edge * 0.8;
-        let target = (POWERUP_BASE_SPAWN / boost).clamp(POWERUP_MIN_SPAWN, POWERUP_MAX_SPAWN);
+        let target = (config.powerup.base_spawn / boost)
+            .clamp(config.powerup.min_spawn, config.powerup.max_spawn);
         self.rng.random_range(target * 0.85..target * 1.15)
     }
 
@@ -98,17 +101,17 @@ impl Game {
     }
 }
 
-fn apply_powerup(tank: &mut Tank, kind: PowerupKind) {
+fn apply_powerup(tank: &mut Tank, kind: PowerupKind, config: &GameConfig) {
     match kind {
         PowerupKind::Invincible => {
-            tank.invincible_timer = POWERUP_DURATION;
+            tank.invincible_timer = config.powerup.duration;
         }
         PowerupKind::RapidRange => {
-            tank.rapid_timer = POWERUP_DURATION;
+            tank.rapid_timer = config.powerup.duration;
         }
         PowerupKind::Heal => {
             tank.health = tank.max_health;
-            tank.health_flash = HEALTH_FLASH_TIME;
+            tank.health_flash = config.tank.health_flash_time;
         }
     }
 }
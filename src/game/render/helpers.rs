@@ -108,15 +108,15 @@ pub(super) fn measure_text_width(text: &str, size: i32) -> i32 {
     unsafe { ffi::MeasureText(c_text.as_ptr(), size) }
 }
 
-pub(super) fn draw_tank_health<D: RaylibDraw>(d: &mut D, tank: &Tank) {
+pub(super) fn draw_tank_health<D: RaylibDraw>(d: &mut D, tank: &Tank, pos: Vector2) {
     if tank.health >= tank.max_health || tank.health_flash <= 0.0 {
         return;
     }
     let pct = (tank.health / tank.max_health).clamp(0.0, 1.0);
     let bar_w = 44.0;
     let bar_h = 6.0;
-    let x = tank.pos.x - bar_w * 0.5;
-    let y = tank.pos.y - TANK_RADIUS - 16.0;
+    let x = pos.x - bar_w * 0.5;
+    let y = pos.y - TANK_RADIUS - 16.0;
     d.draw_rectangle(
         x as i32,
         y as i32,
@@ -133,14 +133,52 @@ pub(super) fn draw_tank_health<D: RaylibDraw>(d: &mut D, tank: &Tank) {
     );
 }
 
-pub(super) fn draw_powerup_markers<D: RaylibDraw>(d: &mut D, tank: &Tank) {
+/// Health and reload bars over a tank, always shown, for spectators.
+pub(super) fn draw_tank_status<D: RaylibDraw>(d: &mut D, tank: &Tank, pos: Vector2, reload: f32) {
+    let pct = (tank.health / tank.max_health).clamp(0.0, 1.0);
+    let bar_w = 44.0;
+    let x = pos.x - bar_w * 0.5;
+    let y = pos.y - TANK_RADIUS - 18.0;
+    d.draw_rectangle(
+        x as i32,
+        y as i32,
+        bar_w as i32,
+        10,
+        Color::new(10, 10, 10, 190),
+    );
+    d.draw_rectangle(
+        (x + 1.0) as i32,
+        (y + 1.0) as i32,
+        ((bar_w - 2.0) * pct) as i32,
+        4,
+        tank.team.color(),
+    );
+    d.draw_rectangle(
+        (x + 1.0) as i32,
+        (y + 6.0) as i32,
+        ((bar_w - 2.0) * reload) as i32,
+        3,
+        reload_color(reload),
+    );
+}
+
+/// Amber while reloading, white once ready to fire.
+pub(super) fn reload_color(reload: f32) -> Color {
+    if reload >= 1.0 {
+        Color::new(240, 240, 240, 230)
+    } else {
+        Color::new(240, 190, 90, 230)
+    }
+}
+
+pub(super) fn draw_powerup_markers<D: RaylibDraw>(d: &mut D, tank: &Tank, pos: Vector2) {
     let mut ring = 0.0;
     if tank.invincible_timer > 0.0 {
         ring += 1.0;
         let pulse = (tank.invincible_timer * 4.0).sin().abs();
         d.draw_circle_lines(
-            tank.pos.x as i32,
-            tank.pos.y as i32,
+            pos.x as i32,
+            pos.y as i32,
             TANK_RADIUS + 8.0 + pulse * 4.0,
             invincible_color(220),
         );
@@ -149,14 +187,27 @@ pub(super) fn draw_powerup_markers<D: RaylibDraw>(d: &mut D, tank: &Tank) {
         ring += 1.0;
         let pulse = (tank.rapid_timer * 5.0).sin().abs();
         d.draw_circle_lines(
-            tank.pos.x as i32,
-            tank.pos.y as i32,
+            pos.x as i32,
+            pos.y as i32,
             TANK_RADIUS + 4.0 + ring * 6.0 + pulse * 3.0,
             rapid_color(220),
         );
     }
 }
 
+/// A pennant on a pole standing at `base`, `height` tall.
+pub(super) fn draw_flag<D: RaylibDraw>(d: &mut D, base: Vector2, height: f32, color: Color) {
+    let top = vec2(base.x, base.y - height);
+    let pole = Color::new(70, 60, 50, color.a);
+    d.draw_line_ex(base, top, (height * 0.07).max(1.5), pole);
+    d.draw_triangle(
+        top,
+        vec2(top.x, top.y + height * 0.45),
+        vec2(top.x + height * 0.6, top.y + height * 0.22),
+        color,
+    );
+}
+
 pub(super) fn draw_powerup<D: RaylibDraw>(d: &mut D, assets: &Assets, powerup: &Powerup) {
     let frames = match powerup.kind {
         PowerupKind::Invincible => &assets.smoke.white,
@@ -206,3 +257,23 @@ pub(super) fn draw_text_centered_screen<D: RaylibDraw>(
     let width = measure_text_width(text, size);
     d.draw_text(text, (screen_width - width) / 2, y, size, color);
 }
+
+/// Draws `text` centred horizontally within `bounds`, `y` pixels below its
+/// top edge.
+pub(super) fn draw_text_centered_in<D: RaylibDraw>(
+    d: &mut D,
+    text: &str,
+    bounds: Rectangle,
+    y: i32,
+    size: i32,
+    color: Color,
+) {
+    let width = measure_text_width(text, size);
+    d.draw_text(
+        text,
+        bounds.x as i32 + (bounds.width as i32 - width) / 2,
+        bounds.y as i32 + y,
+        size,
+        color,
+    );
+}
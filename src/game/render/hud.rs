@@ -1,48 +1,180 @@
-use raylib::prelude::{Color, RaylibDraw};
+use raylib::prelude::{Color, RaylibDraw, Rectangle};
 
-use crate::entities::{Tank, Team};
-use crate::game::input::InputDevice;
+use crate::entities::{FlagState, Tank, Team};
+use crate::game::input::{InputBinding, InputDevice};
+use crate::math::vec2;
 
+use super::super::constants::HUD_BAR_HEIGHT;
+use super::super::{CameraView, Viewport};
 use super::Game;
-use super::helpers::{draw_text_centered_screen, measure_text_width};
+use super::helpers::{
+    draw_flag, draw_text_centered_in, draw_text_centered_screen, measure_text_width, reload_color,
+};
+
+/// Top of the flag status strip, below the score bar and the hull gauge.
+const FLAG_STRIP_Y: i32 = HUD_BAR_HEIGHT as i32 + 36;
 
 impl Game {
-    pub(super) fn draw_hud<D: RaylibDraw>(&self, d: &mut D, screen_width: i32, screen_height: i32) {
-        let bar_height = 48;
-        d.draw_rectangle(0, 0, screen_width, bar_height, Color::new(20, 24, 28, 220));
-        let red_label = format!("{}: {}", Team::Red.name(), self.team_kills[0]);
-        let blue_label = format!("{}: {}", Team::Blue.name(), self.team_kills[1]);
-        d.draw_text(&red_label, 20, 12, 20, Team::Red.color());
-        let blue_width = measure_text_width(&blue_label, 20);
-        d.draw_text(
-            &blue_label,
-            screen_width - blue_width - 20,
-            12,
-            20,
-            Team::Blue.color(),
+    pub(super) fn draw_hud<D: RaylibDraw>(&self, d: &mut D, viewport: &Viewport, view: CameraView) {
+        let bounds = viewport.rect;
+        let (x, y, width, _) = viewport.bounds();
+        d.draw_rectangle(
+            x,
+            y,
+            width,
+            HUD_BAR_HEIGHT as i32,
+            Color::new(20, 24, 28, 220),
         );
+        self.draw_scores(d, viewport);
+        self.draw_flag_status(d, viewport);
         let time_label = format!("Time: {:>3.0}", self.round_timer.ceil());
-        let time_width = measure_text_width(&time_label, 20);
-        d.draw_text(
+        draw_text_centered_in(
+            d,
             &time_label,
-            (screen_width - time_width) / 2,
+            bounds,
             12,
             20,
             Color::new(240, 240, 240, 255),
         );
 
         if self.countdown_timer > 0.0 {
-            self.draw_countdown(d, screen_width, screen_height);
+            self.draw_countdown(d, viewport);
+        }
+        if view.is_spectating() {
+            self.draw_spectator_panel(d, viewport, view);
+            return;
         }
 
-        if let Some(player) = self.tanks.get(self.player_index) {
-            self.draw_player_health(d, player);
+        let Some(slot) = viewport.slot else {
+            return;
+        };
+        if let Some(flag) = self
+            .player_index(slot)
+            .and_then(|index| self.carried_flag(index))
+        {
+            let text = format!("You have the {} flag! Bring it home", flag.team.name());
+            draw_text_centered_in(
+                d,
+                &text,
+                bounds,
+                FLAG_STRIP_Y + 34,
+                22,
+                Color::new(255, 230, 120, 240),
+            );
+        }
+        if let Some(player) = self.player_tank(slot) {
+            let label = if self.seated_players() > 1 {
+                format!("P{} Hull", slot + 1)
+            } else {
+                "Hull".to_string()
+            };
+            self.draw_player_health(d, player, &label, x + 20, y + 58);
             if !player.alive {
-                self.draw_respawn_notice(d, player.respawn_timer, screen_width, screen_height);
+                self.draw_respawn_notice(d, player.respawn_timer, bounds);
             }
         }
     }
 
+    /// Team scores along the score bar, the first half of the teams
+    /// from the left edge and the rest from the right, leaving the middle
+    /// to the clock. Crowded bars fall back to smaller text, then to bare
+    /// numbers in team colours.
+    fn draw_scores<D: RaylibDraw>(&self, d: &mut D, viewport: &Viewport) {
+        let (x, y, width, _) = viewport.bounds();
+        let teams: Vec<Team> = self.world.teams().collect();
+        let score = |team: Team| self.scores().get(team.index()).copied().unwrap_or(0);
+        let (left, right) = teams.split_at(teams.len().div_ceil(2));
+        let gap = 24;
+        let room = width / 2 - 90;
+        let styles = [(true, 20), (true, 16), (false, 20)];
+        let fits = |named: bool, size: i32| {
+            [left, right].iter().all(|side| {
+                let labels: i32 = side
+                    .iter()
+                    .map(|&team| measure_text_width(&score_label(team, score(team), named), size))
+                    .sum();
+                labels + gap * (side.len() as i32 - 1) <= room
+            })
+        };
+        let (named, size) = styles
+            .into_iter()
+            .find(|&(named, size)| fits(named, size))
+            .unwrap_or((false, 16));
+        let text_y = y + (HUD_BAR_HEIGHT as i32 - size) / 2;
+
+        let mut cursor = x + 20;
+        for &team in left {
+            let label = score_label(team, score(team), named);
+            d.draw_text(&label, cursor, text_y, size, team.color());
+            cursor += measure_text_width(&label, size) + gap;
+        }
+        let mut cursor = x + width - 20;
+        for &team in right.iter().rev() {
+            let label = score_label(team, score(team), named);
+            cursor -= measure_text_width(&label, size);
+            d.draw_text(&label, cursor, text_y, size, team.color());
+            cursor -= gap;
+        }
+    }
+
+    /// Where every flag is in a capture-the-flag round, in a strip under
+    /// the score bar: home, taken, or dropped with the seconds until it
+    /// goes home by itself.
+    fn draw_flag_status<D: RaylibDraw>(&self, d: &mut D, viewport: &Viewport) {
+        if self.flags.is_empty() {
+            return;
+        }
+        let (x, y, width, _) = viewport.bounds();
+        let size = 16;
+        let icon = 14;
+        let gap = 22;
+        let entries: Vec<(Team, String, Color)> = self
+            .flags
+            .iter()
+            .map(|flag| match flag.state {
+                FlagState::Home => (
+                    flag.team,
+                    "home".to_string(),
+                    Color::new(220, 220, 220, 230),
+                ),
+                FlagState::Carried(_) => (
+                    flag.team,
+                    "taken".to_string(),
+                    Color::new(240, 120, 100, 240),
+                ),
+                FlagState::Dropped { return_timer } => {
+                    let seconds = return_timer.ceil().max(1.0) as i32;
+                    (
+                        flag.team,
+                        format!("dropped {seconds}"),
+                        Color::new(240, 190, 90, 240),
+                    )
+                }
+            })
+            .collect();
+        let entry_width = |text: &str| icon + 6 + measure_text_width(text, size);
+        let total = entries
+            .iter()
+            .map(|(_, text, _)| entry_width(text))
+            .sum::<i32>()
+            + gap * (entries.len() as i32 - 1);
+        let strip_y = y + FLAG_STRIP_Y;
+        d.draw_rectangle(
+            x + (width - total) / 2 - 10,
+            strip_y,
+            total + 20,
+            size + 10,
+            Color::new(20, 24, 28, 190),
+        );
+        let mut cursor = x + (width - total) / 2;
+        for (team, text, color) in &entries {
+            let base = vec2(cursor as f32 + 2.0, (strip_y + size + 6) as f32);
+            draw_flag(d, base, icon as f32 + 4.0, team.color());
+            d.draw_text(text, cursor + icon + 6, strip_y + 5, size, *color);
+            cursor += entry_width(text) + gap;
+        }
+    }
+
     pub(super) fn draw_round_over<D: RaylibDraw>(
         &self,
         d: &mut D,
@@ -69,7 +201,7 @@ impl Game {
             Color::new(240, 240, 240, 255),
             screen_width,
         );
-        let prompt = if self.input_state.gamepad_available() {
+        let prompt = if self.any_gamepad_available() {
             "Press ENTER or START/A to redeploy"
         } else {
             "Press ENTER to redeploy"
@@ -85,32 +217,134 @@ impl Game {
         );
     }
 
-    fn draw_respawn_notice<D: RaylibDraw>(
+    /// Both teams' tanks down the sides, each with its health, reload and
+    /// respawn timer, plus what the camera is showing and the keys.
+    fn draw_spectator_panel<D: RaylibDraw>(
         &self,
         d: &mut D,
-        timer: f32,
-        screen_width: i32,
-        screen_height: i32,
+        viewport: &Viewport,
+        view: CameraView,
     ) {
+        let bounds = viewport.rect;
+        let (x, y, width, height) = viewport.bounds();
+        let followed = match view {
+            CameraView::Follow { tank } => Some(tank),
+            _ => None,
+        };
+        let caption = match followed.and_then(|index| Some((index, self.tanks.get(index)?))) {
+            Some((index, tank)) => format!("Following {} tank {}", tank.team.name(), index + 1),
+            None => "Overhead view".to_string(),
+        };
+        draw_text_centered_in(
+            d,
+            &caption,
+            bounds,
+            HUD_BAR_HEIGHT as i32 + 10,
+            20,
+            Color::new(240, 240, 240, 230),
+        );
+
+        let row_width = 170;
+        let row_height = 26;
+        // Teams alternate between the left and right columns.
+        let mut column_y = [y + HUD_BAR_HEIGHT as i32 + 12; 2];
+        for (order, team) in self.world.teams().enumerate() {
+            let side = order % 2;
+            let column = match side {
+                0 => x + 12,
+                _ => x + width - row_width - 12,
+            };
+            let mut row_y = column_y[side];
+            for (index, tank) in self.tanks.iter().enumerate() {
+                if tank.team != team {
+                    continue;
+                }
+                d.draw_rectangle(
+                    column,
+                    row_y,
+                    row_width,
+                    row_height - 4,
+                    Color::new(20, 24, 28, 190),
+                );
+                if followed == Some(index) {
+                    d.draw_rectangle_lines(
+                        column,
+                        row_y,
+                        row_width,
+                        row_height - 4,
+                        Color::new(255, 230, 120, 220),
+                    );
+                }
+                let label = format!("{}", index + 1);
+                d.draw_text(&label, column + 6, row_y + 3, 16, team.color());
+                if let Some(flag) = self.carried_flag(index) {
+                    let base = vec2((column + 25) as f32, (row_y + 18) as f32);
+                    draw_flag(d, base, 14.0, flag.team.color());
+                }
+                let bar_x = column + 34;
+                let bar_width = row_width - 42;
+                if tank.alive {
+                    let health = (tank.health / tank.max_health).clamp(0.0, 1.0);
+                    let reload = self.reload_progress(tank);
+                    d.draw_rectangle(
+                        bar_x,
+                        row_y + 4,
+                        (bar_width as f32 * health) as i32,
+                        8,
+                        team.color(),
+                    );
+                    d.draw_rectangle(
+                        bar_x,
+                        row_y + 14,
+                        (bar_width as f32 * reload) as i32,
+                        3,
+                        reload_color(reload),
+                    );
+                } else {
+                    let text = format!("respawn {}", tank.respawn_timer.ceil().max(1.0) as i32);
+                    d.draw_text(&text, bar_x, row_y + 3, 16, Color::new(170, 170, 170, 230));
+                }
+                row_y += row_height;
+            }
+            column_y[side] = row_y + 8;
+        }
+
+        let hint = "LEFT/RIGHT or Q/E cycle tanks • TAB overhead view";
+        draw_text_centered_in(
+            d,
+            hint,
+            bounds,
+            height - 30,
+            16,
+            Color::new(220, 220, 220, 200),
+        );
+    }
+
+    fn draw_respawn_notice<D: RaylibDraw>(&self, d: &mut D, timer: f32, bounds: Rectangle) {
         let remaining = timer.ceil().max(1.0) as i32;
-        d.draw_rectangle(0, 0, screen_width, screen_height, Color::new(8, 8, 12, 150));
+        d.draw_rectangle_rec(bounds, Color::new(8, 8, 12, 150));
         let text = format!("Eliminated! Respawning in {remaining}");
         let size = 38;
-        draw_text_centered_screen(
+        draw_text_centered_in(
             d,
             &text,
-            screen_height / 2 - 20,
+            bounds,
+            bounds.height as i32 / 2 - 20,
             size,
             Color::new(240, 210, 120, 240),
-            screen_width,
         );
     }
 
-    fn draw_player_health<D: RaylibDraw>(&self, d: &mut D, player: &Tank) {
+    fn draw_player_health<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        player: &Tank,
+        label: &str,
+        x: i32,
+        y: i32,
+    ) {
         let bar_width = 260;
         let bar_height = 14;
-        let x = 20;
-        let y = 58;
         let pct = (player.health / player.max_health).clamp(0.0, 1.0);
         d.draw_rectangle(x, y, bar_width, bar_height, Color::new(10, 10, 10, 200));
         d.draw_rectangle(
@@ -124,38 +358,54 @@ impl Game {
                 player.team.color()
             },
         );
-        d.draw_text("Hull", x, y - 18, 16, Color::new(230, 230, 230, 220));
+        d.draw_text(label, x, y - 18, 16, Color::new(230, 230, 230, 220));
     }
 
-    fn draw_countdown<D: RaylibDraw>(&self, d: &mut D, screen_width: i32, screen_height: i32) {
+    fn draw_countdown<D: RaylibDraw>(&self, d: &mut D, viewport: &Viewport) {
+        let height = viewport.rect.height as i32;
         let count = self.countdown_timer.ceil().max(1.0) as i32;
         let text = format!("Deploying in {count}");
         let size = 44;
-        draw_text_centered_screen(
+        draw_text_centered_in(
             d,
             &text,
-            screen_height / 2 - 80,
+            viewport.rect,
+            height / 2 - 80,
             size,
             Color::new(255, 230, 120, 240),
-            screen_width,
         );
-        let hint = if self.input_state.gamepad_available()
-            && self.input_state.last_device() == InputDevice::Gamepad
-        {
-            "Left Stick to move • Right Stick to aim • RT to fire"
-        } else if self.input_state.gamepad_available() {
-            "WASD/Arrows or Left Stick to move • Mouse or Right Stick to aim • LMB/Space or RT to fire"
-        } else {
-            "WASD/Arrows to move • Mouse to aim • LMB/Space to fire"
+        let Some(state) = viewport.slot.and_then(|slot| self.input_state(slot)) else {
+            return;
+        };
+        let hint = match state.binding() {
+            InputBinding::Keyboard => "WASD/Arrows to move • Mouse to aim • LMB/Space to fire",
+            InputBinding::Gamepad(_) => "Left Stick to move • Right Stick to aim • RT to fire",
+            InputBinding::Auto
+                if state.gamepad_available() && state.last_device() == InputDevice::Gamepad =>
+            {
+                "Left Stick to move • Right Stick to aim • RT to fire"
+            }
+            InputBinding::Auto if state.gamepad_available() => {
+                "WASD/Arrows or Left Stick to move • Mouse or Right Stick to aim • LMB/Space or RT to fire"
+            }
+            InputBinding::Auto => "WASD/Arrows to move • Mouse to aim • LMB/Space to fire",
         };
         let hint_size = 18;
-        draw_text_centered_screen(
+        draw_text_centered_in(
             d,
             hint,
-            screen_height / 2 - 32,
+            viewport.rect,
+            height / 2 - 32,
             hint_size,
             Color::new(230, 230, 230, 220),
-            screen_width,
         );
     }
 }
+
+fn score_label(team: Team, score: u32, named: bool) -> String {
+    if named {
+        format!("{}: {score}", team.name())
+    } else {
+        score.to_string()
+    }
+}
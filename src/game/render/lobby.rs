@@ -0,0 +1,78 @@
+use raylib::prelude::{Color, RaylibDraw};
+
+use super::Game;
+use super::helpers::{draw_text_centered_screen, measure_text_width};
+
+impl Game {
+    /// The lobby: a menu down the left, the players on the right.
+    pub(super) fn draw_lobby<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        screen_width: i32,
+        screen_height: i32,
+    ) {
+        d.clear_background(Color::new(30, 85, 140, 255));
+        let Some(lobby) = &self.lobby else {
+            return;
+        };
+        draw_text_centered_screen(
+            d,
+            &lobby.heading,
+            40,
+            40,
+            Color::new(245, 245, 245, 255),
+            screen_width,
+        );
+
+        let line_height = 34;
+        let top = 130;
+        let left = 80;
+        for (index, line) in lobby.menu.iter().enumerate() {
+            let y = top + index as i32 * line_height;
+            if lobby.selected == Some(index) {
+                let width = measure_text_width(line, 24);
+                d.draw_rectangle(
+                    left - 12,
+                    y - 5,
+                    width + 24,
+                    34,
+                    Color::new(10, 40, 80, 200),
+                );
+                d.draw_text(line, left, y, 24, Color::new(240, 200, 110, 255));
+            } else {
+                d.draw_text(line, left, y, 24, Color::new(220, 220, 220, 255));
+            }
+        }
+
+        if !lobby.players.is_empty() {
+            let column = screen_width / 2 + 40;
+            d.draw_text("Players", column, top, 24, Color::new(245, 245, 245, 255));
+            for (index, player) in lobby.players.iter().enumerate() {
+                let y = top + (index as i32 + 1) * line_height + 6;
+                let name = if lobby.you == Some(index) {
+                    format!("{} (you)", player.name)
+                } else {
+                    player.name.clone()
+                };
+                d.draw_rectangle(column, y + 2, 16, 16, player.team.color());
+                d.draw_text(&name, column + 28, y, 22, Color::new(235, 235, 235, 255));
+                let (status, color) = if player.ready {
+                    ("READY", Color::new(120, 230, 160, 255))
+                } else {
+                    ("not ready", Color::new(170, 170, 170, 255))
+                };
+                let width = measure_text_width(status, 22);
+                d.draw_text(status, screen_width - 80 - width, y, 22, color);
+            }
+        }
+
+        draw_text_centered_screen(
+            d,
+            &lobby.footer,
+            screen_height - 60,
+            20,
+            Color::new(220, 200, 120, 255),
+            screen_width,
+        );
+    }
+}
@@ -1,13 +1,14 @@
 mod helpers;
 mod hud;
+mod lobby;
 mod title;
 mod world;
 
-use raylib::prelude::RaylibDraw;
+use raylib::prelude::{Color, RaylibDraw, RaylibScissorModeExt};
 
 use crate::assets::Assets;
 
-use super::{Game, ScreenState};
+use super::{CameraView, Game, ScreenState, Viewport};
 
 impl Game {
     pub fn draw<D: RaylibDraw>(
@@ -16,17 +17,68 @@ impl Game {
         assets: &Assets,
         screen_width: i32,
         screen_height: i32,
+        blend: f32,
+        view: CameraView,
     ) {
-        d.clear_background(raylib::prelude::Color::new(32, 96, 160, 255));
+        d.clear_background(Color::new(32, 96, 160, 255));
         match self.state {
             ScreenState::Title => self.draw_title(d, assets, screen_width, screen_height),
+            ScreenState::Lobby => self.draw_lobby(d, screen_width, screen_height),
             ScreenState::Playing | ScreenState::RoundOver => {
-                self.draw_world(d, assets, screen_width, screen_height);
-                self.draw_hud(d, screen_width, screen_height);
+                let viewports = match view {
+                    CameraView::Player => self.viewports(screen_width, screen_height),
+                    CameraView::Free { .. } | CameraView::Follow { .. } | CameraView::Overhead => {
+                        vec![Viewport::full(screen_width, screen_height, None)]
+                    }
+                };
+                for viewport in &viewports {
+                    self.draw_viewport(d, assets, viewport, blend, view);
+                }
+                if viewports.len() > 1 {
+                    for viewport in &viewports {
+                        d.draw_rectangle_lines_ex(viewport.rect, 2.0, Color::new(12, 14, 18, 255));
+                    }
+                }
                 if self.state == ScreenState::RoundOver {
                     self.draw_round_over(d, screen_width, screen_height);
                 }
             }
         }
     }
+
+    /// A plain screen with one line of text, for when there is no round to
+    /// show yet, e.g. while joining a server.
+    pub fn draw_notice<D: RaylibDraw>(
+        d: &mut D,
+        message: &str,
+        screen_width: i32,
+        screen_height: i32,
+    ) {
+        d.clear_background(Color::new(30, 85, 140, 255));
+        helpers::draw_text_centered_screen(
+            d,
+            message,
+            screen_height / 2 - 14,
+            28,
+            Color::WHITE,
+            screen_width,
+        );
+    }
+
+    /// The world and HUD as seen from one viewport, clipped to it.
+    fn draw_viewport<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        assets: &Assets,
+        viewport: &Viewport,
+        blend: f32,
+        view: CameraView,
+    ) {
+        let camera = self.camera(viewport, blend, view);
+        let (x, y, width, height) = viewport.bounds();
+        d.draw_scissor_mode(x, y, width, height, |mut d| {
+            self.draw_world(&mut d, assets, camera, viewport.slot, blend, view);
+            self.draw_hud(&mut d, viewport, view);
+        });
+    }
 }
@@ -37,7 +37,7 @@ impl Game {
             screen_width,
         );
 
-        let info = if self.input_state.gamepad_available() {
+        let info = if self.any_gamepad_available() {
             "Press ENTER or START/A to deploy"
         } else {
             "Press ENTER to deploy"
@@ -1,13 +1,16 @@
-use raylib::prelude::{Color, RaylibDraw, RaylibMode2DExt};
+use raylib::prelude::{Camera2D, Color, RaylibDraw, RaylibMode2DExt};
 
 use crate::assets::{Assets, bullet_palette, obstacle_texture, tank_palette};
 use crate::config::{TANK_RADIUS, TILE_SIZE, TRACK_LIFE};
+use crate::entities::FlagState;
 use crate::math::{vec2, vec2_angle, with_alpha};
 
+use super::super::CameraView;
+use super::super::constants::FLAG_HEIGHT;
 use super::Game;
 use super::helpers::{
-    draw_barrel, draw_powerup, draw_powerup_markers, draw_tank_health, draw_texture_centered,
-    explosion_frame, measure_text_width, sprite_rotation,
+    draw_barrel, draw_flag, draw_powerup, draw_powerup_markers, draw_tank_health, draw_tank_status,
+    draw_texture_centered, explosion_frame, measure_text_width, sprite_rotation,
 };
 
 impl Game {
@@ -15,10 +18,15 @@ impl Game {
         &self,
         d: &mut D,
         assets: &Assets,
-        screen_width: i32,
-        screen_height: i32,
+        camera: Camera2D,
+        slot: Option<usize>,
+        blend: f32,
+        view: CameraView,
     ) {
-        let camera = self.camera(screen_width, screen_height);
+        let followed = match view {
+            CameraView::Follow { tank } => Some(tank),
+            _ => None,
+        };
         d.draw_mode2D(camera, |mut d2, _| {
             for y in 0..self.world.height {
                 for x in 0..self.world.width {
@@ -63,10 +71,30 @@ impl Game {
                 draw_powerup(&mut d2, assets, powerup);
             }
 
-            for tank in &self.tanks {
+            for flag in &self.flags {
+                let stand = with_alpha(flag.team.color(), 0.45);
+                d2.draw_circle_lines(flag.home.x as i32, flag.home.y as i32, 18.0, stand);
+                match flag.state {
+                    FlagState::Home => {
+                        draw_flag(&mut d2, flag.pos, FLAG_HEIGHT, flag.team.color());
+                    }
+                    FlagState::Dropped { return_timer } => {
+                        let pulse = (return_timer * 5.0).sin().abs();
+                        let color = with_alpha(flag.team.color(), 0.45 + pulse * 0.55);
+                        draw_flag(&mut d2, flag.pos, FLAG_HEIGHT, color);
+                    }
+                    // Drawn over the carrier below.
+                    FlagState::Carried(_) => {}
+                }
+            }
+
+            for (index, tank) in self.tanks.iter().enumerate() {
                 if !tank.alive {
                     continue;
                 }
+                let pos = tank.render_pos(blend);
+                let body_angle = tank.render_body_angle(blend);
+                let turret_angle = tank.render_turret_angle(blend);
                 let palette = tank_palette(assets, tank.team);
                 let tread_texture = if (tank.tread_phase as i32) % 2 == 0 {
                     &assets.tracks_large
@@ -76,48 +104,69 @@ impl Game {
                 draw_texture_centered(
                     &mut d2,
                     tread_texture,
-                    tank.pos,
-                    sprite_rotation(tank.body_angle),
+                    pos,
+                    sprite_rotation(body_angle),
                     with_alpha(Color::new(160, 160, 160, 255), 0.35),
                 );
 
                 draw_texture_centered(
                     &mut d2,
                     &palette.outline_body,
-                    vec2(tank.pos.x + 2.0, tank.pos.y + 2.0),
-                    sprite_rotation(tank.body_angle),
+                    vec2(pos.x + 2.0, pos.y + 2.0),
+                    sprite_rotation(body_angle),
                     Color::new(0, 0, 0, 90),
                 );
                 draw_texture_centered(
                     &mut d2,
                     &palette.body,
-                    tank.pos,
-                    sprite_rotation(tank.body_angle),
+                    pos,
+                    sprite_rotation(body_angle),
                     Color::WHITE,
                 );
                 draw_barrel(
                     &mut d2,
                     &palette.outline_barrel,
-                    tank.pos,
-                    tank.turret_angle,
+                    pos,
+                    turret_angle,
                     Color::new(0, 0, 0, 90),
                 );
-                draw_barrel(
-                    &mut d2,
-                    &palette.barrel,
-                    tank.pos,
-                    tank.turret_angle,
-                    Color::WHITE,
-                );
+                draw_barrel(&mut d2, &palette.barrel, pos, turret_angle, Color::WHITE);
 
-                draw_tank_health(&mut d2, tank);
-                draw_powerup_markers(&mut d2, tank);
+                if view.is_spectating() {
+                    draw_tank_status(&mut d2, tank, pos, self.reload_progress(tank));
+                } else {
+                    draw_tank_health(&mut d2, tank, pos);
+                }
+                draw_powerup_markers(&mut d2, tank, pos);
+                if followed == Some(index) {
+                    d2.draw_circle_lines(
+                        pos.x as i32,
+                        pos.y as i32,
+                        TANK_RADIUS + 12.0,
+                        Color::new(255, 230, 120, 200),
+                    );
+                }
+            }
+
+            for flag in &self.flags {
+                let Some(carrier) = flag.carrier().and_then(|index| self.tanks.get(index)) else {
+                    continue;
+                };
+                let pos = carrier.render_pos(blend);
+                let base = vec2(pos.x + TANK_RADIUS * 0.5, pos.y - TANK_RADIUS * 0.2);
+                draw_flag(&mut d2, base, FLAG_HEIGHT, flag.team.color());
             }
 
             for bullet in &self.bullets {
                 let palette = bullet_palette(assets, bullet.team);
                 let rotation = sprite_rotation(vec2_angle(bullet.vel));
-                draw_texture_centered(&mut d2, &palette.normal, bullet.pos, rotation, Color::WHITE);
+                draw_texture_centered(
+                    &mut d2,
+                    &palette.normal,
+                    bullet.render_pos(blend),
+                    rotation,
+                    Color::WHITE,
+                );
             }
 
             for explosion in &self.explosions {
@@ -126,22 +175,27 @@ impl Game {
             }
 
             if self.intro_timer > 0.0 {
-                if let Some(player) = self.tanks.get(self.player_index).filter(|tank| tank.alive) {
+                let player = slot.and_then(|slot| self.player_tank(slot));
+                if let Some(player) = player.filter(|tank| tank.alive) {
+                    let player_pos = player.render_pos(blend);
                     let pulse = (self.intro_timer * 6.0).sin().abs();
                     let radius = TANK_RADIUS + 10.0 + pulse * 6.0;
                     d2.draw_circle_lines(
-                        player.pos.x as i32,
-                        player.pos.y as i32,
+                        player_pos.x as i32,
+                        player_pos.y as i32,
                         radius,
                         Color::new(255, 230, 120, 220),
                     );
-                    let label = "YOU";
+                    let label = match slot {
+                        Some(slot) if self.seated_players() > 1 => format!("P{}", slot + 1),
+                        _ => "YOU".to_string(),
+                    };
                     let size = 18;
-                    let width = measure_text_width(label, size);
+                    let width = measure_text_width(&label, size);
                     d2.draw_text(
-                        label,
-                        (player.pos.x - width as f32 * 0.5) as i32,
-                        (player.pos.y - radius - 24.0) as i32,
+                        &label,
+                        (player_pos.x - width as f32 * 0.5) as i32,
+                        (player_pos.y - radius - 24.0) as i32,
                         size,
                         Color::new(255, 230, 120, 235),
                     );
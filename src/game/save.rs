@@ -0,0 +1,115 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::Game;
+
+pub const SAVE_FORMAT_VERSION: u32 = 2;
+pub const SAVE_EXTENSION: &str = "tanksave";
+
+/// On-disk wrapper around a serialized [`Game`]. The header is read on its
+/// own first so an old file reports a version mismatch instead of a parse
+/// error somewhere inside the state.
+#[derive(Serialize, Deserialize)]
+struct SaveFile<T> {
+    format_version: u32,
+    game_version: String,
+    game: T,
+}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    format_version: u32,
+}
+
+impl Game {
+    pub fn to_json(&self) -> io::Result<String> {
+        let file = SaveFile {
+            format_version: SAVE_FORMAT_VERSION,
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            game: self,
+        };
+        Ok(serde_json::to_string(&file)?)
+    }
+
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let header: SaveHeader = serde_json::from_str(json)?;
+        if header.format_version != SAVE_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported save format {} (expected {})",
+                    header.format_version, SAVE_FORMAT_VERSION
+                ),
+            ));
+        }
+        let file: SaveFile<Game> = serde_json::from_str(json)?;
+        Ok(file.game)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_json()?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::TICK_DT;
+    use crate::game::TickInput;
+
+    use super::*;
+
+    fn advance(game: &mut Game, ticks: usize) {
+        for _ in 0..ticks {
+            game.update(TICK_DT, &TickInput::default());
+        }
+    }
+
+    #[test]
+    fn a_loaded_game_plays_on_exactly_like_the_original() {
+        let mut game = Game::new(21);
+        let start = TickInput {
+            start_pressed: true,
+            ..TickInput::default()
+        };
+        game.update(TICK_DT, &start);
+        advance(&mut game, 900);
+
+        let mut loaded = Game::from_json(&game.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.checksum(), game.checksum());
+        for _ in 0..10 {
+            advance(&mut game, 100);
+            advance(&mut loaded, 100);
+            assert_eq!(loaded.checksum(), game.checksum());
+        }
+    }
+
+    #[test]
+    fn other_save_formats_are_refused() {
+        let json = Game::new(21).to_json().unwrap();
+        let current = format!("\"format_version\":{SAVE_FORMAT_VERSION}");
+        let newer = format!("\"format_version\":{}", SAVE_FORMAT_VERSION + 1);
+        assert!(json.contains(&current));
+
+        let err = Game::from_json(&json.replacen(&current, &newer, 1))
+            .err()
+            .expect("a newer format is refused");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("unsupported save format"), "{err}");
+
+        // The version is checked before the state is parsed at all.
+        let err = Game::from_json(r#"{"format_version":1,"game":{}}"#)
+            .err()
+            .expect("an older format is refused");
+        assert!(
+            err.to_string().contains("unsupported save format 1"),
+            "{err}"
+        );
+    }
+}
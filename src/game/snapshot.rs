@@ -0,0 +1,365 @@
+use std::hash::{DefaultHasher, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameMode;
+use crate::config::{TICK_DT, TRACK_LIFE};
+use crate::entities::{AiState, Bullet, Explosion, Flag, Powerup, Tank, Team, TrackMark};
+use crate::world::World;
+
+use super::{Game, ScreenState};
+
+/// Track marks sent without a baseline, newest first, so a full snapshot
+/// stays well inside one datagram.
+const FULL_SNAPSHOT_TRACKS: usize = 64;
+
+/// The visible state of a round at one server tick: everything a client
+/// draws apart from the map and the track marks.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    tick: u64,
+    header: Header,
+    tanks: Vec<Tank>,
+    bullets: Vec<Bullet>,
+    powerups: Vec<Powerup>,
+    explosions: Vec<Explosion>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Header {
+    state: ScreenState,
+    round: u32,
+    round_timer: f32,
+    countdown_timer: f32,
+    intro_timer: f32,
+    team_kills: Vec<u32>,
+    last_winner: Option<Team>,
+    mode: GameMode,
+    flags: Vec<Flag>,
+    captures: Vec<u32>,
+}
+
+/// A [`Snapshot`] encoded against an older one the receiver already holds:
+/// only the tanks that changed, and each entity list only when it differs.
+/// Without a baseline everything is sent.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotDelta {
+    pub tick: u64,
+    /// Tick of the snapshot this was encoded against.
+    pub baseline: Option<u64>,
+    header: Header,
+    tank_count: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tanks: Vec<(usize, Tank)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bullets: Option<Vec<Bullet>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    powerups: Option<Vec<Powerup>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    explosions: Option<Vec<Explosion>>,
+    /// Track marks laid since the baseline.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tracks: Vec<TrackMark>,
+}
+
+impl Snapshot {
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn round(&self) -> u32 {
+        self.header.round
+    }
+
+    pub fn tanks(&self) -> &[Tank] {
+        &self.tanks
+    }
+
+    pub fn bullets(&self) -> &[Bullet] {
+        &self.bullets
+    }
+
+    /// Encodes this snapshot against `baseline`, or in full without one.
+    /// `tracks` are the sender's current track marks.
+    pub fn delta(&self, baseline: Option<&Snapshot>, tracks: &[TrackMark]) -> SnapshotDelta {
+        let tanks = self
+            .tanks
+            .iter()
+            .enumerate()
+            .filter(|(index, tank)| baseline.and_then(|base| base.tanks.get(*index)) != Some(tank))
+            .map(|(index, tank)| (index, tank.clone()))
+            .collect();
+        let tracks = match baseline {
+            Some(base) => {
+                // A mark laid on tick `t` has aged one tick by the end of it.
+                let since = (self.tick.saturating_sub(base.tick) as f32 + 0.5) * TICK_DT;
+                tracks
+                    .iter()
+                    .filter(|track| track.age < since)
+                    .cloned()
+                    .collect()
+            }
+            None => tracks[tracks.len().saturating_sub(FULL_SNAPSHOT_TRACKS)..].to_vec(),
+        };
+        SnapshotDelta {
+            tick: self.tick,
+            baseline: baseline.map(|base| base.tick),
+            header: self.header.clone(),
+            tank_count: self.tanks.len(),
+            tanks,
+            bullets: changed(&self.bullets, baseline.map(|base| &base.bullets[..])),
+            powerups: changed(&self.powerups, baseline.map(|base| &base.powerups[..])),
+            explosions: changed(&self.explosions, baseline.map(|base| &base.explosions[..])),
+            tracks,
+        }
+    }
+}
+
+impl SnapshotDelta {
+    /// Rebuilds the full snapshot on top of `baseline`. Returns `None` if
+    /// this delta was encoded against a different one.
+    pub fn apply(&self, baseline: Option<&Snapshot>) -> Option<Snapshot> {
+        let base = match self.baseline {
+            Some(tick) => Some(baseline.filter(|base| base.tick == tick)?),
+            None => None,
+        };
+
+        let mut tanks = base.map(|base| base.tanks.clone()).unwrap_or_default();
+        tanks.truncate(self.tank_count);
+        for (index, tank) in &self.tanks {
+            if *index < tanks.len() {
+                tanks[*index] = tank.clone();
+            } else if *index == tanks.len() {
+                tanks.push(tank.clone());
+            } else {
+                return None;
+            }
+        }
+        if tanks.len() != self.tank_count {
+            return None;
+        }
+
+        Some(Snapshot {
+            tick: self.tick,
+            header: self.header.clone(),
+            tanks,
+            bullets: unchanged_or(&self.bullets, base.map(|base| &base.bullets))?,
+            powerups: unchanged_or(&self.powerups, base.map(|base| &base.powerups))?,
+            explosions: unchanged_or(&self.explosions, base.map(|base| &base.explosions))?,
+        })
+    }
+
+    pub fn round(&self) -> u32 {
+        self.header.round
+    }
+
+    pub fn tracks(&self) -> &[TrackMark] {
+        &self.tracks
+    }
+}
+
+fn changed<T: Clone + PartialEq>(current: &[T], previous: Option<&[T]>) -> Option<Vec<T>> {
+    (previous != Some(current)).then(|| current.to_vec())
+}
+
+fn unchanged_or<T: Clone>(sent: &Option<Vec<T>>, previous: Option<&Vec<T>>) -> Option<Vec<T>> {
+    sent.as_ref().or(previous).cloned()
+}
+
+impl Game {
+    /// Captures what a client needs to draw the round at server tick
+    /// `tick`. AI planning state stays behind.
+    pub fn snapshot(&self, tick: u64) -> Snapshot {
+        Snapshot {
+            tick,
+            header: Header {
+                state: self.state,
+                round: self.round,
+                round_timer: self.round_timer,
+                countdown_timer: self.countdown_timer,
+                intro_timer: self.intro_timer,
+                team_kills: self.team_kills.clone(),
+                last_winner: self.last_winner,
+                mode: self.mode,
+                flags: self.flags.clone(),
+                captures: self.captures.clone(),
+            },
+            tanks: self
+                .tanks
+                .iter()
+                .map(|tank| Tank {
+                    path: Vec::new(),
+                    path_goal: None,
+                    ai: AiState::default(),
+                    ..tank.clone()
+                })
+                .collect(),
+            bullets: self.bullets.clone(),
+            powerups: self.powerups.clone(),
+            explosions: self.explosions.clone(),
+        }
+    }
+
+    /// A hash of the simulated state, for peers to confirm they still agree.
+    /// Who is watching, and from where, does not count.
+    pub fn checksum(&self) -> u64 {
+        let state = (
+            self.snapshot(0),
+            &self.rng,
+            self.powerup_spawn_timer,
+            &self.tracks,
+        );
+        let bytes = serde_json::to_vec(&state).expect("game state is always serializable");
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&bytes);
+        hasher.finish()
+    }
+
+    /// Overwrites the round with a snapshot from a server. The map is not
+    /// part of it; see [`Game::set_world`].
+    pub fn apply_snapshot(&mut self, snapshot: &Snapshot) {
+        let header = &snapshot.header;
+        self.state = header.state;
+        self.round = header.round;
+        self.round_timer = header.round_timer;
+        self.countdown_timer = header.countdown_timer;
+        self.intro_timer = header.intro_timer;
+        self.team_kills.clone_from(&header.team_kills);
+        self.last_winner = header.last_winner;
+        self.mode = header.mode;
+        self.flags.clone_from(&header.flags);
+        self.captures.clone_from(&header.captures);
+        self.tanks.clone_from(&snapshot.tanks);
+        self.bullets.clone_from(&snapshot.bullets);
+        self.powerups.clone_from(&snapshot.powerups);
+        self.explosions.clone_from(&snapshot.explosions);
+    }
+
+    /// Replaces the map, e.g. with the one a server sent for a new round.
+    pub fn set_world(&mut self, world: World) {
+        self.world = world;
+        self.tracks.clear();
+    }
+
+    /// Ages the track marks by `elapsed` seconds and adds the `new` ones
+    /// that are not already down.
+    pub fn merge_tracks(&mut self, new: &[TrackMark], elapsed: f32) {
+        self.tracks.retain_mut(|track| {
+            track.age += elapsed;
+            track.age < TRACK_LIFE
+        });
+        for track in new {
+            let laid = self
+                .tracks
+                .iter()
+                .any(|old| old.pos == track.pos && old.rotation == track.rotation);
+            if !laid {
+                self.tracks.push(track.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::TickInput;
+
+    /// Past the intro and countdown.
+    const PLAY: usize = 8 * 60;
+
+    /// A game `ticks` into a round, with the AI driving every tank.
+    fn playing(seed: u64, ticks: usize) -> Game {
+        let mut game = Game::new(seed);
+        let start = TickInput {
+            start_pressed: true,
+            ..TickInput::default()
+        };
+        game.update(TICK_DT, &start);
+        advance(&mut game, ticks);
+        game
+    }
+
+    fn advance(game: &mut Game, ticks: usize) {
+        for _ in 0..ticks {
+            game.update(TICK_DT, &TickInput::default());
+        }
+    }
+
+    #[test]
+    fn full_snapshots_round_trip() {
+        let game = playing(5, PLAY);
+        let snapshot = game.snapshot(120);
+        let delta = snapshot.delta(None, game.tracks());
+        assert_eq!(delta.baseline, None);
+        assert_eq!(delta.tanks.len(), snapshot.tanks.len());
+
+        let json = serde_json::to_string(&delta).unwrap();
+        let received: SnapshotDelta = serde_json::from_str(&json).unwrap();
+        assert_eq!(received.apply(None), Some(snapshot));
+    }
+
+    #[test]
+    fn deltas_round_trip_against_their_baseline() {
+        let mut game = playing(5, PLAY);
+        let baseline = game.snapshot(120);
+        advance(&mut game, 30);
+        let snapshot = game.snapshot(150);
+
+        let delta = snapshot.delta(Some(&baseline), game.tracks());
+        assert_eq!(delta.baseline, Some(120));
+        assert!(!delta.tanks.is_empty(), "nobody moved in half a second");
+        let json = serde_json::to_string(&delta).unwrap();
+        let received: SnapshotDelta = serde_json::from_str(&json).unwrap();
+        assert_eq!(received.apply(Some(&baseline)), Some(snapshot));
+    }
+
+    #[test]
+    fn an_unchanged_snapshot_sends_no_entities() {
+        let game = playing(5, PLAY);
+        let snapshot = game.snapshot(120);
+        let again = Snapshot {
+            tick: 121,
+            ..snapshot.clone()
+        };
+
+        let delta = again.delta(Some(&snapshot), &[]);
+        assert!(delta.tanks.is_empty());
+        assert!(delta.bullets.is_none());
+        assert!(delta.powerups.is_none());
+        assert!(delta.explosions.is_none());
+        assert_eq!(delta.apply(Some(&snapshot)), Some(again));
+    }
+
+    #[test]
+    fn a_mismatched_baseline_is_refused() {
+        let mut game = playing(5, PLAY);
+        let baseline = game.snapshot(120);
+        advance(&mut game, 10);
+        let other = game.snapshot(130);
+        advance(&mut game, 10);
+        let delta = game.snapshot(140).delta(Some(&baseline), game.tracks());
+
+        assert_eq!(delta.apply(Some(&other)), None);
+        assert_eq!(delta.apply(None), None);
+    }
+
+    #[test]
+    fn tank_count_changes_survive_a_delta() {
+        let game = playing(5, PLAY);
+        let full = game.snapshot(60);
+        let mut fewer = full.clone();
+        fewer.tick = 61;
+        fewer.tanks.pop();
+
+        let shrunk = fewer.delta(Some(&full), &[]).apply(Some(&full));
+        assert_eq!(shrunk.as_ref(), Some(&fewer));
+
+        let mut more = full.clone();
+        more.tick = 62;
+        let shrunk = shrunk.unwrap();
+        let grown = more.delta(Some(&shrunk), &[]);
+        assert_eq!(grown.tanks.len(), 1);
+        assert_eq!(grown.apply(Some(&shrunk)), Some(more));
+    }
+}
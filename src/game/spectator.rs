@@ -0,0 +1,89 @@
+#[cfg(feature = "graphics")]
+use raylib::prelude::{KeyboardKey, RaylibHandle};
+
+use super::{CameraView, Game};
+
+/// Camera controls for watching a match without a tank: follow any tank,
+/// step through them with hotkeys, or look down on the whole map.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spectator {
+    following: usize,
+    overhead: bool,
+}
+
+impl Spectator {
+    /// Starts on the overhead view.
+    pub fn new() -> Self {
+        Self {
+            following: 0,
+            overhead: true,
+        }
+    }
+
+    /// Left/Right or Q/E step to the previous or next tank; Tab switches
+    /// between following and the overhead view.
+    #[cfg(feature = "graphics")]
+    pub fn handle_keys(&mut self, rl: &RaylibHandle, game: &Game) {
+        if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+            self.overhead = !self.overhead;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) || rl.is_key_pressed(KeyboardKey::KEY_E) {
+            self.cycle(game, true);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) || rl.is_key_pressed(KeyboardKey::KEY_Q) {
+            self.cycle(game, false);
+        }
+    }
+
+    /// Follows the next tank, or the previous one, skipping destroyed tanks
+    /// while any are alive. From the overhead view it first returns to the
+    /// tank followed last.
+    pub fn cycle(&mut self, game: &Game, forward: bool) {
+        if self.overhead {
+            self.overhead = false;
+            return;
+        }
+        let tanks = game.tanks();
+        let count = tanks.len();
+        if count == 0 {
+            return;
+        }
+        let start = self.following.min(count - 1);
+        let any_alive = tanks.iter().any(|tank| tank.alive);
+        for step in 1..=count {
+            let index = if forward {
+                (start + step) % count
+            } else {
+                (start + count - step) % count
+            };
+            if tanks[index].alive || !any_alive {
+                self.following = index;
+                return;
+            }
+        }
+    }
+
+    /// Follows tank `index`.
+    pub fn follow(&mut self, index: usize) {
+        self.following = index;
+        self.overhead = false;
+    }
+
+    /// The camera to draw `game` with.
+    pub fn view(&self, game: &Game) -> CameraView {
+        let count = game.tanks().len();
+        if self.overhead || count == 0 {
+            CameraView::Overhead
+        } else {
+            CameraView::Follow {
+                tank: self.following.min(count - 1),
+            }
+        }
+    }
+}
+
+impl Default for Spectator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
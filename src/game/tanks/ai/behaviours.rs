@@ -0,0 +1,839 @@
+use crate::config::TILE_SIZE;
+use crate::entities::{BehaviourKind, FlagState, PowerupKind, Tank, Team};
+use crate::math::{
+    GameRng, Vector2, vec2, vec2_add, vec2_distance, vec2_normalize, vec2_scale, vec2_sub,
+};
+use crate::world::World;
+
+use super::super::pathing::path_direction;
+use super::super::spawn::pick_waypoint;
+use super::super::squad::{FlagRole, Lane};
+use super::perception::Situation;
+use super::powerups::best_pickup;
+
+/// Obstacle push applied on top of the desired heading. A* paths already
+/// keep clear of obstacles, so path following only needs a light touch.
+const DIRECT_AVOIDANCE: f32 = 1.4;
+const PATH_AVOIDANCE: f32 = 0.4;
+
+/// Bonus the behaviour chosen last tick gets, so bots don't flip-flop
+/// between two options that score about the same.
+const STICKINESS: f32 = 0.1;
+/// Health fraction below which a Heal pickup is worth retreating to.
+const HEAL_HEALTH: f32 = 0.4;
+/// Targets further than this many engagement distances away are approached
+/// along the bot's own lane rather than head-on.
+const LANE_APPROACH: f32 = 2.0;
+/// Bots regrouping stop once this close to the rally point, and set off
+/// again once further than `REGROUP_LEAVE`.
+const REGROUP_ARRIVE: f32 = TILE_SIZE;
+const REGROUP_LEAVE: f32 = TILE_SIZE * 3.0;
+/// Any bot goes for an enemy flag, or to the rescue of its own, within
+/// this distance, whatever its role.
+const FLAG_NEARBY: f32 = TILE_SIZE * 5.0;
+/// Escorts drive this far ahead of the carrier, towards home.
+const ESCORT_LEAD: f32 = TILE_SIZE;
+/// Defenders hold this far out from their flag, towards the middle of the
+/// map, and set off once they have drifted `GUARD_LEAVE` from that spot.
+const GUARD_DISTANCE: f32 = TILE_SIZE * 1.5;
+const GUARD_LEAVE: f32 = TILE_SIZE;
+
+/// Where a behaviour wants the tank to drive this tick.
+pub(super) struct Steering {
+    pub direction: Vector2,
+    /// How strongly nearby obstacles push the heading away.
+    pub avoidance: f32,
+}
+
+impl Steering {
+    fn path(direction: Vector2) -> Self {
+        Self {
+            direction,
+            avoidance: PATH_AVOIDANCE,
+        }
+    }
+
+    fn direct(direction: Vector2) -> Self {
+        Self {
+            direction,
+            avoidance: DIRECT_AVOIDANCE,
+        }
+    }
+}
+
+/// One thing a bot can decide to do. Every tick each behaviour scores the
+/// situation, roughly in 0..1, and the highest scorer steers the tank.
+/// Aiming and firing happen regardless of the behaviour chosen.
+pub(super) trait Behaviour {
+    fn kind(&self) -> BehaviourKind;
+    fn score(&self, situation: &Situation) -> f32;
+    fn steer(&self, tank: &mut Tank, situation: &Situation, rng: &mut GameRng) -> Steering;
+}
+
+/// Every behaviour a bot chooses between. New behaviours only need an entry
+/// here and a `BehaviourKind`.
+const BEHAVIOURS: [&dyn Behaviour; 10] = [
+    &Patrol,
+    &Engage,
+    &Flank,
+    &RetreatToHeal,
+    &GrabPowerup,
+    &GuardSpawn,
+    &Regroup,
+    &AttackFlag,
+    &EscortCarrier,
+    &DefendFlag,
+];
+
+/// The best-scoring behaviour for `situation`.
+pub(super) fn choose(situation: &Situation) -> &'static dyn Behaviour {
+    let mut best = BEHAVIOURS[0];
+    let mut best_score = f32::NEG_INFINITY;
+    for behaviour in BEHAVIOURS {
+        let mut score = behaviour.score(situation);
+        if behaviour.kind() == situation.current {
+            score += STICKINESS;
+        }
+        if score > best_score {
+            best = behaviour;
+            best_score = score;
+        }
+    }
+    best
+}
+
+/// Wander between random waypoints in the bot's lane, outside the enemy
+/// spawn zone.
+struct Patrol;
+
+impl Behaviour for Patrol {
+    fn kind(&self) -> BehaviourKind {
+        BehaviourKind::Patrol
+    }
+
+    fn score(&self, _situation: &Situation) -> f32 {
+        0.1
+    }
+
+    fn steer(&self, tank: &mut Tank, situation: &Situation, rng: &mut GameRng) -> Steering {
+        let lane = situation.orders.lane;
+        let off_lane = lane.is_some_and(|lane| !lane.contains(tank.waypoint.y));
+        if off_lane || vec2_distance(tank.waypoint, tank.pos) < 80.0 {
+            tank.waypoint = lane_waypoint(situation.world, tank.team, lane, rng);
+        }
+        Steering::path(path_direction(tank, situation.world, tank.waypoint))
+    }
+}
+
+/// Close to fighting distance and circle the target. Distant targets are
+/// approached along the bot's lane so the team comes in spread out.
+struct Engage;
+
+impl Behaviour for Engage {
+    fn kind(&self) -> BehaviourKind {
+        BehaviourKind::Engage
+    }
+
+    fn score(&self, situation: &Situation) -> f32 {
+        match situation.target {
+            Some(target) if target.visible && situation.invincible => 0.8,
+            Some(target) if target.visible && situation.rapid => 0.7,
+            Some(target) if target.visible => 0.6,
+            Some(_) => 0.45,
+            None => 0.0,
+        }
+    }
+
+    fn steer(&self, tank: &mut Tank, situation: &Situation, _rng: &mut GameRng) -> Steering {
+        let Some(target) = situation.target else {
+            return Steering::direct(vec2(0.0, 0.0));
+        };
+        let ai = &situation.config.ai;
+        let range_scale = situation.profile.range_scale();
+        let lane = situation.orders.lane;
+        if let Some(lane) =
+            lane.filter(|_| target.dist > ai.target_far * range_scale * LANE_APPROACH)
+        {
+            let goal = vec2(target.pos.x, lane.center());
+            Steering::path(path_direction(tank, situation.world, goal))
+        } else if target.dist > ai.target_far * range_scale || !target.visible {
+            // Close in, or work around cover until there is a clear shot.
+            Steering::path(path_direction(tank, situation.world, target.pos))
+        } else if target.dist < ai.target_near * range_scale {
+            Steering::direct(vec2_normalize(vec2_sub(tank.pos, target.pos)))
+        } else {
+            let to_target = vec2_sub(target.pos, tank.pos);
+            Steering::direct(vec2_normalize(vec2(-to_target.y, to_target.x)))
+        }
+    }
+}
+
+/// Swing wide around the target to catch it side-on, either because it is
+/// hiding behind cover or because the team assigned this bot to flank.
+struct Flank;
+
+impl Behaviour for Flank {
+    fn kind(&self) -> BehaviourKind {
+        BehaviourKind::Flank
+    }
+
+    fn score(&self, situation: &Situation) -> f32 {
+        match situation.target {
+            Some(_) if situation.orders.flank => 0.7,
+            Some(target) if !target.visible => 0.3 + 0.35 * situation.profile.aggression,
+            _ => 0.0,
+        }
+    }
+
+    fn steer(&self, tank: &mut Tank, situation: &Situation, _rng: &mut GameRng) -> Steering {
+        let Some(target) = situation.target else {
+            return Steering::direct(vec2(0.0, 0.0));
+        };
+        let to_target = vec2_normalize(vec2_sub(target.pos, tank.pos));
+        let side = vec2(-to_target.y, to_target.x);
+        let reach = situation.config.ai.target_far * situation.profile.range_scale();
+        // Prefer the side with a clear shot, then the nearer one.
+        let flank_point = [1.0, -1.0]
+            .map(|sign| vec2_add(target.pos, vec2_scale(side, reach * sign)))
+            .into_iter()
+            .min_by(|a, b| {
+                let clear_a = situation.world.line_of_sight(*a, target.pos, 0.0);
+                let clear_b = situation.world.line_of_sight(*b, target.pos, 0.0);
+                clear_b
+                    .cmp(&clear_a)
+                    .then(vec2_distance(*a, tank.pos).total_cmp(&vec2_distance(*b, tank.pos)))
+            })
+            .unwrap_or(target.pos);
+        Steering::path(path_direction(tank, situation.world, flank_point))
+    }
+}
+
+/// Fall back while badly hurt, towards a Heal pickup if there is one.
+struct RetreatToHeal;
+
+impl Behaviour for RetreatToHeal {
+    fn kind(&self) -> BehaviourKind {
+        BehaviourKind::RetreatToHeal
+    }
+
+    fn score(&self, situation: &Situation) -> f32 {
+        let mut threshold = situation.profile.retreat_health();
+        if situation.nearest_powerup(PowerupKind::Heal).is_some() {
+            threshold = threshold.max(HEAL_HEALTH);
+        }
+        if situation.health < threshold && !situation.invincible {
+            0.9
+        } else {
+            0.0
+        }
+    }
+
+    fn steer(&self, tank: &mut Tank, situation: &Situation, _rng: &mut GameRng) -> Steering {
+        if let Some(heal) = situation.nearest_powerup(PowerupKind::Heal) {
+            return Steering::path(path_direction(tank, situation.world, heal.pos));
+        }
+        match situation.target {
+            Some(target) if target.visible => {
+                Steering::direct(vec2_normalize(vec2_sub(tank.pos, target.pos)))
+            }
+            _ => {
+                let home = situation.world.spawn_zone(tank.team).center();
+                Steering::path(path_direction(tank, situation.world, home))
+            }
+        }
+    }
+}
+
+/// Go for the pickup the bot values most, or snatch one an enemy is about
+/// to take.
+struct GrabPowerup;
+
+impl Behaviour for GrabPowerup {
+    fn kind(&self) -> BehaviourKind {
+        BehaviourKind::GrabPowerup
+    }
+
+    fn score(&self, situation: &Situation) -> f32 {
+        let Some((_, worth)) = best_pickup(situation) else {
+            return 0.0;
+        };
+        let idle = situation.target.is_none_or(|target| !target.visible);
+        worth + if idle { 0.2 } else { 0.0 }
+    }
+
+    fn steer(&self, tank: &mut Tank, situation: &Situation, _rng: &mut GameRng) -> Steering {
+        match best_pickup(situation) {
+            Some((powerup, _)) => {
+                Steering::path(path_direction(tank, situation.world, powerup.pos))
+            }
+            None => Steering::direct(vec2(0.0, 0.0)),
+        }
+    }
+}
+
+/// Head back to cut off enemies pushing up to the team's spawn zone.
+struct GuardSpawn;
+
+impl Behaviour for GuardSpawn {
+    fn kind(&self) -> BehaviourKind {
+        BehaviourKind::GuardSpawn
+    }
+
+    fn score(&self, situation: &Situation) -> f32 {
+        if situation.spawn_threat.is_some() {
+            0.5
+        } else {
+            0.0
+        }
+    }
+
+    fn steer(&self, tank: &mut Tank, situation: &Situation, _rng: &mut GameRng) -> Steering {
+        let home = situation.world.spawn_zone(tank.team).center();
+        let Some(threat) = situation.spawn_threat else {
+            return Steering::path(path_direction(tank, situation.world, home));
+        };
+        // Hold the ground between the threat and the zone.
+        let guard_point = vec2_add(home, vec2_scale(vec2_sub(threat, home), 0.5));
+        Steering::path(path_direction(tank, situation.world, guard_point))
+    }
+}
+
+/// Fall back to the rally point in front of the spawn zone while the team
+/// is outnumbered, so respawning teammates join up before pushing again.
+struct Regroup;
+
+impl Behaviour for Regroup {
+    fn kind(&self) -> BehaviourKind {
+        BehaviourKind::Regroup
+    }
+
+    fn score(&self, situation: &Situation) -> f32 {
+        let radius = if situation.current == BehaviourKind::Regroup {
+            REGROUP_ARRIVE
+        } else {
+            REGROUP_LEAVE
+        };
+        let away = vec2_distance(situation.pos, situation.plan.rally) > radius;
+        if situation.plan.outnumbered && away && !situation.invincible {
+            0.75
+        } else {
+            0.0
+        }
+    }
+
+    fn steer(&self, tank: &mut Tank, situation: &Situation, _rng: &mut GameRng) -> Steering {
+        Steering::path(path_direction(tank, situation.world, situation.plan.rally))
+    }
+}
+
+/// Take the nearest enemy flag nobody is carrying, and once holding one,
+/// bring it back to the spawn zone to score.
+struct AttackFlag;
+
+impl Behaviour for AttackFlag {
+    fn kind(&self) -> BehaviourKind {
+        BehaviourKind::AttackFlag
+    }
+
+    fn score(&self, situation: &Situation) -> f32 {
+        if situation.carrying {
+            return 0.95;
+        }
+        let Some(flag) = situation.enemy_flag else {
+            return 0.0;
+        };
+        if flag.dist < FLAG_NEARBY {
+            // Close enough to grab it before turning back to heal.
+            0.92
+        } else if situation.orders.role == FlagRole::Attack {
+            0.65
+        } else {
+            0.25
+        }
+    }
+
+    fn steer(&self, tank: &mut Tank, situation: &Situation, _rng: &mut GameRng) -> Steering {
+        let goal = match situation.enemy_flag {
+            Some(flag) if !situation.carrying => flag.pos,
+            _ => situation.world.spawn_zone(tank.team).center(),
+        };
+        Steering::path(path_direction(tank, situation.world, goal))
+    }
+}
+
+/// Drive just ahead of a teammate bringing a flag home, to draw fire and
+/// clear the way.
+struct EscortCarrier;
+
+impl Behaviour for EscortCarrier {
+    fn kind(&self) -> BehaviourKind {
+        BehaviourKind::EscortCarrier
+    }
+
+    fn score(&self, situation: &Situation) -> f32 {
+        match situation.flag_carrier {
+            Some(_) if situation.orders.role == FlagRole::Escort => 0.7,
+            _ => 0.0,
+        }
+    }
+
+    fn steer(&self, tank: &mut Tank, situation: &Situation, _rng: &mut GameRng) -> Steering {
+        let Some(carrier) = situation.flag_carrier else {
+            return Steering::direct(vec2(0.0, 0.0));
+        };
+        let home = situation.world.spawn_zone(tank.team).center();
+        let ahead = vec2_scale(vec2_normalize(vec2_sub(home, carrier)), ESCORT_LEAD);
+        let goal = vec2_add(carrier, ahead);
+        Steering::path(path_direction(tank, situation.world, goal))
+    }
+}
+
+/// Chase down whoever took the team's flag, return it once dropped, and
+/// otherwise keep watch in front of it.
+struct DefendFlag;
+
+impl Behaviour for DefendFlag {
+    fn kind(&self) -> BehaviourKind {
+        BehaviourKind::DefendFlag
+    }
+
+    fn score(&self, situation: &Situation) -> f32 {
+        let Some(flag) = situation.own_flag else {
+            return 0.0;
+        };
+        let defender = situation.orders.role == FlagRole::Defend;
+        let nearby = flag.dist < FLAG_NEARBY;
+        match flag.state {
+            FlagState::Carried(_) if defender => 0.85,
+            FlagState::Carried(_) if nearby => 0.75,
+            FlagState::Dropped { .. } if defender || nearby => 0.8,
+            FlagState::Carried(_) | FlagState::Dropped { .. } => 0.4,
+            FlagState::Home if defender => 0.62,
+            FlagState::Home => 0.0,
+        }
+    }
+
+    fn steer(&self, tank: &mut Tank, situation: &Situation, _rng: &mut GameRng) -> Steering {
+        let Some(flag) = situation.own_flag else {
+            return Steering::direct(vec2(0.0, 0.0));
+        };
+        if flag.state != FlagState::Home {
+            return Steering::path(path_direction(tank, situation.world, flag.pos));
+        }
+        let out = vec2_normalize(vec2_sub(situation.world.center(), flag.pos));
+        let guard_point = vec2_add(flag.pos, vec2_scale(out, GUARD_DISTANCE));
+        let holding = situation.current == BehaviourKind::DefendFlag
+            && vec2_distance(tank.pos, guard_point) < GUARD_LEAVE;
+        if holding {
+            Steering::direct(vec2(0.0, 0.0))
+        } else {
+            Steering::path(path_direction(tank, situation.world, guard_point))
+        }
+    }
+}
+
+/// A patrol waypoint inside `lane`, when the bot has one.
+fn lane_waypoint(world: &World, team: Team, lane: Option<Lane>, rng: &mut GameRng) -> Vector2 {
+    let Some(lane) = lane else {
+        return pick_waypoint(world, team, rng);
+    };
+    for _ in 0..20 {
+        let waypoint = pick_waypoint(world, team, rng);
+        if lane.contains(waypoint.y) {
+            return waypoint;
+        }
+    }
+    let waypoint = pick_waypoint(world, team, rng);
+    vec2(waypoint.x, lane.center())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::config::{GameConfig, SkillProfile};
+    use crate::math::Rectangle;
+    use crate::world::SpawnZone;
+
+    use super::super::super::spawn::spawn_tanks;
+    use super::super::super::squad::{Orders, TeamPlan};
+    use super::super::perception::{FlagSighting, PowerupSighting, Target};
+    use super::*;
+
+    /// Middle of the test map, where the bot under test stands.
+    const CENTER: Vector2 = tile(10.0, 5.0);
+
+    const fn tile(x: f32, y: f32) -> Vector2 {
+        Vector2 {
+            x: x * TILE_SIZE,
+            y: y * TILE_SIZE,
+        }
+    }
+
+    /// An open 20x10 tile map with Crimson's spawn zone on the left and
+    /// Azure's on the right.
+    struct Fixture {
+        world: World,
+        config: GameConfig,
+        profile: SkillProfile,
+        plan: TeamPlan,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let zone = |x, team| SpawnZone {
+                rect: Rectangle {
+                    x: x * TILE_SIZE,
+                    y: 3.0 * TILE_SIZE,
+                    width: 2.0 * TILE_SIZE,
+                    height: 4.0 * TILE_SIZE,
+                },
+                team,
+            };
+            Self {
+                world: World::open(
+                    20,
+                    10,
+                    Vec::new(),
+                    vec![zone(0.0, Team::Red), zone(18.0, Team::Blue)],
+                ),
+                config: GameConfig::default(),
+                profile: SkillProfile::veteran(),
+                plan: TeamPlan {
+                    focus: None,
+                    outnumbered: false,
+                    rally: tile(3.0, 5.0),
+                },
+            }
+        }
+
+        /// A healthy Crimson bot at `CENTER` with nothing in sight.
+        fn situation(&self) -> Situation<'_> {
+            Situation {
+                world: &self.world,
+                config: &self.config,
+                profile: &self.profile,
+                pos: CENTER,
+                health: 1.0,
+                invincible: false,
+                rapid: false,
+                target: None,
+                powerups: Vec::new(),
+                spawn_threat: None,
+                carrying: false,
+                enemy_flag: None,
+                own_flag: None,
+                flag_carrier: None,
+                plan: &self.plan,
+                orders: Orders::default(),
+                current: BehaviourKind::Patrol,
+            }
+        }
+
+        fn tank(&self) -> Tank {
+            let mut rng = GameRng::seed_from_u64(1);
+            let mut tank = spawn_tanks(&mut rng, &self.world, &self.config).remove(0);
+            tank.pos = CENTER;
+            tank.prev_pos = CENTER;
+            tank
+        }
+
+        /// Where `behaviour` steers a fresh bot at `CENTER` in `situation`.
+        fn steer(&self, behaviour: &dyn Behaviour, situation: &Situation) -> Vector2 {
+            let mut rng = GameRng::seed_from_u64(1);
+            behaviour
+                .steer(&mut self.tank(), situation, &mut rng)
+                .direction
+        }
+    }
+
+    fn target(pos: Vector2, visible: bool) -> Target {
+        Target {
+            index: 4,
+            pos,
+            vel: vec2(0.0, 0.0),
+            dist: vec2_distance(CENTER, pos),
+            visible,
+        }
+    }
+
+    fn powerup(kind: PowerupKind, pos: Vector2) -> PowerupSighting {
+        PowerupSighting {
+            kind,
+            pos,
+            dist: vec2_distance(CENTER, pos),
+            enemy_dist: f32::INFINITY,
+        }
+    }
+
+    fn flag(pos: Vector2, state: FlagState) -> FlagSighting {
+        FlagSighting {
+            pos,
+            dist: vec2_distance(CENTER, pos),
+            state,
+        }
+    }
+
+    fn orders(role: FlagRole) -> Orders {
+        Orders {
+            role,
+            ..Orders::default()
+        }
+    }
+
+    /// Asserts `direction` points from `CENTER` at `goal`, give or take the
+    /// staircase of an A* path.
+    fn assert_towards(direction: Vector2, goal: Vector2) {
+        let wanted = vec2_normalize(vec2_sub(goal, CENTER));
+        let dot = direction.x * wanted.x + direction.y * wanted.y;
+        assert!(dot > 0.9, "steering {direction:?}, wanted {wanted:?}");
+    }
+
+    #[test]
+    fn patrol_heads_for_its_waypoint() {
+        let fixture = Fixture::new();
+        let situation = fixture.situation();
+        assert_eq!(Patrol.score(&situation), 0.1);
+
+        let mut tank = fixture.tank();
+        tank.waypoint = tile(16.0, 5.0);
+        let mut rng = GameRng::seed_from_u64(1);
+        let steering = Patrol.steer(&mut tank, &situation, &mut rng);
+        assert_towards(steering.direction, tile(16.0, 5.0));
+    }
+
+    #[test]
+    fn engage_closes_circles_and_backs_off() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation();
+        assert_eq!(Engage.score(&situation), 0.0);
+        situation.target = Some(target(tile(16.0, 5.0), false));
+        assert_eq!(Engage.score(&situation), 0.45);
+        situation.target = Some(target(tile(16.0, 5.0), true));
+        assert_eq!(Engage.score(&situation), 0.6);
+        situation.rapid = true;
+        assert_eq!(Engage.score(&situation), 0.7);
+        situation.invincible = true;
+        assert_eq!(Engage.score(&situation), 0.8);
+
+        // Out of range: drive at it.
+        let far = tile(16.0, 5.0);
+        situation.target = Some(target(far, true));
+        assert_towards(fixture.steer(&Engage, &situation), far);
+        // Inside `target_near`: back away.
+        let close = vec2(CENTER.x + 100.0, CENTER.y);
+        situation.target = Some(target(close, true));
+        assert_towards(fixture.steer(&Engage, &situation), vec2(0.0, CENTER.y));
+        // In between: circle it.
+        let circling = vec2(CENTER.x + 220.0, CENTER.y);
+        situation.target = Some(target(circling, true));
+        let direction = fixture.steer(&Engage, &situation);
+        assert!(direction.x.abs() < 0.01 && direction.y.abs() > 0.99);
+    }
+
+    #[test]
+    fn flank_swings_out_to_the_side() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation();
+        assert_eq!(Flank.score(&situation), 0.0);
+        let pos = tile(14.0, 5.0);
+        situation.target = Some(target(pos, true));
+        assert_eq!(Flank.score(&situation), 0.0);
+        situation.target = Some(target(pos, false));
+        assert_eq!(Flank.score(&situation), 0.3 + 0.35 * 0.5);
+        situation.target = Some(target(pos, true));
+        situation.orders.flank = true;
+        assert_eq!(Flank.score(&situation), 0.7);
+
+        let reach = fixture.config.ai.target_far;
+        let direction = fixture.steer(&Flank, &situation);
+        let sides = [-reach, reach].map(|offset| vec2(pos.x, pos.y + offset));
+        let wanted = sides.map(|side| vec2_normalize(vec2_sub(side, CENTER)));
+        assert!(
+            wanted
+                .iter()
+                .any(|wanted| direction.x * wanted.x + direction.y * wanted.y > 0.9),
+            "steering {direction:?}, wanted one of {wanted:?}"
+        );
+    }
+
+    #[test]
+    fn retreat_to_heal_when_badly_hurt() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation();
+        situation.health = 0.3;
+        // Veterans only fall back when there is a Heal to fall back to.
+        assert_eq!(RetreatToHeal.score(&situation), 0.0);
+        let heal = tile(6.0, 5.0);
+        situation.powerups = vec![powerup(PowerupKind::Heal, heal)];
+        assert_eq!(RetreatToHeal.score(&situation), 0.9);
+        assert_towards(fixture.steer(&RetreatToHeal, &situation), heal);
+        situation.invincible = true;
+        assert_eq!(RetreatToHeal.score(&situation), 0.0);
+
+        // Without one, back away from whoever is shooting.
+        let mut situation = fixture.situation();
+        situation.health = 0.3;
+        situation.target = Some(target(tile(13.0, 5.0), true));
+        assert_towards(fixture.steer(&RetreatToHeal, &situation), tile(0.0, 5.0));
+    }
+
+    #[test]
+    fn grab_powerup_weighs_value_against_distance() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation();
+        assert_eq!(GrabPowerup.score(&situation), 0.0);
+        // A full-health bot has no use for a Heal.
+        let pos = tile(10.0, 3.0);
+        situation.powerups = vec![powerup(PowerupKind::Heal, pos)];
+        assert_eq!(GrabPowerup.score(&situation), 0.0);
+
+        // Half a Heal's worth, halfway to the edge of its reach, plus the
+        // bonus for having nothing better to do.
+        situation.health = 0.5;
+        let score = GrabPowerup.score(&situation);
+        assert!((score - 0.45).abs() < 1e-4, "scored {score}");
+        assert_towards(fixture.steer(&GrabPowerup, &situation), pos);
+        situation.target = Some(target(tile(16.0, 5.0), true));
+        assert!(GrabPowerup.score(&situation) < score);
+    }
+
+    #[test]
+    fn guard_spawn_cuts_off_a_threat() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation();
+        assert_eq!(GuardSpawn.score(&situation), 0.0);
+        situation.spawn_threat = Some(tile(3.0, 5.0));
+        assert_eq!(GuardSpawn.score(&situation), 0.5);
+        // Halfway between the zone's centre and the threat.
+        assert_towards(fixture.steer(&GuardSpawn, &situation), tile(2.0, 5.0));
+    }
+
+    #[test]
+    fn regroup_only_when_outnumbered_and_away() {
+        let mut fixture = Fixture::new();
+        assert_eq!(Regroup.score(&fixture.situation()), 0.0);
+        fixture.plan.outnumbered = true;
+        let mut situation = fixture.situation();
+        assert_eq!(Regroup.score(&situation), 0.75);
+        assert_towards(fixture.steer(&Regroup, &situation), fixture.plan.rally);
+        situation.pos = vec2_add(fixture.plan.rally, vec2(TILE_SIZE * 2.0, 0.0));
+        assert_eq!(Regroup.score(&situation), 0.0);
+        // Once regrouping, keep going until right at the rally point.
+        situation.current = BehaviourKind::Regroup;
+        assert_eq!(Regroup.score(&situation), 0.75);
+        situation.invincible = true;
+        assert_eq!(Regroup.score(&situation), 0.0);
+    }
+
+    #[test]
+    fn attack_flag_takes_it_and_brings_it_home() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation();
+        assert_eq!(AttackFlag.score(&situation), 0.0);
+
+        let far = tile(16.0, 5.0);
+        situation.enemy_flag = Some(flag(far, FlagState::Home));
+        assert_eq!(AttackFlag.score(&situation), 0.65);
+        situation.orders = orders(FlagRole::Defend);
+        assert_eq!(AttackFlag.score(&situation), 0.25);
+        let near = tile(13.0, 5.0);
+        situation.enemy_flag = Some(flag(near, FlagState::Home));
+        assert_eq!(AttackFlag.score(&situation), 0.92);
+        assert_towards(fixture.steer(&AttackFlag, &situation), near);
+
+        situation.carrying = true;
+        assert_eq!(AttackFlag.score(&situation), 0.95);
+        assert_towards(fixture.steer(&AttackFlag, &situation), tile(1.0, 5.0));
+    }
+
+    #[test]
+    fn escort_runs_ahead_of_the_carrier() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation();
+        situation.orders = orders(FlagRole::Escort);
+        assert_eq!(EscortCarrier.score(&situation), 0.0);
+        situation.flag_carrier = Some(tile(13.0, 5.0));
+        assert_eq!(EscortCarrier.score(&situation), 0.7);
+        situation.orders = orders(FlagRole::Attack);
+        assert_eq!(EscortCarrier.score(&situation), 0.0);
+
+        // A tile ahead of the carrier, on its way home.
+        situation.orders = orders(FlagRole::Escort);
+        assert_towards(fixture.steer(&EscortCarrier, &situation), tile(12.0, 5.0));
+    }
+
+    #[test]
+    fn defend_flag_guards_chases_and_returns() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation();
+        assert_eq!(DefendFlag.score(&situation), 0.0);
+
+        let stand = tile(3.0, 5.0);
+        situation.own_flag = Some(flag(stand, FlagState::Home));
+        assert_eq!(DefendFlag.score(&situation), 0.0);
+        situation.orders = orders(FlagRole::Defend);
+        assert_eq!(DefendFlag.score(&situation), 0.62);
+        // Guard a little way out from the stand, towards the middle.
+        let guard_point = vec2_add(stand, vec2(GUARD_DISTANCE, 0.0));
+        assert_towards(fixture.steer(&DefendFlag, &situation), guard_point);
+
+        let thief = tile(4.0, 5.0);
+        situation.own_flag = Some(flag(thief, FlagState::Carried(5)));
+        assert_eq!(DefendFlag.score(&situation), 0.85);
+        assert_towards(fixture.steer(&DefendFlag, &situation), thief);
+        situation.orders = orders(FlagRole::Attack);
+        assert_eq!(DefendFlag.score(&situation), 0.4);
+        let nearby = tile(12.0, 5.0);
+        situation.own_flag = Some(flag(nearby, FlagState::Carried(5)));
+        assert_eq!(DefendFlag.score(&situation), 0.75);
+
+        let dropped = FlagState::Dropped { return_timer: 5.0 };
+        situation.own_flag = Some(flag(nearby, dropped));
+        assert_eq!(DefendFlag.score(&situation), 0.8);
+        assert_towards(fixture.steer(&DefendFlag, &situation), nearby);
+    }
+
+    #[test]
+    fn defenders_hold_once_on_station() {
+        let fixture = Fixture::new();
+        let stand = vec2_sub(CENTER, vec2(GUARD_DISTANCE, 0.0));
+        let mut situation = fixture.situation();
+        situation.orders = orders(FlagRole::Defend);
+        situation.own_flag = Some(flag(stand, FlagState::Home));
+        situation.current = BehaviourKind::DefendFlag;
+        let direction = fixture.steer(&DefendFlag, &situation);
+        assert_eq!((direction.x, direction.y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn choose_picks_the_behaviour_for_the_moment() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation();
+        assert_eq!(choose(&situation).kind(), BehaviourKind::Patrol);
+
+        situation.target = Some(target(tile(14.0, 5.0), true));
+        assert_eq!(choose(&situation).kind(), BehaviourKind::Engage);
+
+        // Hurt, under fire and near a Heal: fall back to it.
+        situation.health = 0.3;
+        situation.powerups = vec![powerup(PowerupKind::Heal, tile(8.0, 5.0))];
+        assert_eq!(choose(&situation).kind(), BehaviourKind::RetreatToHeal);
+
+        // A flag carrier keeps running whatever else is going on.
+        situation.carrying = true;
+        assert_eq!(choose(&situation).kind(), BehaviourKind::AttackFlag);
+    }
+
+    #[test]
+    fn choose_sticks_with_a_close_second() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation();
+        // Behind cover, flanking edges out engaging...
+        situation.target = Some(target(tile(14.0, 5.0), false));
+        assert_eq!(choose(&situation).kind(), BehaviourKind::Flank);
+        // ...but not by enough to drop an engagement already under way.
+        situation.current = BehaviourKind::Engage;
+        assert_eq!(choose(&situation).kind(), BehaviourKind::Engage);
+    }
+}
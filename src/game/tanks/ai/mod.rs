@@ -0,0 +1,133 @@
+mod behaviours;
+mod perception;
+mod powerups;
+
+use rand::Rng;
+
+use crate::entities::{Bullet, Tank, Team, TrackMark};
+use crate::math::{
+    GameRng, Vector2, angle_difference, intercept_point, rotate_towards, vec2, vec2_add,
+    vec2_angle, vec2_distance, vec2_length, vec2_lerp, vec2_normalize, vec2_scale, vec2_sub,
+};
+use crate::world::World;
+
+use super::TankContext;
+use super::combat::fire_bullet;
+use super::modifiers::{range_multiplier, speed_multiplier};
+use super::movement::advance_tank;
+use perception::{Situation, Target, find_target};
+
+pub(super) fn update_ai_tank(
+    tank: &mut Tank,
+    index: usize,
+    dt: f32,
+    context: &TankContext,
+    rng: &mut GameRng,
+    new_tracks: &mut Vec<TrackMark>,
+    new_bullets: &mut Vec<Bullet>,
+) {
+    let TankContext { world, config, .. } = *context;
+    tank.path_retry = (tank.path_retry - dt).max(0.0);
+    let profile = config.ai.profile(tank.skill);
+    let target = find_target(context, tank.team, tank.pos);
+    notice_target(tank, target, profile.reaction_time, profile.aim_error, rng);
+
+    let situation = Situation::observe(tank, index, context, profile, target);
+    let behaviour = behaviours::choose(&situation);
+    tank.ai.behaviour = behaviour.kind();
+    let steering = behaviour.steer(tank, &situation, rng);
+
+    let turret_rot_speed = config.tank.turret_rot_speed * profile.turret_speed;
+    if tank.ai.reaction_timer > 0.0 {
+        // Still taking in the new target: hold the turret where it is.
+        tank.ai.reaction_timer -= dt;
+    } else if let Some(target) = target {
+        let aim_pos = lead_target(tank.pos, &target, config.bullet.speed, profile.leading);
+        let aim_angle = vec2_angle(vec2_sub(aim_pos, tank.pos)) + tank.ai.aim_offset;
+        tank.turret_angle = rotate_towards(tank.turret_angle, aim_angle, turret_rot_speed * dt);
+
+        if target.visible
+            && angle_difference(tank.turret_angle, aim_angle) < profile.aim_tolerance
+            && tank.fire_cooldown <= 0.0
+            && target.dist < config.ai.fire_range * range_multiplier(tank)
+        {
+            fire_bullet(tank, config, new_bullets);
+            tank.ai.aim_offset = sample_aim_offset(profile.aim_error, rng);
+        }
+    } else {
+        tank.turret_angle =
+            rotate_towards(tank.turret_angle, tank.body_angle, turret_rot_speed * dt);
+    }
+
+    let avoidance = avoidance_vector(world, tank.team, tank.pos);
+    let steer = vec2_normalize(vec2_add(
+        steering.direction,
+        vec2_scale(avoidance, steering.avoidance),
+    ));
+    if vec2_length(steer) > 0.1 {
+        let target_angle = vec2_angle(steer);
+        tank.body_angle = rotate_towards(
+            tank.body_angle,
+            target_angle,
+            config.tank.body_rot_speed * dt,
+        );
+        advance_tank(tank, dt, speed_multiplier(tank), world, new_tracks);
+    }
+}
+
+/// Starts the reaction delay when the bot switches targets or its target
+/// steps out of cover.
+fn notice_target(
+    tank: &mut Tank,
+    target: Option<Target>,
+    reaction_time: f32,
+    aim_error: f32,
+    rng: &mut GameRng,
+) {
+    let index = target.map(|target| target.index);
+    let visible = target.is_some_and(|target| target.visible);
+    if index.is_some() && (index != tank.ai.target || (visible && !tank.ai.target_visible)) {
+        tank.ai.reaction_timer = reaction_time;
+        tank.ai.aim_offset = sample_aim_offset(aim_error, rng);
+    }
+    tank.ai.target = index;
+    tank.ai.target_visible = visible;
+}
+
+fn sample_aim_offset(aim_error: f32, rng: &mut GameRng) -> f32 {
+    if aim_error > 0.0 {
+        rng.random_range(-aim_error..aim_error)
+    } else {
+        0.0
+    }
+}
+
+/// Blends between aiming at the target (`leading` 0) and at the point a
+/// bullet would meet it if it kept its current velocity (`leading` 1).
+fn lead_target(from: Vector2, target: &Target, bullet_speed: f32, leading: f32) -> Vector2 {
+    match intercept_point(from, target.pos, target.vel, bullet_speed) {
+        Some(intercept) => vec2_lerp(target.pos, intercept, leading),
+        None => target.pos,
+    }
+}
+
+fn avoidance_vector(world: &World, team: Team, pos: Vector2) -> Vector2 {
+    let mut steer = vec2(0.0, 0.0);
+    for obstacle in &world.obstacles {
+        let dist = vec2_distance(pos, obstacle.pos);
+        let avoid_radius = obstacle.radius + 70.0;
+        if dist < avoid_radius && dist > 0.1 {
+            let push = vec2_scale(
+                vec2_normalize(vec2_sub(pos, obstacle.pos)),
+                (avoid_radius - dist) / avoid_radius,
+            );
+            steer = vec2_add(steer, push);
+        }
+    }
+
+    if let Some(zone) = world.enemy_zone_at(team, pos) {
+        steer = vec2_add(steer, vec2_normalize(vec2_sub(pos, zone.center())));
+    }
+
+    steer
+}
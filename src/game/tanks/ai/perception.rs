@@ -0,0 +1,201 @@
+use crate::config::{BULLET_RADIUS, GameConfig, SkillProfile, TILE_SIZE};
+use crate::entities::{BehaviourKind, Flag, FlagState, PowerupKind, Tank, Team};
+use crate::math::{Vector2, vec2_distance};
+use crate::world::World;
+
+use super::super::TankContext;
+use super::super::squad::{Orders, TeamPlan};
+
+/// Enemies this close to the edge of a team's spawn zone count as a threat
+/// to it.
+const SPAWN_THREAT_DISTANCE: f32 = TILE_SIZE * 3.0;
+
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Target {
+    /// Position of the target in the tick snapshot.
+    pub index: usize,
+    pub pos: Vector2,
+    pub vel: Vector2,
+    pub dist: f32,
+    /// Whether a bullet fired now would reach the target past obstacles.
+    pub visible: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(super) struct PowerupSighting {
+    pub kind: PowerupKind,
+    pub pos: Vector2,
+    pub dist: f32,
+    /// Distance from the nearest living enemy, infinite if there is none.
+    pub enemy_dist: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(super) struct FlagSighting {
+    pub pos: Vector2,
+    pub dist: f32,
+    pub state: FlagState,
+}
+
+impl FlagSighting {
+    fn of(flag: &Flag, from: Vector2) -> Self {
+        Self {
+            pos: flag.pos,
+            dist: vec2_distance(flag.pos, from),
+            state: flag.state,
+        }
+    }
+}
+
+/// Everything a behaviour may base its score on, gathered once per tick.
+/// Behaviours only read this, so each can be scored in isolation.
+pub(super) struct Situation<'a> {
+    pub world: &'a World,
+    pub config: &'a GameConfig,
+    pub profile: &'a SkillProfile,
+    pub pos: Vector2,
+    /// Fraction of max health left.
+    pub health: f32,
+    pub invincible: bool,
+    pub rapid: bool,
+    pub target: Option<Target>,
+    pub powerups: Vec<PowerupSighting>,
+    /// Nearest enemy closing in on this team's spawn zone.
+    pub spawn_threat: Option<Vector2>,
+    /// Carrying an enemy flag. The flag fields stay empty outside capture
+    /// the flag.
+    pub carrying: bool,
+    /// Nearest enemy flag nobody is carrying.
+    pub enemy_flag: Option<FlagSighting>,
+    pub own_flag: Option<FlagSighting>,
+    /// Nearest teammate carrying a flag home.
+    pub flag_carrier: Option<Vector2>,
+    pub plan: &'a TeamPlan,
+    pub orders: Orders,
+    /// Behaviour chosen last tick.
+    pub current: BehaviourKind,
+}
+
+impl<'a> Situation<'a> {
+    pub fn observe(
+        tank: &Tank,
+        index: usize,
+        context: &TankContext<'a>,
+        profile: &'a SkillProfile,
+        target: Option<Target>,
+    ) -> Self {
+        let TankContext {
+            world,
+            config,
+            snapshot,
+            powerups,
+            flags,
+            squads,
+        } = *context;
+        let zone = world.spawn_zone(tank.team);
+        let spawn_threat = snapshot
+            .iter()
+            .filter(|other| other.alive && other.team != tank.team)
+            .map(|other| (other.pos, vec2_distance(other.pos, zone.center())))
+            .filter(|(pos, _)| zone.distance_to(*pos) < SPAWN_THREAT_DISTANCE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(pos, _)| pos);
+        Self {
+            world,
+            config,
+            profile,
+            pos: tank.pos,
+            health: tank.health / tank.max_health,
+            invincible: tank.invincible_timer > 0.0,
+            rapid: tank.rapid_timer > 0.0,
+            target,
+            powerups: powerups
+                .iter()
+                .map(|powerup| PowerupSighting {
+                    kind: powerup.kind,
+                    pos: powerup.pos,
+                    dist: vec2_distance(powerup.pos, tank.pos),
+                    enemy_dist: snapshot
+                        .iter()
+                        .filter(|other| other.alive && other.team != tank.team)
+                        .map(|other| vec2_distance(other.pos, powerup.pos))
+                        .fold(f32::INFINITY, f32::min),
+                })
+                .collect(),
+            spawn_threat,
+            carrying: flags.iter().any(|flag| flag.carrier() == Some(index)),
+            enemy_flag: flags
+                .iter()
+                .filter(|flag| flag.team != tank.team && flag.carrier().is_none())
+                .map(|flag| FlagSighting::of(flag, tank.pos))
+                .min_by(|a, b| a.dist.total_cmp(&b.dist)),
+            own_flag: flags
+                .iter()
+                .find(|flag| flag.team == tank.team)
+                .map(|flag| FlagSighting::of(flag, tank.pos)),
+            flag_carrier: flags
+                .iter()
+                .filter_map(Flag::carrier)
+                .filter(|&carrier| carrier != index)
+                .filter_map(|carrier| snapshot.get(carrier))
+                .filter(|ally| ally.alive && ally.team == tank.team)
+                .map(|ally| ally.pos)
+                .min_by(|a, b| vec2_distance(*a, tank.pos).total_cmp(&vec2_distance(*b, tank.pos))),
+            plan: squads.plan(tank.team),
+            orders: squads.orders(index),
+            current: tank.ai.behaviour,
+        }
+    }
+
+    /// The closest powerup of `kind`.
+    pub fn nearest_powerup(&self, kind: PowerupKind) -> Option<PowerupSighting> {
+        self.powerups
+            .iter()
+            .filter(|powerup| powerup.kind == kind)
+            .min_by(|a, b| a.dist.total_cmp(&b.dist))
+            .copied()
+    }
+}
+
+/// The team's focus target when it is in the clear and in range, otherwise
+/// the nearest enemy with a clear line of fire, or the nearest enemy overall
+/// when every one of them is behind cover.
+pub(super) fn find_target(context: &TankContext, team: Team, pos: Vector2) -> Option<Target> {
+    let TankContext {
+        world,
+        config,
+        snapshot,
+        squads,
+        ..
+    } = *context;
+    let focus = squads.plan(team).focus;
+    let mut best: Option<Target> = None;
+    for (index, other) in snapshot.iter().enumerate() {
+        if !other.alive || other.team == team {
+            continue;
+        }
+        let candidate = Target {
+            index,
+            pos: other.pos,
+            vel: other.vel,
+            dist: vec2_distance(other.pos, pos),
+            visible: world.line_of_sight(pos, other.pos, BULLET_RADIUS),
+        };
+        let focused = |target: &Target| {
+            Some(target.index) == focus && target.visible && target.dist < config.ai.fire_range
+        };
+        let better = best.is_none_or(|current| {
+            if candidate.visible != current.visible {
+                candidate.visible
+            } else if focused(&candidate) != focused(&current) {
+                focused(&candidate)
+            } else {
+                candidate.dist < current.dist
+            }
+        });
+        if better {
+            best = Some(candidate);
+        }
+    }
+    best
+}
@@ -0,0 +1,205 @@
+use crate::config::TILE_SIZE;
+use crate::entities::PowerupKind;
+
+use super::perception::{PowerupSighting, Situation};
+
+/// Furthest a bot will drive for a pickup it wants as much as possible.
+const MAX_DETOUR: f32 = TILE_SIZE * 8.0;
+/// Enemies this close to a pickup are about to take it.
+const CONTEST_DISTANCE: f32 = TILE_SIZE * 3.0;
+/// Value of keeping a pickup from an enemy sitting right on top of it.
+const DENIAL_VALUE: f32 = 0.4;
+
+/// How much the bot itself would gain from `kind` right now, 0..1.
+pub(super) fn pickup_value(kind: PowerupKind, situation: &Situation) -> f32 {
+    match kind {
+        PowerupKind::Heal => 1.0 - situation.health,
+        PowerupKind::RapidRange => match situation.target {
+            _ if situation.rapid => 0.1,
+            Some(target) if target.visible => 0.9,
+            Some(_) => 0.6,
+            None => 0.3,
+        },
+        // Worth most to bots about to push in.
+        PowerupKind::Invincible if situation.invincible => 0.1,
+        PowerupKind::Invincible => 0.3 + 0.6 * situation.profile.aggression,
+    }
+}
+
+/// Extra value from taking a pickup before a nearby enemy does.
+fn denial_value(sighting: &PowerupSighting) -> f32 {
+    if sighting.enemy_dist < CONTEST_DISTANCE {
+        DENIAL_VALUE * (1.0 - sighting.enemy_dist / CONTEST_DISTANCE)
+    } else {
+        0.0
+    }
+}
+
+/// How worthwhile driving to `sighting` is, falling off with distance.
+pub(super) fn pickup_worth(sighting: &PowerupSighting, situation: &Situation) -> f32 {
+    let value = pickup_value(sighting.kind, situation) + denial_value(sighting);
+    let reach = MAX_DETOUR * value.min(1.0);
+    if sighting.dist >= reach {
+        return 0.0;
+    }
+    value * (1.0 - sighting.dist / reach)
+}
+
+/// The pickup most worth going for, with its worth.
+pub(super) fn best_pickup(situation: &Situation) -> Option<(PowerupSighting, f32)> {
+    situation
+        .powerups
+        .iter()
+        .map(|sighting| (*sighting, pickup_worth(sighting, situation)))
+        .filter(|(_, worth)| *worth > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{GameConfig, SkillProfile};
+    use crate::entities::BehaviourKind;
+    use crate::math::vec2;
+    use crate::world::World;
+
+    use super::super::super::squad::{Orders, TeamPlan};
+    use super::super::perception::Target;
+    use super::*;
+
+    struct Fixture {
+        world: World,
+        config: GameConfig,
+        profile: SkillProfile,
+        plan: TeamPlan,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            Self {
+                world: World::open(10, 10, Vec::new(), Vec::new()),
+                config: GameConfig::default(),
+                profile: SkillProfile::veteran(),
+                plan: TeamPlan {
+                    focus: None,
+                    outnumbered: false,
+                    rally: vec2(0.0, 0.0),
+                },
+            }
+        }
+
+        /// A bot at full health with nothing in sight but `powerups`.
+        fn situation(&self, powerups: Vec<PowerupSighting>) -> Situation<'_> {
+            Situation {
+                world: &self.world,
+                config: &self.config,
+                profile: &self.profile,
+                pos: vec2(0.0, 0.0),
+                health: 1.0,
+                invincible: false,
+                rapid: false,
+                target: None,
+                powerups,
+                spawn_threat: None,
+                carrying: false,
+                enemy_flag: None,
+                own_flag: None,
+                flag_carrier: None,
+                plan: &self.plan,
+                orders: Orders::default(),
+                current: BehaviourKind::Patrol,
+            }
+        }
+    }
+
+    fn sighting(kind: PowerupKind, dist: f32, enemy_dist: f32) -> PowerupSighting {
+        PowerupSighting {
+            kind,
+            pos: vec2(dist, 0.0),
+            dist,
+            enemy_dist,
+        }
+    }
+
+    #[test]
+    fn pickups_are_worth_what_the_bot_lacks() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation(Vec::new());
+        assert_eq!(pickup_value(PowerupKind::Heal, &situation), 0.0);
+        situation.health = 0.25;
+        assert_eq!(pickup_value(PowerupKind::Heal, &situation), 0.75);
+
+        assert_eq!(pickup_value(PowerupKind::RapidRange, &situation), 0.3);
+        situation.target = Some(Target {
+            index: 1,
+            pos: vec2(300.0, 0.0),
+            vel: vec2(0.0, 0.0),
+            dist: 300.0,
+            visible: true,
+        });
+        assert_eq!(pickup_value(PowerupKind::RapidRange, &situation), 0.9);
+        situation.rapid = true;
+        assert_eq!(pickup_value(PowerupKind::RapidRange, &situation), 0.1);
+
+        let aggression = fixture.profile.aggression;
+        assert_eq!(
+            pickup_value(PowerupKind::Invincible, &situation),
+            0.3 + 0.6 * aggression
+        );
+        situation.invincible = true;
+        assert_eq!(pickup_value(PowerupKind::Invincible, &situation), 0.1);
+    }
+
+    #[test]
+    fn denial_grows_as_an_enemy_closes_in() {
+        let far = sighting(PowerupKind::Heal, 0.0, CONTEST_DISTANCE);
+        let near = sighting(PowerupKind::Heal, 0.0, CONTEST_DISTANCE / 2.0);
+        let on_top = sighting(PowerupKind::Heal, 0.0, 0.0);
+        assert_eq!(denial_value(&far), 0.0);
+        assert_eq!(denial_value(&near), DENIAL_VALUE / 2.0);
+        assert_eq!(denial_value(&on_top), DENIAL_VALUE);
+    }
+
+    #[test]
+    fn a_pickup_the_bot_needs_beats_one_it_would_only_deny() {
+        let fixture = Fixture::new();
+        let mut situation = fixture.situation(vec![
+            sighting(PowerupKind::Heal, 300.0, 0.0),
+            sighting(PowerupKind::Invincible, 300.0, f32::INFINITY),
+        ]);
+        let (best, _) = best_pickup(&situation).unwrap();
+        assert_eq!(best.kind, PowerupKind::Invincible);
+
+        // Once nothing else is on offer, a contested pickup is still worth
+        // taking away from the enemy even at full health.
+        situation.powerups.truncate(1);
+        let (best, worth) = best_pickup(&situation).unwrap();
+        assert_eq!(best.kind, PowerupKind::Heal);
+        assert!(worth > 0.0);
+    }
+
+    #[test]
+    fn a_near_pickup_beats_a_far_one() {
+        let fixture = Fixture::new();
+        let situation = fixture.situation(vec![
+            sighting(PowerupKind::RapidRange, 500.0, f32::INFINITY),
+            sighting(PowerupKind::RapidRange, 150.0, f32::INFINITY),
+        ]);
+        let (best, worth) = best_pickup(&situation).unwrap();
+        assert_eq!(best.dist, 150.0);
+        assert_eq!(worth, pickup_worth(&situation.powerups[1], &situation));
+        assert!(worth > pickup_worth(&situation.powerups[0], &situation));
+    }
+
+    #[test]
+    fn nothing_is_picked_when_no_pickup_is_worth_the_trip() {
+        let fixture = Fixture::new();
+        assert!(best_pickup(&fixture.situation(Vec::new())).is_none());
+        // A heal nobody else wants is useless at full health, and anything
+        // past the furthest detour is out of reach.
+        let situation = fixture.situation(vec![
+            sighting(PowerupKind::Heal, 100.0, f32::INFINITY),
+            sighting(PowerupKind::RapidRange, MAX_DETOUR, f32::INFINITY),
+        ]);
+        assert!(best_pickup(&situation).is_none());
+    }
+}
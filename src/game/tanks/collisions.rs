@@ -24,10 +24,10 @@ pub(super) fn resolve_tank_collisions(tanks: &mut [Tank], world: &World) {
         if !tank.alive {
             continue;
         }
-        if let Some(zone) = world
+        for zone in world
             .spawn_zones
             .iter()
-            .find(|zone| zone.team == tank.team.enemy())
+            .filter(|zone| zone.team != tank.team)
         {
             tank.pos = push_outside_rect(tank.pos, zone.rect, TANK_RADIUS + 2.0);
         }
@@ -1,21 +1,24 @@
-use crate::config::{BULLET_LIFE, BULLET_SPEED, FIRE_COOLDOWN};
+use crate::config::GameConfig;
 use crate::entities::{Bullet, Tank};
 use crate::math::{vec2_add, vec2_from_angle, vec2_scale};
 
 use super::super::constants::BARREL_LENGTH;
 use super::modifiers::{fire_rate_multiplier, range_multiplier};
 
-pub(super) fn fire_bullet(tank: &mut Tank, new_bullets: &mut Vec<Bullet>) {
-    new_bullets.push(bullet_from_tank(tank));
-    tank.fire_cooldown = FIRE_COOLDOWN / fire_rate_multiplier(tank);
+pub(super) fn fire_bullet(tank: &mut Tank, config: &GameConfig, new_bullets: &mut Vec<Bullet>) {
+    new_bullets.push(bullet_from_tank(tank, config));
+    tank.fire_cooldown = config.tank.fire_cooldown / fire_rate_multiplier(tank);
 }
 
-fn bullet_from_tank(tank: &Tank) -> Bullet {
+fn bullet_from_tank(tank: &Tank, config: &GameConfig) -> Bullet {
     let dir = vec2_from_angle(tank.turret_angle);
+    let pos = vec2_add(tank.pos, vec2_scale(dir, BARREL_LENGTH));
     Bullet {
-        pos: vec2_add(tank.pos, vec2_scale(dir, BARREL_LENGTH)),
-        vel: vec2_scale(dir, BULLET_SPEED),
+        pos,
+        prev_pos: pos,
+        vel: vec2_scale(dir, config.bullet.speed),
         team: tank.team,
-        life: BULLET_LIFE * range_multiplier(tank),
+        owner: None,
+        life: config.bullet.life * range_multiplier(tank),
     }
 }
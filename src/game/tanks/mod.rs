@@ -3,29 +3,70 @@ mod collisions;
 mod combat;
 mod modifiers;
 mod movement;
+mod pathing;
 mod player;
 mod spawn;
+mod squad;
 
-use raylib::prelude::{RaylibHandle, Vector2};
-
-use crate::entities::{Tank, Team};
+use crate::config::GameConfig;
+use crate::entities::{Flag, Powerup, Tank, Team};
+use crate::math::{GameRng, Vector2, vec2, vec2_scale, vec2_sub};
 use crate::world::World;
 
 use super::Game;
+use super::input::{PlayerInput, TickInput};
+use squad::Blackboard;
+
+/// Read-only state every tank update in a tick can look at.
+struct TankContext<'a> {
+    world: &'a World,
+    config: &'a GameConfig,
+    snapshot: &'a [TankSnapshot],
+    powerups: &'a [Powerup],
+    /// Empty outside capture the flag.
+    flags: &'a [Flag],
+    squads: &'a Blackboard,
+}
+
+/// Where a tank was at the start of the tick.
+#[derive(Clone, Copy)]
+struct TankSnapshot {
+    team: Team,
+    pos: Vector2,
+    vel: Vector2,
+    /// Fraction of max health left.
+    health: f32,
+    alive: bool,
+}
 
-pub(super) fn spawn_tanks(rng: &mut rand::rngs::SmallRng, world: &World) -> Vec<Tank> {
-    spawn::spawn_tanks(rng, world)
+pub(super) fn spawn_tanks(rng: &mut GameRng, world: &World, config: &GameConfig) -> Vec<Tank> {
+    spawn::spawn_tanks(rng, world, config)
 }
 
 impl Game {
-    pub(super) fn update_tanks(&mut self, dt: f32, rl: &RaylibHandle) {
+    /// How far `tank` is through reloading, from 0 right after a shot to 1
+    /// when it can fire again.
+    #[cfg(feature = "graphics")]
+    pub(super) fn reload_progress(&self, tank: &Tank) -> f32 {
+        let full = self.config.tank.fire_cooldown / modifiers::fire_rate_multiplier(tank);
+        (1.0 - tank.fire_cooldown / full).clamp(0.0, 1.0)
+    }
+
+    pub(super) fn update_tanks(&mut self, dt: f32, input: &TickInput) {
         let snapshot = collect_snapshot(&self.tanks);
+        let squads = Blackboard::build(&self.world, &snapshot, &self.flags);
         let mut new_bullets = Vec::new();
         let mut new_tracks = Vec::new();
         let world = &self.world;
-        let camera = self.camera(rl.get_screen_width(), rl.get_screen_height());
-        let mouse_world = rl.get_screen_to_world2D(rl.get_mouse_position(), camera);
-        let player_input = self.input_state.player_input(rl);
+        let config = &self.config;
+        let context = TankContext {
+            world,
+            config,
+            snapshot: &snapshot,
+            powerups: &self.powerups,
+            flags: &self.flags,
+            squads: &squads,
+        };
 
         for (index, tank) in self.tanks.iter_mut().enumerate() {
             movement::update_tank_timers(tank, dt);
@@ -33,39 +74,78 @@ impl Game {
             if !tank.alive {
                 tank.respawn_timer -= dt;
                 if tank.respawn_timer <= 0.0 {
-                    spawn::respawn_tank(tank, world, &mut self.rng);
+                    spawn::respawn_tank(tank, world, config, &mut self.rng);
                 }
                 continue;
             }
 
             tank.fire_cooldown = (tank.fire_cooldown - dt).max(0.0);
 
-            if index == self.player_index {
+            let manual = input
+                .tanks
+                .iter()
+                .find(|external| external.tank == index)
+                .map(|external| &external.input);
+            let fired_from = new_bullets.len();
+            if let Some(manual) = manual {
                 player::update_player_tank(
                     tank,
                     dt,
-                    world,
-                    mouse_world,
-                    &player_input,
+                    &context,
+                    manual,
                     &mut new_tracks,
                     &mut new_bullets,
                 );
-                continue;
+            } else {
+                ai::update_ai_tank(
+                    tank,
+                    index,
+                    dt,
+                    &context,
+                    &mut self.rng,
+                    &mut new_tracks,
+                    &mut new_bullets,
+                );
+            }
+            for bullet in &mut new_bullets[fired_from..] {
+                bullet.owner = Some(index);
             }
-            ai::update_ai_tank(
-                tank,
-                dt,
-                world,
-                &snapshot,
-                &mut self.rng,
-                &mut new_tracks,
-                &mut new_bullets,
-            );
         }
 
         self.bullets.extend(new_bullets);
         self.tracks.extend(new_tracks);
         self.resolve_tank_collisions();
+        for tank in &mut self.tanks {
+            tank.vel = if tank.alive {
+                vec2_scale(vec2_sub(tank.pos, tank.prev_pos), 1.0 / dt)
+            } else {
+                vec2(0.0, 0.0)
+            };
+        }
+    }
+
+    /// Drives `tank` one tick as [`Game::update`] would under `input`,
+    /// against this game's map but ignoring other tanks and discarding any
+    /// shots. Clients use it to predict their own tank ahead of the server.
+    pub fn predict_tank(&self, tank: &mut Tank, input: &PlayerInput, dt: f32) {
+        tank.store_previous();
+        if !self.is_playing() || self.countdown_timer > 0.0 || !tank.alive {
+            return;
+        }
+        movement::update_tank_timers(tank, dt);
+        tank.fire_cooldown = (tank.fire_cooldown - dt).max(0.0);
+        let squads = Blackboard::build(&self.world, &[], &[]);
+        let context = TankContext {
+            world: &self.world,
+            config: &self.config,
+            snapshot: &[],
+            powerups: &self.powerups,
+            flags: &[],
+            squads: &squads,
+        };
+        let mut tracks = Vec::new();
+        let mut bullets = Vec::new();
+        player::update_player_tank(tank, dt, &context, input, &mut tracks, &mut bullets);
     }
 
     fn resolve_tank_collisions(&mut self) {
@@ -73,9 +153,15 @@ impl Game {
     }
 }
 
-fn collect_snapshot(tanks: &[Tank]) -> Vec<(Team, Vector2, bool)> {
+fn collect_snapshot(tanks: &[Tank]) -> Vec<TankSnapshot> {
     tanks
         .iter()
-        .map(|tank| (tank.team, tank.pos, tank.alive))
+        .map(|tank| TankSnapshot {
+            team: tank.team,
+            pos: tank.pos,
+            vel: tank.vel,
+            health: tank.health / tank.max_health,
+            alive: tank.alive,
+        })
         .collect()
 }
@@ -1,8 +1,6 @@
-use raylib::prelude::Vector2;
-
 use crate::config::TANK_RADIUS;
 use crate::entities::{Tank, Team, TrackMark};
-use crate::math::{vec2_add, vec2_distance, vec2_from_angle, vec2_scale};
+use crate::math::{Vector2, vec2, vec2_add, vec2_distance, vec2_from_angle, vec2_scale};
 use crate::world::World;
 
 use super::super::constants::{TRACK_OFFSET, TRACK_STEP_DISTANCE};
@@ -13,14 +11,24 @@ pub(super) fn update_tank_timers(tank: &mut Tank, dt: f32) {
     tank.rapid_timer = (tank.rapid_timer - dt).max(0.0);
 }
 
+/// Moves `tank` to `new_pos`, or slides it along one axis when something is
+/// in the way, so a tank grazing an obstacle keeps going.
 pub(super) fn try_move_tank(
     tank: &mut Tank,
     world: &World,
     new_pos: Vector2,
     new_tracks: &mut Vec<TrackMark>,
 ) {
-    if position_clear(world, tank.team, new_pos, TANK_RADIUS) {
-        move_tank_with_tracks(tank, new_pos, new_tracks);
+    let slides = [
+        new_pos,
+        vec2(new_pos.x, tank.pos.y),
+        vec2(tank.pos.x, new_pos.y),
+    ];
+    let clear = slides
+        .into_iter()
+        .find(|&pos| position_clear(world, tank.team, pos, TANK_RADIUS));
+    if let Some(pos) = clear {
+        move_tank_with_tracks(tank, pos, new_tracks);
     }
 }
 
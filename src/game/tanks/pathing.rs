@@ -0,0 +1,147 @@
+use crate::config::TILE_SIZE;
+use crate::entities::Tank;
+use crate::math::{Vector2, distance_to_segment, vec2_distance, vec2_normalize, vec2_sub};
+use crate::world::{NAV_CELL_SIZE, World};
+
+/// How far the goal may drift before the cached path is replanned.
+const REPATH_DISTANCE: f32 = TILE_SIZE;
+const WAYPOINT_REACHED: f32 = NAV_CELL_SIZE * 0.75;
+/// How far a tank may be pushed off its current leg before the path is
+/// replanned from where it now is.
+const OFF_COURSE_DISTANCE: f32 = NAV_CELL_SIZE;
+/// Seconds before a goal that had no route is searched for again, unless
+/// the goal moves first.
+const NO_ROUTE_RETRY: f32 = 1.0;
+
+/// Direction along the tank's cached A* path towards `goal`, replanning when
+/// the goal has moved or the tank has been shoved off its current leg. A goal
+/// with no route is only searched for again once `NO_ROUTE_RETRY` has passed;
+/// until then, and once the path is used up, the tank heads straight for it.
+pub(super) fn path_direction(tank: &mut Tank, world: &World, goal: Vector2) -> Vector2 {
+    let stale = tank
+        .path_goal
+        .is_none_or(|planned| vec2_distance(planned, goal) > REPATH_DISTANCE);
+    let retry = tank.path.is_empty() && tank.path_retry <= 0.0;
+    if stale || retry || off_course(tank) {
+        tank.path = world.nav().find_path(tank.team, tank.pos, goal);
+        tank.path.reverse();
+        if tank.path.is_empty() {
+            tank.path_retry = NO_ROUTE_RETRY;
+        } else {
+            tank.path.push(tank.pos);
+        }
+        tank.path_goal = Some(goal);
+    }
+
+    while tank.path.len() > 2
+        && vec2_distance(tank.path[tank.path.len() - 2], tank.pos) < WAYPOINT_REACHED
+    {
+        tank.path.pop();
+    }
+
+    let next = match tank.path.as_slice() {
+        [.., next, _] => *next,
+        _ => goal,
+    };
+    vec2_normalize(vec2_sub(next, tank.pos))
+}
+
+/// Whether the tank has strayed from the leg between the point it started
+/// from and its next waypoint.
+fn off_course(tank: &Tank) -> bool {
+    match tank.path.as_slice() {
+        [.., next, from] => distance_to_segment(tank.pos, *from, *next) > OFF_COURSE_DISTANCE,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::config::GameConfig;
+    use crate::entities::Team;
+    use crate::math::{GameRng, Rectangle, vec2, vec2_add};
+    use crate::world::{Obstacle, ObstacleKind, SpawnZone};
+
+    use super::super::spawn::spawn_tanks;
+    use super::*;
+
+    /// A 20x10 tile map split in half by a wall with no gap in it.
+    fn walled_world() -> World {
+        let wall = (0..20)
+            .map(|y| Obstacle {
+                kind: ObstacleKind::BarrelRedUp,
+                pos: vec2(20.5 * NAV_CELL_SIZE, (y as f32 + 0.5) * NAV_CELL_SIZE),
+                radius: 20.0,
+            })
+            .collect();
+        let zone = |x, team| SpawnZone {
+            rect: Rectangle {
+                x: x * TILE_SIZE,
+                y: 3.0 * TILE_SIZE,
+                width: 2.0 * TILE_SIZE,
+                height: 4.0 * TILE_SIZE,
+            },
+            team,
+        };
+        World::open(
+            20,
+            10,
+            wall,
+            vec![zone(0.0, Team::Red), zone(18.0, Team::Blue)],
+        )
+    }
+
+    fn red_tank(world: &World) -> Tank {
+        let mut rng = GameRng::seed_from_u64(1);
+        let mut tank = spawn_tanks(&mut rng, world, &GameConfig::default()).remove(0);
+        assert_eq!(tank.team, Team::Red);
+        tank.pos = vec2(5.0 * TILE_SIZE, 5.0 * TILE_SIZE);
+        tank
+    }
+
+    #[test]
+    fn follows_a_route_to_a_reachable_goal() {
+        let world = walled_world();
+        let mut tank = red_tank(&world);
+        let goal = vec2(8.0 * TILE_SIZE, 1.0 * TILE_SIZE);
+        path_direction(&mut tank, &world, goal);
+        assert!(tank.path.len() >= 2);
+        assert_eq!(tank.path_goal, Some(goal));
+        assert_eq!(tank.path_retry, 0.0);
+    }
+
+    #[test]
+    fn an_unreachable_goal_is_not_searched_for_every_tick() {
+        let world = walled_world();
+        let mut tank = red_tank(&world);
+        let goal = vec2(15.0 * TILE_SIZE, 5.0 * TILE_SIZE);
+
+        let direction = path_direction(&mut tank, &world, goal);
+        assert!(tank.path.is_empty());
+        assert_eq!(tank.path_goal, Some(goal));
+        assert_eq!(tank.path_retry, NO_ROUTE_RETRY);
+        // Without a route the tank heads straight for the goal.
+        assert!(direction.x > 0.99);
+
+        // A search would reset the retry timer, so a timer left part-run
+        // shows the goal was not searched for again.
+        tank.path_retry = 0.5;
+        path_direction(&mut tank, &world, goal);
+        assert_eq!(tank.path_retry, 0.5);
+        path_direction(&mut tank, &world, vec2_add(goal, vec2(10.0, 0.0)));
+        assert_eq!(tank.path_retry, 0.5);
+
+        // Moving the goal properly searches again straight away.
+        let moved = vec2(15.0 * TILE_SIZE, 2.0 * TILE_SIZE);
+        path_direction(&mut tank, &world, moved);
+        assert_eq!(tank.path_goal, Some(moved));
+        assert_eq!(tank.path_retry, NO_ROUTE_RETRY);
+
+        // So does running the timer out.
+        tank.path_retry = 0.0;
+        path_direction(&mut tank, &world, moved);
+        assert_eq!(tank.path_retry, NO_ROUTE_RETRY);
+    }
+}
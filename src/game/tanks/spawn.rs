@@ -1,15 +1,14 @@
-use rand::{Rng, rngs::SmallRng};
-use raylib::prelude::Vector2;
+use rand::Rng;
 
-use crate::config::{FIRE_COOLDOWN, MAX_HEALTH, TANK_SPEED, TANKS_PER_TEAM, TILE_SIZE};
-use crate::entities::{Tank, Team};
-use crate::math::{random_angle, vec2};
+use crate::config::{GameConfig, TILE_SIZE};
+use crate::entities::{AiState, Tank, Team};
+use crate::math::{GameRng, Vector2, random_angle, vec2};
 use crate::world::World;
 
-pub(super) fn spawn_tanks(rng: &mut SmallRng, world: &World) -> Vec<Tank> {
+pub(super) fn spawn_tanks(rng: &mut GameRng, world: &World, config: &GameConfig) -> Vec<Tank> {
     let mut tanks = Vec::new();
-    for team in [Team::Red, Team::Blue] {
-        for _ in 0..TANKS_PER_TEAM {
+    for team in world.teams() {
+        for slot in 0..config.round.team_size() {
             let pos = world.random_point_in_zone(team, rng);
             let angle = random_angle(rng);
             tanks.push(Tank {
@@ -17,37 +16,51 @@ pub(super) fn spawn_tanks(rng: &mut SmallRng, world: &World) -> Vec<Tank> {
                 pos,
                 body_angle: angle,
                 turret_angle: angle,
-                speed: TANK_SPEED + rng.random_range(-12.0..14.0),
+                prev_pos: pos,
+                prev_body_angle: angle,
+                prev_turret_angle: angle,
+                vel: vec2(0.0, 0.0),
+                speed: config.tank.speed + rng.random_range(-12.0..14.0),
                 fire_cooldown: rng.random_range(0.0..0.8),
                 alive: true,
                 respawn_timer: 0.0,
                 waypoint: pick_waypoint(world, team, rng),
+                path: Vec::new(),
+                path_goal: None,
+                path_retry: 0.0,
                 track_distance: rng.random_range(0.0..40.0),
                 tread_phase: rng.random_range(0.0..3.0),
-                health: MAX_HEALTH,
-                max_health: MAX_HEALTH,
+                health: config.tank.max_health,
+                max_health: config.tank.max_health,
                 health_flash: 0.0,
                 invincible_timer: 0.0,
                 rapid_timer: 0.0,
+                skill: config.ai.difficulty_for(team, slot),
+                ai: AiState::default(),
             });
         }
     }
     tanks
 }
 
-pub(super) fn respawn_tank(tank: &mut Tank, world: &World, rng: &mut SmallRng) {
+pub(super) fn respawn_tank(tank: &mut Tank, world: &World, config: &GameConfig, rng: &mut GameRng) {
     tank.alive = true;
     tank.pos = world.random_point_in_zone(tank.team, rng);
     tank.body_angle = random_angle(rng);
     tank.turret_angle = tank.body_angle;
-    tank.fire_cooldown = FIRE_COOLDOWN * 0.5;
+    tank.store_previous();
+    tank.vel = vec2(0.0, 0.0);
+    tank.path.clear();
+    tank.path_goal = None;
+    tank.ai = AiState::default();
+    tank.fire_cooldown = config.tank.fire_cooldown * 0.5;
     tank.health = tank.max_health;
     tank.health_flash = 0.0;
     tank.invincible_timer = 0.0;
     tank.rapid_timer = 0.0;
 }
 
-pub(super) fn pick_waypoint(world: &World, team: Team, rng: &mut SmallRng) -> Vector2 {
+pub(super) fn pick_waypoint(world: &World, team: Team, rng: &mut GameRng) -> Vector2 {
     for _ in 0..40 {
         let bounds = world.world_bounds();
         let margin = TILE_SIZE * 1.2;
@@ -0,0 +1,424 @@
+use crate::config::{BULLET_RADIUS, TILE_SIZE};
+use crate::entities::{Flag, Team};
+use crate::math::{Vector2, vec2_add, vec2_distance, vec2_normalize, vec2_scale, vec2_sub};
+use crate::world::World;
+
+use super::TankSnapshot;
+
+/// Most lanes a team spreads over.
+const MAX_LANES: usize = 3;
+/// One flanker for every this many living tanks.
+const TANKS_PER_FLANKER: usize = 3;
+/// In capture the flag, one defender for every this many living tanks
+/// without a flag, rounded to the nearest.
+const TANKS_PER_DEFENDER: usize = 4;
+
+/// Team tactics every bot on a team reads, rebuilt from the snapshot at the
+/// start of each tick.
+pub(super) struct Blackboard {
+    /// Indexed by [`Team::index`].
+    teams: Vec<TeamPlan>,
+    /// Per tank, indexed like the snapshot.
+    orders: Vec<Orders>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(super) struct TeamPlan {
+    /// Weakest enemy some teammate can shoot at.
+    pub focus: Option<usize>,
+    /// Clearly fewer tanks alive than the strongest enemy team.
+    pub outnumbered: bool,
+    /// Where to fall back to when outnumbered, just outside the spawn zone.
+    pub rally: Vector2,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct Orders {
+    pub lane: Option<Lane>,
+    pub flank: bool,
+    pub role: FlagRole,
+}
+
+/// A bot's job in a capture-the-flag round.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) enum FlagRole {
+    /// Go for the enemy flags. Every bot attacks outside capture the flag.
+    #[default]
+    Attack,
+    /// Stay with a teammate bringing a flag home.
+    Escort,
+    /// Hold the team's own flag.
+    Defend,
+}
+
+/// A horizontal band of the map.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Lane {
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl Lane {
+    pub fn center(&self) -> f32 {
+        (self.top + self.bottom) * 0.5
+    }
+
+    pub fn contains(&self, y: f32) -> bool {
+        y >= self.top && y <= self.bottom
+    }
+}
+
+impl Blackboard {
+    pub fn build(world: &World, snapshot: &[TankSnapshot], flags: &[Flag]) -> Self {
+        let mut orders = vec![Orders::default(); snapshot.len()];
+        let teams = world
+            .teams()
+            .map(|team| {
+                let plan = plan_team(world, snapshot, flags, team);
+                assign_orders(world, snapshot, team, &plan, &mut orders);
+                assign_flag_roles(snapshot, flags, team, &mut orders);
+                plan
+            })
+            .collect();
+        Self { teams, orders }
+    }
+
+    pub fn plan(&self, team: Team) -> &TeamPlan {
+        &self.teams[team.index()]
+    }
+
+    pub fn orders(&self, index: usize) -> Orders {
+        self.orders.get(index).copied().unwrap_or_default()
+    }
+}
+
+fn living(snapshot: &[TankSnapshot], team: Team) -> impl Iterator<Item = (usize, &TankSnapshot)> {
+    snapshot
+        .iter()
+        .enumerate()
+        .filter(move |(_, tank)| tank.alive && tank.team == team)
+}
+
+fn living_enemies(
+    snapshot: &[TankSnapshot],
+    team: Team,
+) -> impl Iterator<Item = (usize, &TankSnapshot)> {
+    snapshot
+        .iter()
+        .enumerate()
+        .filter(move |(_, tank)| tank.alive && tank.team != team)
+}
+
+fn plan_team(world: &World, snapshot: &[TankSnapshot], flags: &[Flag], team: Team) -> TeamPlan {
+    let in_sight = |enemy: &TankSnapshot| {
+        living(snapshot, team)
+            .any(|(_, ally)| world.line_of_sight(ally.pos, enemy.pos, BULLET_RADIUS))
+    };
+    // Whoever is running off with our flag comes first.
+    let thief = flags
+        .iter()
+        .find(|flag| flag.team == team)
+        .and_then(Flag::carrier)
+        .filter(|&index| snapshot.get(index).is_some_and(&in_sight));
+    let focus = thief.or_else(|| {
+        living_enemies(snapshot, team)
+            .filter(|(_, enemy)| in_sight(enemy))
+            .min_by(|a, b| a.1.health.total_cmp(&b.1.health))
+            .map(|(index, _)| index)
+    });
+
+    // 3v4 is a fair fight, 2v4 is not. With several enemy teams, only
+    // the strongest counts; they are busy fighting each other too.
+    let allies = living(snapshot, team).count();
+    let enemies = world
+        .teams()
+        .filter(|other| *other != team)
+        .map(|other| living(snapshot, other).count())
+        .max()
+        .unwrap_or(0);
+    let outnumbered = allies * 3 < enemies * 2;
+
+    let zone = world.spawn_zone(team);
+    let forward = vec2_normalize(vec2_sub(world.center(), zone.center()));
+    let rally = vec2_add(
+        zone.center(),
+        vec2_scale(forward, zone.rect.width * 0.5 + TILE_SIZE),
+    );
+
+    TeamPlan {
+        focus,
+        outnumbered,
+        rally,
+    }
+}
+
+/// Splits the team across lanes by height and picks flankers from the
+/// outer lanes furthest from the focus target.
+fn assign_orders(
+    world: &World,
+    snapshot: &[TankSnapshot],
+    team: Team,
+    plan: &TeamPlan,
+    orders: &mut [Orders],
+) {
+    let mut members: Vec<(usize, Vector2)> = living(snapshot, team)
+        .map(|(index, tank)| (index, tank.pos))
+        .collect();
+    if members.is_empty() {
+        return;
+    }
+    members.sort_by(|a, b| a.1.y.total_cmp(&b.1.y).then(a.0.cmp(&b.0)));
+
+    let bounds = world.world_bounds();
+    let lanes = members.len().min(MAX_LANES);
+    let lane_height = bounds.height / lanes as f32;
+    for (rank, (index, _)) in members.iter().enumerate() {
+        let lane = rank * lanes / members.len();
+        orders[*index].lane = Some(Lane {
+            top: bounds.y + lane_height * lane as f32,
+            bottom: bounds.y + lane_height * (lane + 1) as f32,
+        });
+    }
+
+    let flankers = members.len() / TANKS_PER_FLANKER;
+    if flankers == 0 || plan.outnumbered {
+        return;
+    }
+    let focus_y = plan.focus.map_or(bounds.y + bounds.height * 0.5, |index| {
+        snapshot[index].pos.y
+    });
+    members.sort_by(|a, b| {
+        let gap_a = (a.1.y - focus_y).abs();
+        let gap_b = (b.1.y - focus_y).abs();
+        gap_b.total_cmp(&gap_a).then(a.0.cmp(&b.0))
+    });
+    for (index, _) in members.iter().take(flankers) {
+        orders[*index].flank = true;
+    }
+}
+
+/// Picks defenders, those nearest the team's own flag, then escorts, those
+/// nearest a teammate carrying a flag. Everyone else attacks.
+fn assign_flag_roles(snapshot: &[TankSnapshot], flags: &[Flag], team: Team, orders: &mut [Orders]) {
+    let Some(own) = flags.iter().find(|flag| flag.team == team) else {
+        return;
+    };
+    let carrier = flags
+        .iter()
+        .filter_map(Flag::carrier)
+        .find(|&index| snapshot.get(index).is_some_and(|tank| tank.team == team));
+    let mut free: Vec<(usize, Vector2)> = living(snapshot, team)
+        .filter(|(index, _)| flags.iter().all(|flag| flag.carrier() != Some(*index)))
+        .map(|(index, tank)| (index, tank.pos))
+        .collect();
+
+    let defenders = (free.len() + TANKS_PER_DEFENDER / 2) / TANKS_PER_DEFENDER;
+    let closest_first = |target: Vector2, members: &mut Vec<(usize, Vector2)>| {
+        members.sort_by(|a, b| {
+            vec2_distance(a.1, target)
+                .total_cmp(&vec2_distance(b.1, target))
+                .then(a.0.cmp(&b.0))
+        });
+    };
+    closest_first(own.home, &mut free);
+    for (index, _) in free.drain(..defenders.min(free.len())) {
+        orders[index].role = FlagRole::Defend;
+    }
+
+    let Some(carrier) = carrier else {
+        return;
+    };
+    closest_first(snapshot[carrier].pos, &mut free);
+    let escorts = (free.len() / 2).max(1).min(free.len());
+    for (index, _) in free.drain(..escorts) {
+        orders[index].role = FlagRole::Escort;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entities::FlagState;
+    use crate::math::{Rectangle, vec2};
+    use crate::world::SpawnZone;
+
+    use super::FlagRole::{Attack, Defend, Escort};
+    use super::*;
+
+    fn tile(x: f32, y: f32) -> Vector2 {
+        vec2(x * TILE_SIZE, y * TILE_SIZE)
+    }
+
+    /// An open 20x10 tile map, Crimson spawning on the left and Azure on
+    /// the right.
+    fn world() -> World {
+        let zone = |x, team| SpawnZone {
+            rect: Rectangle {
+                x: x * TILE_SIZE,
+                y: 3.0 * TILE_SIZE,
+                width: 2.0 * TILE_SIZE,
+                height: 4.0 * TILE_SIZE,
+            },
+            team,
+        };
+        World::open(
+            20,
+            10,
+            Vec::new(),
+            vec![zone(0.0, Team::Red), zone(18.0, Team::Blue)],
+        )
+    }
+
+    fn tank(team: Team, x: f32, y: f32, health: f32) -> TankSnapshot {
+        TankSnapshot {
+            team,
+            pos: tile(x, y),
+            vel: vec2(0.0, 0.0),
+            health,
+            alive: true,
+        }
+    }
+
+    fn flag(team: Team, home: Vector2, state: FlagState) -> Flag {
+        Flag {
+            team,
+            home,
+            pos: home,
+            state,
+        }
+    }
+
+    fn orders(world: &World, snapshot: &[TankSnapshot], team: Team) -> Vec<Orders> {
+        let plan = plan_team(world, snapshot, &[], team);
+        let mut orders = vec![Orders::default(); snapshot.len()];
+        assign_orders(world, snapshot, team, &plan, &mut orders);
+        orders
+    }
+
+    #[test]
+    fn the_team_focuses_the_weakest_enemy_in_sight() {
+        let world = world();
+        let mut snapshot = vec![
+            tank(Team::Red, 4.0, 5.0, 1.0),
+            tank(Team::Blue, 12.0, 2.0, 1.0),
+            tank(Team::Blue, 12.0, 5.0, 0.3),
+            tank(Team::Blue, 12.0, 8.0, 0.6),
+        ];
+        assert_eq!(plan_team(&world, &snapshot, &[], Team::Red).focus, Some(2));
+
+        snapshot[2].alive = false;
+        assert_eq!(plan_team(&world, &snapshot, &[], Team::Red).focus, Some(3));
+
+        // Whoever runs off with the team's flag comes first.
+        let flags = [flag(Team::Red, tile(2.0, 5.0), FlagState::Carried(1))];
+        assert_eq!(
+            plan_team(&world, &snapshot, &flags, Team::Red).focus,
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn the_team_spreads_over_lanes_and_flanks_from_the_outside() {
+        let world = world();
+        let heights = [1.0, 9.0, 4.0, 2.0, 8.0, 6.0];
+        let snapshot: Vec<_> = heights
+            .iter()
+            .map(|&y| tank(Team::Red, 4.0, y, 1.0))
+            .collect();
+        let orders = orders(&world, &snapshot, Team::Red);
+
+        let lane_height = 10.0 * TILE_SIZE / 3.0;
+        let lane = |index: usize| (orders[index].lane.unwrap().top / lane_height).round();
+        let lanes: Vec<f32> = (0..snapshot.len()).map(lane).collect();
+        assert_eq!(lanes, [0.0, 2.0, 1.0, 0.0, 2.0, 1.0]);
+        for (order, tank) in orders.iter().zip(&snapshot) {
+            assert!(order.lane.unwrap().contains(tank.pos.y));
+        }
+
+        // One flanker per three tanks, the two furthest from the middle of
+        // the map, where the fight is when there is no focus.
+        let flankers: Vec<usize> = (0..orders.len()).filter(|&i| orders[i].flank).collect();
+        assert_eq!(flankers, [0, 1]);
+    }
+
+    #[test]
+    fn flankers_go_wide_of_the_focus_target() {
+        let world = world();
+        let snapshot = vec![
+            tank(Team::Red, 4.0, 2.0, 1.0),
+            tank(Team::Red, 4.0, 5.0, 1.0),
+            tank(Team::Red, 4.0, 8.5, 1.0),
+            tank(Team::Blue, 12.0, 1.0, 0.2),
+        ];
+        let orders = orders(&world, &snapshot, Team::Red);
+        let flankers: Vec<usize> = (0..orders.len()).filter(|&i| orders[i].flank).collect();
+        assert_eq!(flankers, [2]);
+    }
+
+    #[test]
+    fn an_outnumbered_team_rallies_and_holds_back_its_flankers() {
+        let world = world();
+        let mut snapshot: Vec<_> = (0..3)
+            .map(|i| tank(Team::Red, 4.0, 2.0 + 3.0 * i as f32, 1.0))
+            .collect();
+        snapshot.extend((0..5).map(|i| tank(Team::Blue, 14.0, 1.0 + 2.0 * i as f32, 1.0)));
+
+        let plan = plan_team(&world, &snapshot, &[], Team::Red);
+        assert!(plan.outnumbered);
+        // Just in front of the spawn zone, towards the middle of the map.
+        assert_eq!(plan.rally, tile(3.0, 5.0));
+        assert!(!plan_team(&world, &snapshot, &[], Team::Blue).outnumbered);
+        assert!(
+            orders(&world, &snapshot, Team::Red)
+                .iter()
+                .all(|order| !order.flank)
+        );
+
+        // 3 against 4 is still a fair fight.
+        snapshot[7].alive = false;
+        assert!(!plan_team(&world, &snapshot, &[], Team::Red).outnumbered);
+        assert!(
+            orders(&world, &snapshot, Team::Red)
+                .iter()
+                .any(|order| order.flank)
+        );
+    }
+
+    #[test]
+    fn flag_roles_split_defenders_escorts_and_attackers() {
+        let home = tile(2.0, 5.0);
+        let snapshot = vec![
+            tank(Team::Red, 12.0, 5.0, 1.0),
+            tank(Team::Red, 3.0, 5.0, 1.0),
+            tank(Team::Red, 11.0, 5.0, 1.0),
+            tank(Team::Red, 16.0, 2.0, 1.0),
+            tank(Team::Red, 16.0, 8.0, 1.0),
+            tank(Team::Blue, 17.0, 5.0, 1.0),
+        ];
+        let mut flags = [
+            flag(Team::Red, home, FlagState::Home),
+            flag(Team::Blue, tile(18.0, 5.0), FlagState::Home),
+        ];
+        let roles = |flags: &[Flag]| {
+            let mut orders = vec![Orders::default(); snapshot.len()];
+            assign_flag_roles(&snapshot, flags, Team::Red, &mut orders);
+            orders.iter().map(|order| order.role).collect::<Vec<_>>()
+        };
+
+        // Five free tanks: the one nearest home defends, the rest attack.
+        assert_eq!(
+            roles(&flags),
+            [Attack, Defend, Attack, Attack, Attack, Attack]
+        );
+
+        // With a teammate bringing a flag home, the nearest free tank
+        // escorts it.
+        flags[1].state = FlagState::Carried(0);
+        assert_eq!(
+            roles(&flags),
+            [Attack, Defend, Escort, Attack, Attack, Attack]
+        );
+
+        // Outside capture the flag everyone attacks.
+        assert!(roles(&[]).iter().all(|role| *role == Attack));
+    }
+}
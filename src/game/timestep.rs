@@ -0,0 +1,46 @@
+use crate::config::{MAX_FRAME_TIME, TICK_DT};
+
+use super::input::TickInput;
+
+/// Turns variable frame times into whole simulation ticks of `TICK_DT`, so a
+/// seed plus an input stream always replays the same round.
+pub struct FixedTimestep {
+    accumulator: f32,
+    pending_start: bool,
+}
+
+impl FixedTimestep {
+    pub fn new() -> Self {
+        Self {
+            accumulator: 0.0,
+            pending_start: false,
+        }
+    }
+
+    /// Runs `tick` once per whole step covered by `frame_dt`. A start press is
+    /// held until the next tick actually runs and is delivered to that tick
+    /// only, so it is neither dropped nor repeated.
+    pub fn advance(&mut self, frame_dt: f32, input: TickInput, mut tick: impl FnMut(&TickInput)) {
+        self.accumulator += frame_dt.clamp(0.0, MAX_FRAME_TIME);
+        self.pending_start |= input.start_pressed;
+        while self.accumulator >= TICK_DT {
+            self.accumulator -= TICK_DT;
+            let step = TickInput {
+                start_pressed: std::mem::take(&mut self.pending_start),
+                ..input.clone()
+            };
+            tick(&step);
+        }
+    }
+
+    /// How far the renderer is between the previous and the current tick.
+    pub fn blend(&self) -> f32 {
+        (self.accumulator / TICK_DT).clamp(0.0, 1.0)
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new()
+    }
+}
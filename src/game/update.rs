@@ -1,23 +1,24 @@
-use raylib::prelude::RaylibHandle;
-
-use crate::config::{
-    BULLET_DAMAGE, BULLET_RADIUS, HEALTH_FLASH_TIME, RESPAWN_TIME, TANK_RADIUS, TRACK_LIFE,
-};
-use crate::entities::{Explosion, SmokeColor, Team};
-use crate::math::{point_in_bounds, vec2, vec2_add, vec2_distance, vec2_scale};
+use crate::config::{BULLET_RADIUS, TANK_RADIUS, TRACK_LIFE};
+use crate::entities::{Explosion, SmokeColor};
+use crate::math::{Vector2, point_in_bounds, vec2, vec2_add, vec2_distance, vec2_scale};
 
+use super::events::GameEvent;
+use super::input::TickInput;
 use super::{Game, ScreenState};
 
 impl Game {
-    pub fn update(&mut self, dt: f32, rl: &RaylibHandle) {
+    pub fn update(&mut self, dt: f32, input: &TickInput) {
+        self.events.clear();
         match self.state {
             ScreenState::Title => {
-                if self.input_state.start_pressed(rl) {
+                if input.start_pressed {
                     self.reset_round();
                     self.state = ScreenState::Playing;
                 }
             }
+            ScreenState::Lobby => {}
             ScreenState::Playing => {
+                self.store_previous_state();
                 self.intro_timer = (self.intro_timer - dt).max(0.0);
                 self.update_powerups(dt);
                 if self.countdown_timer > 0.0 {
@@ -25,23 +26,20 @@ impl Game {
                     return;
                 }
 
-                self.round_timer -= dt;
-                if self.round_timer <= 0.0 {
-                    self.round_timer = 0.0;
+                self.round_timer = (self.round_timer - dt).max(0.0);
+                let rules = self.rules();
+                if self.round_timer <= 0.0 || rules.is_decided(self) {
                     self.state = ScreenState::RoundOver;
-                    self.last_winner = match self.team_kills[0].cmp(&self.team_kills[1]) {
-                        std::cmp::Ordering::Greater => Some(Team::Red),
-                        std::cmp::Ordering::Less => Some(Team::Blue),
-                        std::cmp::Ordering::Equal => None,
-                    };
+                    self.last_winner = self.leader();
                 }
-                self.update_tanks(dt, rl);
+                self.update_tanks(dt, input);
                 self.update_bullets(dt);
+                rules.update(self, dt);
                 self.update_tracks(dt);
                 self.update_explosions(dt);
             }
             ScreenState::RoundOver => {
-                if self.input_state.start_pressed(rl) {
+                if input.start_pressed {
                     self.reset_round();
                     self.state = ScreenState::Playing;
                 }
@@ -49,7 +47,17 @@ impl Game {
         }
     }
 
+    fn store_previous_state(&mut self) {
+        for tank in &mut self.tanks {
+            tank.store_previous();
+        }
+        for bullet in &mut self.bullets {
+            bullet.prev_pos = bullet.pos;
+        }
+    }
+
     fn update_bullets(&mut self, dt: f32) {
+        let config = &self.config;
         let mut survivors = Vec::with_capacity(self.bullets.len());
         for mut bullet in self.bullets.drain(..) {
             bullet.life -= dt;
@@ -88,16 +96,27 @@ impl Game {
                 continue;
             }
 
-            for tank in &mut self.tanks {
+            for (index, tank) in self.tanks.iter_mut().enumerate() {
                 if tank.alive && tank.team != bullet.team {
                     if vec2_distance(bullet.pos, tank.pos) < TANK_RADIUS + BULLET_RADIUS {
                         if tank.invincible_timer <= 0.0 {
-                            tank.health = (tank.health - BULLET_DAMAGE).max(0.0);
-                            tank.health_flash = HEALTH_FLASH_TIME;
+                            tank.health = (tank.health - config.bullet.damage).max(0.0);
+                            tank.health_flash = config.tank.health_flash_time;
+                            self.events.push(GameEvent::Damage {
+                                victim: index,
+                                attacker: bullet.team,
+                                shooter: bullet.owner,
+                                amount: config.bullet.damage,
+                            });
                             if tank.health <= 0.0 {
                                 tank.alive = false;
-                                tank.respawn_timer = RESPAWN_TIME;
+                                tank.respawn_timer = config.round.respawn_time;
                                 self.team_kills[bullet.team.index()] += 1;
+                                self.events.push(GameEvent::Kill {
+                                    victim: index,
+                                    killer: bullet.team,
+                                    shooter: bullet.owner,
+                                });
                                 spawn_explosion_pair(
                                     &mut self.explosions,
                                     tank.pos,
@@ -138,11 +157,7 @@ impl Game {
     }
 }
 
-fn spawn_explosion(
-    explosions: &mut Vec<Explosion>,
-    pos: raylib::prelude::Vector2,
-    color: SmokeColor,
-) {
+fn spawn_explosion(explosions: &mut Vec<Explosion>, pos: Vector2, color: SmokeColor) {
     explosions.push(Explosion {
         pos,
         color,
@@ -152,10 +167,10 @@ fn spawn_explosion(
 
 fn spawn_explosion_pair(
     explosions: &mut Vec<Explosion>,
-    pos: raylib::prelude::Vector2,
+    pos: Vector2,
     primary: SmokeColor,
     secondary: SmokeColor,
-    offset: raylib::prelude::Vector2,
+    offset: Vector2,
 ) {
     spawn_explosion(explosions, pos, primary);
     spawn_explosion(explosions, vec2_add(pos, offset), secondary);
@@ -0,0 +1,12 @@
+#[cfg(feature = "graphics")]
+pub mod assets;
+pub mod bot;
+pub mod config;
+pub mod entities;
+pub mod env;
+pub mod game;
+pub mod math;
+pub mod net;
+pub mod replay;
+pub mod sim;
+pub mod world;
@@ -0,0 +1,452 @@
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+
+use raylib::prelude::{KeyboardKey, RaylibHandle};
+use tanks::assets::Assets;
+use tanks::config::{GameConfig, MAX_TANKS_PER_TEAM};
+use tanks::entities::Team;
+use tanks::game::{CameraView, Game, LobbyView, PlayerInput};
+use tanks::net::{
+    Client, Discovery, LinkConditions, LobbyGuest, LobbyHost, LobbySettings, LobbyStatus, Server,
+};
+
+use crate::cli::LobbyArgs;
+use crate::{draw_client, open_window, system_seed};
+
+/// Round time steps the host can pick, in seconds.
+const ROUND_TIME_STEP: f32 = 30.0;
+const MIN_ROUND_TIME: f32 = 30.0;
+const MAX_ROUND_TIME: f32 = 600.0;
+
+/// Where the player is between starting `tanks lobby` and playing.
+enum Stage {
+    Browse {
+        discovery: Discovery,
+        selected: usize,
+    },
+    Host {
+        lobby: LobbyHost,
+        selected: usize,
+    },
+    Guest(LobbyGuest),
+    Match {
+        client: Box<Client>,
+        /// Present when this player is the host.
+        hosted: Option<HostedMatch>,
+    },
+}
+
+/// The host's side of a running match: the lobby, still pointing late
+/// arrivals at the game, and the server on its own thread.
+struct HostedMatch {
+    lobby: LobbyHost,
+    stop: Arc<AtomicBool>,
+    server: JoinHandle<io::Result<()>>,
+}
+
+/// What every stage needs to know about the player.
+struct Player {
+    name: String,
+    port: u16,
+    base: GameConfig,
+    conditions: LinkConditions,
+}
+
+pub fn run_lobby(args: LobbyArgs) -> io::Result<()> {
+    let base = args.game.config()?;
+    let player = Player {
+        name: args.name.clone(),
+        port: args.port,
+        conditions: args.link.conditions(),
+        base,
+    };
+    let mut stage = match args.join_address() {
+        Some(address) => Stage::Guest(LobbyGuest::join(&address, &player.name, player.conditions)?),
+        None if args.host => {
+            let seed = args.game.seed.unwrap_or_else(system_seed);
+            host(&player, default_settings(&player, seed))?
+        }
+        None => browse(&player)?,
+    };
+
+    let (mut rl, thread) = open_window(args.window);
+    let assets = Assets::load(&mut rl, &thread);
+    let mut screen = Game::new(0);
+    while !rl.window_should_close() {
+        let screen_width = rl.get_screen_width();
+        let screen_height = rl.get_screen_height();
+        stage = stage.update(&rl, &player)?;
+
+        let mut d = rl.begin_drawing(&thread);
+        match &stage {
+            Stage::Match { client, .. } => draw_client(
+                &mut d,
+                client,
+                &assets,
+                screen_width,
+                screen_height,
+                CameraView::Player,
+            ),
+            _ => {
+                screen.show_lobby(stage.view());
+                screen.draw(
+                    &mut d,
+                    &assets,
+                    screen_width,
+                    screen_height,
+                    1.0,
+                    CameraView::Player,
+                );
+            }
+        }
+    }
+    stage.leave()
+}
+
+fn browse(player: &Player) -> io::Result<Stage> {
+    Ok(Stage::Browse {
+        discovery: Discovery::new(player.port)?,
+        selected: 0,
+    })
+}
+
+/// A full game against the AI on a fresh map, until the host says
+/// otherwise.
+fn default_settings(player: &Player, map_seed: u64) -> LobbySettings {
+    LobbySettings {
+        map_seed,
+        round_time: player.base.round.time,
+        bots: player.base.round.tanks_per_team * 2 - 1,
+        teams: 2,
+    }
+}
+
+fn host(player: &Player, settings: LobbySettings) -> io::Result<Stage> {
+    let lobby = LobbyHost::bind(
+        ("0.0.0.0", player.port),
+        &player.name,
+        settings,
+        player.conditions,
+    )?;
+    Ok(Stage::Host { lobby, selected: 0 })
+}
+
+impl Stage {
+    /// Handles this frame's keys and network traffic, returning the stage
+    /// to carry on with.
+    fn update(self, rl: &RaylibHandle, player: &Player) -> io::Result<Stage> {
+        let frame_dt = rl.get_frame_time();
+        let pressed = |key| rl.is_key_pressed(key);
+        match self {
+            Stage::Browse {
+                mut discovery,
+                selected,
+            } => {
+                discovery.update(frame_dt)?;
+                let found: Vec<_> = discovery.lobbies().cloned().collect();
+                let selected = menu_step(rl, selected, found.len() + 1);
+                if !pressed(KeyboardKey::KEY_ENTER) {
+                    return Ok(Stage::Browse {
+                        discovery,
+                        selected,
+                    });
+                }
+                match found.get(selected) {
+                    Some(lobby) => {
+                        let guest = LobbyGuest::join(lobby.addr, &player.name, player.conditions)?;
+                        Ok(Stage::Guest(guest))
+                    }
+                    None => host(player, default_settings(player, system_seed())),
+                }
+            }
+            Stage::Host {
+                mut lobby,
+                selected,
+            } => {
+                lobby.update(frame_dt)?;
+                let selected = menu_step(rl, selected, 4);
+                let mut settings = lobby.settings();
+                let step = i32::from(pressed(KeyboardKey::KEY_RIGHT))
+                    - i32::from(pressed(KeyboardKey::KEY_LEFT));
+                match selected {
+                    0 => settings.map_seed = settings.map_seed.wrapping_add_signed(step.into()),
+                    1 => {
+                        settings.round_time = (settings.round_time + step as f32 * ROUND_TIME_STEP)
+                            .clamp(MIN_ROUND_TIME, MAX_ROUND_TIME)
+                    }
+                    2 => {
+                        settings.teams = settings
+                            .teams
+                            .saturating_add_signed(step as isize)
+                            .clamp(2, player.base.map.max_teams())
+                    }
+                    _ => {
+                        let humans: usize = lobby.humans().iter().sum();
+                        let most = MAX_TANKS_PER_TEAM * settings.teams - humans;
+                        settings.bots =
+                            settings.bots.saturating_add_signed(step as isize).min(most);
+                    }
+                }
+                if pressed(KeyboardKey::KEY_R) {
+                    settings.map_seed = system_seed();
+                }
+                lobby.set_settings(settings);
+
+                let me = lobby.players().remove(0);
+                let (mut team, mut ready) = (me.team, me.ready);
+                if pressed(KeyboardKey::KEY_TAB) {
+                    team = next_team(team, settings.playing_teams());
+                }
+                if pressed(KeyboardKey::KEY_ENTER) {
+                    ready = !ready;
+                }
+                lobby.choose(team, ready);
+
+                if pressed(KeyboardKey::KEY_BACKSPACE) {
+                    lobby.close();
+                    browse(player)
+                } else if pressed(KeyboardKey::KEY_SPACE) && lobby.all_ready() {
+                    start_match(lobby, player, team)
+                } else {
+                    Ok(Stage::Host { lobby, selected })
+                }
+            }
+            Stage::Guest(mut lobby) => {
+                lobby.update(frame_dt)?;
+                if pressed(KeyboardKey::KEY_BACKSPACE) {
+                    lobby.leave();
+                    return browse(player);
+                }
+                if let LobbyStatus::Started(server) = lobby.status() {
+                    let client =
+                        Client::connect(*server, &player.name, lobby.team(), player.conditions)?;
+                    return Ok(Stage::Match {
+                        client: Box::new(client),
+                        hosted: None,
+                    });
+                }
+                if let (Some(team), Some(settings)) = (lobby.team(), lobby.settings()) {
+                    let team = if pressed(KeyboardKey::KEY_TAB) {
+                        next_team(team, settings.playing_teams())
+                    } else {
+                        team
+                    };
+                    let ready = lobby.is_ready() != pressed(KeyboardKey::KEY_ENTER);
+                    if Some(team) != lobby.team() || ready != lobby.is_ready() {
+                        lobby.choose(team, ready);
+                    }
+                }
+                Ok(Stage::Guest(lobby))
+            }
+            Stage::Match { mut client, hosted } => {
+                let hosted = match hosted {
+                    Some(mut hosted) => {
+                        hosted.lobby.update(frame_dt)?;
+                        // The server only stops by itself on an error.
+                        if hosted.server.is_finished() {
+                            return match hosted.server.join() {
+                                Ok(Err(err)) => Err(err),
+                                _ => Err(server_crashed()),
+                            };
+                        }
+                        Some(hosted)
+                    }
+                    None => None,
+                };
+                let screen_width = rl.get_screen_width();
+                let screen_height = rl.get_screen_height();
+                let input = client
+                    .game_mut()
+                    .sample_input(rl, screen_width, screen_height)
+                    .tanks
+                    .first()
+                    .map_or(PlayerInput::default(), |tank| tank.input);
+                client.update(frame_dt, input)?;
+                Ok(Stage::Match { client, hosted })
+            }
+        }
+    }
+
+    /// The lobby screen for this stage.
+    fn view(&self) -> LobbyView {
+        match self {
+            Stage::Browse {
+                discovery,
+                selected,
+            } => {
+                let mut menu: Vec<String> = discovery
+                    .lobbies()
+                    .map(|lobby| {
+                        let players = match lobby.players {
+                            1 => "1 player".to_string(),
+                            count => format!("{count} players"),
+                        };
+                        let started = if lobby.started { ", in progress" } else { "" };
+                        format!(
+                            "{}'s game at {} ({players}{started})",
+                            lobby.host, lobby.addr
+                        )
+                    })
+                    .collect();
+                menu.push("Host a new game".to_string());
+                LobbyView {
+                    heading: "Find a game".to_string(),
+                    selected: Some((*selected).min(menu.len() - 1)),
+                    menu,
+                    footer: "UP/DOWN choose   ENTER join or host   ESC quit".to_string(),
+                    ..LobbyView::default()
+                }
+            }
+            Stage::Host { lobby, selected } => {
+                let port = lobby.local_addr().map_or(0, |addr| addr.port());
+                let footer = if lobby.all_ready() {
+                    "Everyone is ready: SPACE to start"
+                } else {
+                    "TAB switch team   ENTER ready   LEFT/RIGHT change   R random map   BACKSPACE leave"
+                };
+                LobbyView {
+                    heading: format!("Your lobby on port {port}"),
+                    menu: settings_menu(&lobby.settings(), &lobby.humans()),
+                    selected: Some(*selected),
+                    players: lobby.players(),
+                    you: Some(0),
+                    footer: footer.to_string(),
+                }
+            }
+            Stage::Guest(lobby) => {
+                let players = lobby.players();
+                let (heading, footer) = match lobby.status() {
+                    LobbyStatus::Joining => (
+                        format!("Joining {}...", lobby.host()),
+                        "BACKSPACE back".to_string(),
+                    ),
+                    LobbyStatus::Closed(reason) => (
+                        format!("Lobby closed: {reason}"),
+                        "BACKSPACE back".to_string(),
+                    ),
+                    LobbyStatus::Waiting | LobbyStatus::Started(_) => (
+                        match players.first() {
+                            Some(host) => format!("{}'s lobby", host.name),
+                            None => "Lobby".to_string(),
+                        },
+                        "TAB switch team   ENTER ready   BACKSPACE leave".to_string(),
+                    ),
+                };
+                let humans = Team::ALL
+                    .map(|team| players.iter().filter(|player| player.team == team).count());
+                LobbyView {
+                    heading,
+                    menu: lobby
+                        .settings()
+                        .map_or(Vec::new(), |settings| settings_menu(&settings, &humans)),
+                    selected: None,
+                    players,
+                    you: lobby.you(),
+                    footer,
+                }
+            }
+            Stage::Match { .. } => LobbyView::default(),
+        }
+    }
+
+    /// Lets everyone else know we are going.
+    fn leave(self) -> io::Result<()> {
+        match self {
+            Stage::Browse { .. } => Ok(()),
+            Stage::Host { mut lobby, .. } => {
+                lobby.close();
+                Ok(())
+            }
+            Stage::Guest(mut lobby) => {
+                lobby.leave();
+                Ok(())
+            }
+            Stage::Match { mut client, hosted } => {
+                client.disconnect();
+                let Some(mut hosted) = hosted else {
+                    return Ok(());
+                };
+                hosted.lobby.close();
+                hosted.stop.store(true, Ordering::Relaxed);
+                hosted.server.join().map_err(|_| server_crashed())?
+            }
+        }
+    }
+}
+
+/// Starts the game server on a spare port, points the lobby at it and
+/// joins it like everyone else.
+fn start_match(mut lobby: LobbyHost, player: &Player, team: Team) -> io::Result<Stage> {
+    let settings = lobby.settings();
+    let config = lobby.config(&player.base);
+    let mut server = Server::bind("0.0.0.0:0", config, settings.map_seed, player.conditions)?;
+    let port = server.local_addr()?.port();
+    lobby.start(port)?;
+    let client = Client::connect(
+        ("127.0.0.1", port),
+        &player.name,
+        Some(team),
+        player.conditions,
+    )?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopping = Arc::clone(&stop);
+    let server = thread::spawn(move || {
+        server.run(|_, events| {
+            for event in events {
+                println!("{event}");
+            }
+            !stopping.load(Ordering::Relaxed)
+        })
+    });
+    Ok(Stage::Match {
+        client: Box::new(client),
+        hosted: Some(HostedMatch {
+            lobby,
+            stop,
+            server,
+        }),
+    })
+}
+
+fn server_crashed() -> io::Error {
+    io::Error::other("the game server crashed")
+}
+
+/// The host's settings as menu lines, in the order they are edited.
+fn settings_menu(settings: &LobbySettings, humans: &[usize]) -> Vec<String> {
+    let team_size = settings
+        .config(&GameConfig::default(), humans)
+        .round
+        .tanks_per_team;
+    let time = settings.round_time as u32;
+    vec![
+        format!("Map seed: {}", settings.map_seed),
+        format!("Round time: {}:{:02}", time / 60, time % 60),
+        format!("Teams: {}", settings.playing_teams().len()),
+        format!("Bots: {} ({team_size} tanks a side)", settings.bots),
+    ]
+}
+
+/// The team after `team` among those playing, wrapping around.
+fn next_team(team: Team, playing: &[Team]) -> Team {
+    let next = playing
+        .iter()
+        .position(|&other| other == team)
+        .map_or(0, |at| at + 1);
+    playing[next % playing.len()]
+}
+
+/// Moves a menu selection with the arrow keys.
+fn menu_step(rl: &RaylibHandle, selected: usize, len: usize) -> usize {
+    let selected = selected.min(len.saturating_sub(1));
+    if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+        selected.saturating_sub(1)
+    } else if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+        (selected + 1).min(len.saturating_sub(1))
+    } else {
+        selected
+    }
+}
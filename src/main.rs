@@ -1,67 +1,384 @@
-mod assets;
-mod config;
-mod entities;
-mod game;
-mod math;
-mod world;
+mod cli;
+mod duel;
+mod lobby;
 
+use std::io;
+use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use assets::Assets;
-use config::{WINDOW_HEIGHT, WINDOW_WIDTH};
-use game::Game;
+use clap::Parser;
+use raylib::prelude::{KeyboardKey, RaylibDraw, RaylibHandle, RaylibThread};
+use tanks::assets::Assets;
+use tanks::bot::{BotProcess, Observation};
+use tanks::config::{ConfigWatcher, TICK_DT, TICK_RATE};
+use tanks::entities::Team;
+use tanks::game::{
+    CameraView, FixedTimestep, Game, PlayerInput, SAVE_EXTENSION, Spectator, TankInput, TickInput,
+    validate_players,
+};
+use tanks::net::{Client, ClientStatus, Server};
+use tanks::replay::{Replay, ReplayViewer};
+use tanks::sim::{BatchReport, simulate_batch};
 
-fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    let debug_frame = args.iter().any(|arg| arg == "--render-frame");
-    let seed_override = parse_seed(&args);
+use cli::{
+    BotArgs, Cli, Command, ConnectArgs, MapFormat, MapgenArgs, PlayArgs, RenderArgs, ReplayArgs,
+    ReportFormat, ServeArgs, SimulateArgs, WindowArgs,
+};
 
-    let (mut rl, thread) = raylib::init()
-        .size(WINDOW_WIDTH, WINDOW_HEIGHT)
-        .title("Tanks: Dominion")
-        .resizable()
-        .build();
+fn main() {
+    let cli = Cli::parse();
+    let result = match cli.command.unwrap_or(Command::Play(cli.play)) {
+        Command::Play(args) => run_play(args),
+        Command::Simulate(args) => run_simulate(args),
+        Command::Bot(args) => run_bot(args),
+        Command::Replay(args) => run_replay(args),
+        Command::Render(args) => run_render(args),
+        Command::Mapgen(args) => run_mapgen(args),
+        Command::Serve(args) => run_serve(args),
+        Command::Connect(args) => run_connect(args),
+        Command::Duel(args) => duel::run_duel(args),
+        Command::Lobby(args) => lobby::run_lobby(args),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
 
-    rl.set_target_fps(60);
+fn run_play(args: PlayArgs) -> io::Result<()> {
+    let config = args.game.config()?;
+    let seed = args.game.seed.unwrap_or_else(system_seed);
+    let mut game = match &args.load {
+        Some(path) => Game::load(path).map_err(|err| with_path(err, path))?,
+        None => Game::with_config(seed, config),
+    };
+    if !args.players.is_empty() {
+        validate_players(&args.players, &game.config().round)?;
+        game.set_local_players(args.players.clone());
+    }
+    if args.spectate {
+        game.set_ai_only(true);
+    }
+    let mut spectator = args.spectate.then(Spectator::new);
 
-    let seed = seed_override.unwrap_or_else(system_seed);
+    let (mut rl, thread) = open_window(args.window);
     let assets = Assets::load(&mut rl, &thread);
-    let mut game = Game::new(seed);
 
-    if debug_frame {
-        game.update(1.0 / 60.0, &rl);
+    let quicksave_path = format!("quicksave.{SAVE_EXTENSION}");
+    let mut recording = args.record.as_ref().map(|_| {
+        // Spectated games start from their state so playback is AI only too.
+        if args.load.is_some() || args.spectate {
+            Replay::from_state(seed, &game)
+        } else {
+            Replay::new(seed, game.config(), game.local_players())
+        }
+    });
+    let mut watcher = args.game.config_path().map(ConfigWatcher::new);
+    let mut timestep = FixedTimestep::new();
+    while !rl.window_should_close() {
+        if let Some(watcher) = &mut watcher {
+            match watcher
+                .poll()
+                .map(|loaded| args.game.apply_overrides(loaded?))
+            {
+                Some(Ok(config)) => {
+                    eprintln!("reloaded {}", watcher.path().display());
+                    if let Some(recording) = &mut recording {
+                        recording.record_config(&config);
+                    }
+                    game.set_config(config);
+                }
+                Some(Err(err)) => eprintln!("keeping previous config: {err}"),
+                None => {}
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F5) {
+            game.save(&quicksave_path)
+                .unwrap_or_else(|err| eprintln!("failed to save {quicksave_path}: {err}"));
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_F9) {
+            match Game::load(&quicksave_path) {
+                Ok(loaded) => {
+                    game = loaded;
+                    timestep = FixedTimestep::new();
+                    if let Some(recording) = &mut recording {
+                        recording.record_state(&game);
+                    }
+                }
+                Err(err) => eprintln!("failed to load {quicksave_path}: {err}"),
+            }
+        }
+
+        let screen_width = rl.get_screen_width();
+        let screen_height = rl.get_screen_height();
+        let input = game.sample_input(&rl, screen_width, screen_height);
+        if let Some(spectator) = &mut spectator {
+            spectator.handle_keys(&rl, &game);
+        }
+        timestep.advance(rl.get_frame_time(), input, |tick| {
+            if let Some(recording) = &mut recording {
+                recording.record(tick);
+            }
+            game.update(TICK_DT, tick);
+        });
+        let view = spectator.map_or(CameraView::Player, |spectator| spectator.view(&game));
+        let mut d = rl.begin_drawing(&thread);
+        game.draw(
+            &mut d,
+            &assets,
+            screen_width,
+            screen_height,
+            timestep.blend(),
+            view,
+        );
+    }
+
+    match (&args.record, recording) {
+        (Some(path), Some(recording)) => recording.save(path).map_err(|err| with_path(err, path)),
+        _ => Ok(()),
+    }
+}
+
+fn run_simulate(args: SimulateArgs) -> io::Result<()> {
+    let config = args.game.config()?;
+    let seed = args.game.seed.unwrap_or_else(system_seed);
+    let report = BatchReport::new(simulate_batch(seed, args.rounds, &config));
+    let text = match args.format {
+        ReportFormat::Text => format!("seed {seed}\n{}", report.to_text()),
+        ReportFormat::Csv => report.to_csv(),
+        ReportFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+    };
+    match &args.output {
+        Some(path) => std::fs::write(path, text).map_err(|err| with_path(err, path)),
+        None => {
+            print!("{text}");
+            Ok(())
+        }
+    }
+}
+
+fn run_bot(args: BotArgs) -> io::Result<()> {
+    let config = args.game.config()?;
+    let seed = args.game.seed.unwrap_or_else(system_seed);
+    let mut game = Game::with_config(seed, config);
+    game.set_ai_only(true);
+    game.start_round();
+    let tank_count = game.tanks().len();
+    if let Some(spec) = args.bots.iter().find(|spec| spec.tank >= tank_count) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("no tank {} (tanks are numbered 0..{tank_count})", spec.tank),
+        ));
+    }
+    let mut bots = args
+        .bots
+        .iter()
+        .map(|spec| Ok((spec.tank, BotProcess::spawn(&spec.command)?)))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let mut recording = args
+        .record
+        .as_ref()
+        .map(|_| Replay::from_state(seed, &game));
+    let mut round = 1;
+    let mut tick = 0;
+    loop {
+        let mut input = TickInput::default();
+        if game.is_round_over() {
+            let score = Team::scoreline(game.scores());
+            let result = game
+                .last_winner()
+                .map_or("draw".to_string(), |team| format!("{} wins", team.name()));
+            println!("round {round}: {score}, {result}");
+            if round == args.rounds {
+                break;
+            }
+            round += 1;
+            input.start_pressed = true;
+        } else {
+            for (tank, bot) in &mut bots {
+                let action = bot.exchange(&Observation::new(&game, *tank, tick))?;
+                input.tanks.push(TankInput {
+                    tank: *tank,
+                    input: action.to_input(),
+                });
+            }
+        }
+        if let Some(recording) = &mut recording {
+            recording.record(&input);
+        }
+        game.update(TICK_DT, &input);
+        tick += 1;
+    }
+
+    match (&args.record, recording) {
+        (Some(path), Some(recording)) => recording.save(path).map_err(|err| with_path(err, path)),
+        _ => Ok(()),
+    }
+}
+
+fn run_replay(args: ReplayArgs) -> io::Result<()> {
+    let replay = Replay::load(&args.file).map_err(|err| with_path(err, &args.file))?;
+    if !replay.matches_game_version() {
+        eprintln!(
+            "warning: replay was recorded with version {}, playback may diverge",
+            replay.game_version
+        );
+    }
+
+    let (mut rl, thread) = open_window(args.window);
+    let assets = Assets::load(&mut rl, &thread);
+    let mut viewer = ReplayViewer::new(&replay);
+    while !rl.window_should_close() {
         let screen_width = rl.get_screen_width();
         let screen_height = rl.get_screen_height();
-        {
-            let mut d = rl.begin_drawing(&thread);
-            game.draw(&mut d, &assets, screen_width, screen_height);
+        viewer.update(&rl, screen_width, screen_height);
+        let mut d = rl.begin_drawing(&thread);
+        viewer.draw(&mut d, &assets, screen_width, screen_height);
+    }
+    Ok(())
+}
+
+fn run_render(args: RenderArgs) -> io::Result<()> {
+    let config = args.game.config()?;
+    let seed = args.game.seed.unwrap_or_else(system_seed);
+    let mut game = Game::with_config(seed, config);
+    if args.time > 0.0 {
+        game.start_round();
+        let ticks = (args.time * TICK_RATE as f32).round() as u32;
+        for _ in 0..ticks {
+            game.update(TICK_DT, &TickInput::default());
         }
-        rl.take_screenshot(&thread, "debug_frame.png");
-        return;
     }
 
+    let (mut rl, thread) = open_window(args.window);
+    let assets = Assets::load(&mut rl, &thread);
+    let screen_width = rl.get_screen_width();
+    let screen_height = rl.get_screen_height();
+    {
+        let mut d = rl.begin_drawing(&thread);
+        game.draw(
+            &mut d,
+            &assets,
+            screen_width,
+            screen_height,
+            1.0,
+            CameraView::Player,
+        );
+    }
+    rl.take_screenshot(&thread, &args.output.to_string_lossy());
+    Ok(())
+}
+
+fn run_mapgen(args: MapgenArgs) -> io::Result<()> {
+    let config = args.game.config()?;
+    let seed = args.game.seed.unwrap_or_else(system_seed);
+    let game = Game::with_config(seed, config);
+    match args.format {
+        MapFormat::Ascii => {
+            println!("seed {seed}");
+            print!("{}", game.world().to_ascii());
+        }
+        MapFormat::Json => println!("{}", serde_json::to_string(game.world())?),
+    }
+    Ok(())
+}
+
+fn run_serve(args: ServeArgs) -> io::Result<()> {
+    let config = args.game.config()?;
+    let seed = args.game.seed.unwrap_or_else(system_seed);
+    let mut server = Server::bind(args.address(), config, seed, args.link.conditions())?;
+    println!("seed {seed}, listening on {}", server.local_addr()?);
+    server.run(|_, events| {
+        for event in events {
+            println!("{event}");
+        }
+        true
+    })
+}
+
+fn run_connect(args: ConnectArgs) -> io::Result<()> {
+    let address = args.address();
+    let team = args.team.map(|team| team.team());
+    let conditions = args.link.conditions();
+    let mut client = if args.spectate {
+        Client::spectate(&address, &args.name, conditions)?
+    } else {
+        Client::connect(&address, &args.name, team, conditions)?
+    };
+    let mut spectator = args.spectate.then(Spectator::new);
+
+    let (mut rl, thread) = open_window(args.window);
+    let assets = Assets::load(&mut rl, &thread);
     while !rl.window_should_close() {
-        let dt = rl.get_frame_time();
-        game.update(dt, &rl);
         let screen_width = rl.get_screen_width();
         let screen_height = rl.get_screen_height();
+        let input = client
+            .game_mut()
+            .sample_input(&rl, screen_width, screen_height)
+            .tanks
+            .first()
+            .map_or(PlayerInput::default(), |tank| tank.input);
+        if let Some(spectator) = &mut spectator {
+            spectator.handle_keys(&rl, client.game());
+        }
+        client.update(rl.get_frame_time(), input)?;
+
+        let view = spectator.map_or(CameraView::Player, |spectator| {
+            spectator.view(client.game())
+        });
         let mut d = rl.begin_drawing(&thread);
-        game.draw(&mut d, &assets, screen_width, screen_height);
+        draw_client(&mut d, &client, &assets, screen_width, screen_height, view);
     }
+    client.disconnect();
+    Ok(())
 }
 
-fn parse_seed(args: &[String]) -> Option<u64> {
-    let mut iter = args.iter();
-    while let Some(arg) = iter.next() {
-        if arg == "--seed" {
-            if let Some(value) = iter.next() {
-                if let Ok(parsed) = value.parse::<u64>() {
-                    return Some(parsed);
-                }
-            }
+/// The client's replica once it is playing, or a notice saying why not.
+fn draw_client<D: RaylibDraw>(
+    d: &mut D,
+    client: &Client,
+    assets: &Assets,
+    screen_width: i32,
+    screen_height: i32,
+    view: CameraView,
+) {
+    match client.status() {
+        ClientStatus::Playing => {
+            client
+                .game()
+                .draw(d, assets, screen_width, screen_height, client.blend(), view)
         }
+        ClientStatus::Connecting => Game::draw_notice(
+            d,
+            &format!("Connecting to {}...", client.server()),
+            screen_width,
+            screen_height,
+        ),
+        ClientStatus::Loading => {
+            Game::draw_notice(d, "Loading map...", screen_width, screen_height)
+        }
+        ClientStatus::Disconnected(reason) => Game::draw_notice(
+            d,
+            &format!("Disconnected: {reason}"),
+            screen_width,
+            screen_height,
+        ),
     }
-    None
+}
+
+fn open_window(window: WindowArgs) -> (RaylibHandle, RaylibThread) {
+    let (mut rl, thread) = raylib::init()
+        .size(window.width, window.height)
+        .title("Tanks: Dominion")
+        .resizable()
+        .build();
+    rl.set_target_fps(60);
+    (rl, thread)
+}
+
+fn with_path(err: io::Error, path: &Path) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {err}", path.display()))
 }
 
 fn system_seed() -> u64 {
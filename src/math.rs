@@ -1,7 +1,56 @@
-use rand::{Rng, rngs::SmallRng};
-use raylib::prelude::{Color, Rectangle, Vector2};
+use rand::Rng;
+#[cfg(feature = "graphics")]
+use raylib::prelude::Color;
 use std::f32::consts::PI;
 
+#[cfg(not(feature = "graphics"))]
+pub use self::geometry::{Rectangle, Vector2};
+#[cfg(feature = "graphics")]
+pub use raylib::prelude::{Rectangle, Vector2};
+
+/// Stand-ins for raylib's vector and rectangle when it is not built in.
+/// They have the same fields, so saves, replays and packets read the same
+/// either way.
+#[cfg(not(feature = "graphics"))]
+mod geometry {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+    pub struct Vector2 {
+        pub x: f32,
+        pub y: f32,
+    }
+
+    impl Vector2 {
+        pub const fn new(x: f32, y: f32) -> Self {
+            Self { x, y }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+    pub struct Rectangle {
+        pub x: f32,
+        pub y: f32,
+        pub width: f32,
+        pub height: f32,
+    }
+
+    impl Rectangle {
+        pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+            Self {
+                x,
+                y,
+                width,
+                height,
+            }
+        }
+    }
+}
+
+/// The simulation's random number generator. Unlike `SmallRng` its state can
+/// be serialized, which save files and replays rely on.
+pub type GameRng = rand_xoshiro::Xoshiro256PlusPlus;
+
 pub fn vec2(x: f32, y: f32) -> Vector2 {
     Vector2 { x, y }
 }
@@ -35,6 +84,49 @@ pub fn vec2_normalize(v: Vector2) -> Vector2 {
     }
 }
 
+pub fn vec2_lerp(a: Vector2, b: Vector2, t: f32) -> Vector2 {
+    vec2(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+/// Shortest distance from `point` to the segment `a`-`b`.
+pub fn distance_to_segment(point: Vector2, a: Vector2, b: Vector2) -> f32 {
+    let ab = vec2_sub(b, a);
+    let len_sq = ab.x * ab.x + ab.y * ab.y;
+    if len_sq <= f32::EPSILON {
+        return vec2_distance(point, a);
+    }
+    let ap = vec2_sub(point, a);
+    let t = ((ap.x * ab.x + ap.y * ab.y) / len_sq).clamp(0.0, 1.0);
+    vec2_distance(point, vec2_add(a, vec2_scale(ab, t)))
+}
+
+/// Where a projectile fired from `from` at `speed` meets a target at `pos`
+/// moving with constant `vel`, or `None` if it can never catch up.
+pub fn intercept_point(from: Vector2, pos: Vector2, vel: Vector2, speed: f32) -> Option<Vector2> {
+    let offset = vec2_sub(pos, from);
+    // |offset + vel * t| = speed * t, as a quadratic in t.
+    let a = vel.x * vel.x + vel.y * vel.y - speed * speed;
+    let b = 2.0 * (offset.x * vel.x + offset.y * vel.y);
+    let c = offset.x * offset.x + offset.y * offset.y;
+    let t = if a.abs() <= f32::EPSILON {
+        -c / b
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let (t1, t2) = ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a));
+        match (t1 > 0.0, t2 > 0.0) {
+            (true, true) => t1.min(t2),
+            (true, false) => t1,
+            (false, true) => t2,
+            (false, false) => return None,
+        }
+    };
+    (t.is_finite() && t >= 0.0).then(|| vec2_add(pos, vec2_scale(vel, t)))
+}
+
 pub fn vec2_from_angle(angle: f32) -> Vector2 {
     vec2(angle.cos(), angle.sin())
 }
@@ -69,7 +161,18 @@ pub fn rotate_towards(current: f32, target: f32, max_delta: f32) -> f32 {
     }
 }
 
-pub fn random_angle(rng: &mut SmallRng) -> f32 {
+pub fn lerp_angle(from: f32, to: f32, t: f32) -> f32 {
+    let mut diff = to - from;
+    while diff > PI {
+        diff -= PI * 2.0;
+    }
+    while diff < -PI {
+        diff += PI * 2.0;
+    }
+    from + diff * t
+}
+
+pub fn random_angle(rng: &mut GameRng) -> f32 {
     rng.random_range(0.0..(PI * 2.0))
 }
 
@@ -77,6 +180,7 @@ pub fn rad_to_deg(rad: f32) -> f32 {
     rad * 180.0 / PI
 }
 
+#[cfg(feature = "graphics")]
 pub fn with_alpha(color: Color, alpha: f32) -> Color {
     let clamped = alpha.clamp(0.0, 1.0);
     Color::new(color.r, color.g, color.b, (clamped * color.a as f32) as u8)
@@ -113,3 +217,48 @@ pub fn push_outside_rect(pos: Vector2, rect: Rectangle, margin: f32) -> Vector2
         vec2(pos.x, rect.y + rect.height + margin)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vector2, b: Vector2) -> bool {
+        vec2_distance(a, b) < 1e-2
+    }
+
+    #[test]
+    fn a_still_target_is_hit_where_it_stands() {
+        let target = vec2(120.0, -50.0);
+        let hit = intercept_point(vec2(0.0, 0.0), target, vec2(0.0, 0.0), 400.0);
+        assert!(hit.is_some_and(|hit| close(hit, target)), "{hit:?}");
+    }
+
+    #[test]
+    fn a_crossing_target_is_led() {
+        // Meets after 10 s at (300, 400), 500 away.
+        let hit = intercept_point(vec2(0.0, 0.0), vec2(300.0, 0.0), vec2(0.0, 40.0), 50.0);
+        assert!(
+            hit.is_some_and(|hit| close(hit, vec2(300.0, 400.0))),
+            "{hit:?}"
+        );
+    }
+
+    #[test]
+    fn a_faster_target_running_away_cannot_be_caught() {
+        let hit = intercept_point(vec2(0.0, 0.0), vec2(100.0, 0.0), vec2(50.0, 0.0), 20.0);
+        assert_eq!(hit, None);
+    }
+
+    #[test]
+    fn a_target_as_fast_as_the_shot_is_solved_linearly() {
+        // Coming straight in at the bullet's own speed, they meet halfway.
+        let hit = intercept_point(vec2(0.0, 0.0), vec2(100.0, 0.0), vec2(-10.0, 0.0), 10.0);
+        assert!(
+            hit.is_some_and(|hit| close(hit, vec2(50.0, 0.0))),
+            "{hit:?}"
+        );
+        // Running away at that speed it stays out of reach.
+        let hit = intercept_point(vec2(0.0, 0.0), vec2(100.0, 0.0), vec2(10.0, 0.0), 10.0);
+        assert_eq!(hit, None);
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::VecDeque;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+
+use crate::config::{MAX_FRAME_TIME, TICK_DT, TICK_RATE};
+use crate::entities::{Tank, Team};
+use crate::game::{Game, InputBinding, LocalPlayer, PlayerInput, Snapshot, SnapshotDelta};
+use crate::math::{lerp_angle, vec2_lerp, vec2_scale, vec2_sub};
+use crate::world::World;
+
+use super::link::{Link, LinkConditions};
+use super::protocol::{ClientMessage, PROTOCOL_VERSION, SequencedInput, ServerMessage};
+
+/// How far behind the newest snapshot remote entities are drawn, in ticks,
+/// so there is nearly always a later snapshot to blend towards.
+const INTERPOLATION_DELAY: f64 = 6.0;
+/// Snapshots kept for interpolation and as delta baselines.
+const SNAPSHOT_HISTORY: usize = 64;
+/// Unacknowledged inputs repeated in every packet to ride out loss.
+const INPUT_REDUNDANCY: usize = 4;
+/// Inputs kept for replay past this are forgotten.
+const MAX_PENDING_INPUTS: usize = 120;
+const HELLO_INTERVAL: f32 = 0.5;
+const SERVER_TIMEOUT: f32 = 5.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientStatus {
+    Connecting,
+    /// Seated, but the map for the current round has not arrived yet.
+    Loading,
+    Playing,
+    Disconnected(String),
+}
+
+/// A player connected to a [`Server`](super::Server). Keeps a replica
+/// [`Game`] for drawing: remote tanks and bullets are interpolated between
+/// snapshots, the local tank is predicted from unacknowledged inputs.
+pub struct Client {
+    link: Link,
+    server: SocketAddr,
+    name: String,
+    team: Option<Team>,
+    spectate: bool,
+    status: ClientStatus,
+    game: Game,
+    tank: Option<usize>,
+    accumulator: f32,
+    seq: u32,
+    pending: VecDeque<SequencedInput>,
+    snapshots: VecDeque<Snapshot>,
+    predicted: Option<Tank>,
+    world_round: Option<u32>,
+    world_chunks: (u32, Vec<Option<String>>),
+    render_tick: f64,
+    blend: f32,
+    silence: f32,
+    hello_timer: f32,
+}
+
+impl Client {
+    pub fn connect(
+        server: impl ToSocketAddrs,
+        name: &str,
+        team: Option<Team>,
+        conditions: LinkConditions,
+    ) -> io::Result<Self> {
+        let server = server.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "server address did not resolve",
+            )
+        })?;
+        let local = if server.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let mut game = Game::new(0);
+        game.set_local_players(Vec::new());
+        Ok(Self {
+            link: Link::bind(local, conditions)?,
+            server,
+            name: name.to_string(),
+            team,
+            spectate: false,
+            status: ClientStatus::Connecting,
+            game,
+            tank: None,
+            accumulator: 0.0,
+            seq: 0,
+            pending: VecDeque::new(),
+            snapshots: VecDeque::new(),
+            predicted: None,
+            world_round: None,
+            world_chunks: (0, Vec::new()),
+            render_tick: 0.0,
+            blend: 1.0,
+            silence: 0.0,
+            hello_timer: 0.0,
+        })
+    }
+
+    /// Joins as a spectator: the server sends snapshots of the whole match
+    /// but seats us in no tank.
+    pub fn spectate(
+        server: impl ToSocketAddrs,
+        name: &str,
+        conditions: LinkConditions,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            spectate: true,
+            ..Self::connect(server, name, None, conditions)?
+        })
+    }
+
+    pub fn status(&self) -> &ClientStatus {
+        &self.status
+    }
+
+    pub fn server(&self) -> SocketAddr {
+        self.server
+    }
+
+    /// The tank the server gave us.
+    pub fn tank(&self) -> Option<usize> {
+        self.tank
+    }
+
+    /// The replica to draw, already blended for this frame.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// The replica, for sampling input against its cameras.
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    /// Blend to draw the replica with.
+    pub fn blend(&self) -> f32 {
+        self.blend
+    }
+
+    /// Exchanges packets and advances the local clock by `frame_dt`,
+    /// sending `input` once per whole tick.
+    pub fn update(&mut self, frame_dt: f32, input: PlayerInput) -> io::Result<()> {
+        if matches!(self.status, ClientStatus::Disconnected(_)) {
+            return Ok(());
+        }
+        let frame_dt = frame_dt.clamp(0.0, MAX_FRAME_TIME);
+        self.receive()?;
+        if matches!(self.status, ClientStatus::Disconnected(_)) {
+            return Ok(());
+        }
+        self.silence += frame_dt;
+        if self.silence > SERVER_TIMEOUT {
+            self.status = ClientStatus::Disconnected(match self.status {
+                ClientStatus::Connecting => "no answer from the server".to_string(),
+                _ => "the server stopped responding".to_string(),
+            });
+            return Ok(());
+        }
+
+        if self.status == ClientStatus::Connecting {
+            self.hello_timer -= frame_dt;
+            if self.hello_timer <= 0.0 {
+                self.hello_timer = HELLO_INTERVAL;
+                let hello = ClientMessage::Hello {
+                    version: PROTOCOL_VERSION,
+                    name: self.name.clone(),
+                    team: self.team,
+                    spectate: self.spectate,
+                };
+                self.link.send(self.server, &hello)?;
+            }
+            return Ok(());
+        }
+
+        self.accumulator += frame_dt;
+        while self.accumulator >= TICK_DT {
+            self.accumulator -= TICK_DT;
+            self.send_input(input)?;
+        }
+        self.render_tick += frame_dt as f64 * TICK_RATE as f64;
+        self.present();
+        Ok(())
+    }
+
+    /// Tells the server we are leaving. Best effort: the server also times
+    /// out silent clients.
+    pub fn disconnect(&mut self) {
+        let _ = self.link.send(self.server, &ClientMessage::Bye);
+        let _ = self.link.flush();
+        self.status = ClientStatus::Disconnected("left the game".to_string());
+    }
+
+    fn receive(&mut self) -> io::Result<()> {
+        while let Some((from, message)) = self.link.recv::<ServerMessage>()? {
+            if from != self.server {
+                continue;
+            }
+            self.silence = 0.0;
+            match message {
+                ServerMessage::Welcome { tank, team, config } => {
+                    if self.status == ClientStatus::Connecting {
+                        self.status = ClientStatus::Loading;
+                    }
+                    self.tank = Some(tank);
+                    self.predicted = None;
+                    self.game.set_config(config);
+                    self.game.set_local_players(vec![LocalPlayer {
+                        team,
+                        binding: InputBinding::Auto,
+                    }]);
+                    self.game.seat_player(0, Some(tank));
+                }
+                ServerMessage::Spectating { config } => {
+                    if self.status == ClientStatus::Connecting {
+                        self.status = ClientStatus::Loading;
+                    }
+                    self.game.set_config(config);
+                }
+                ServerMessage::Reject { reason } => {
+                    self.status = ClientStatus::Disconnected(reason);
+                }
+                ServerMessage::Snapshot { last_input, delta } => {
+                    self.receive_snapshot(last_input, delta);
+                }
+                ServerMessage::WorldChunk {
+                    round,
+                    index,
+                    count,
+                    data,
+                } => self.receive_world_chunk(round, index, count, data),
+            }
+        }
+        Ok(())
+    }
+
+    fn receive_snapshot(&mut self, last_input: Option<u32>, delta: SnapshotDelta) {
+        let newest = self.snapshots.back().map(|snapshot| snapshot.tick());
+        // Late or repeated; a newer snapshot already covers it.
+        if newest.is_some_and(|newest| delta.tick <= newest) {
+            return;
+        }
+        let baseline = delta
+            .baseline
+            .and_then(|tick| self.snapshots.iter().find(|old| old.tick() == tick));
+        let Some(snapshot) = delta.apply(baseline) else {
+            return;
+        };
+
+        let elapsed = newest.map_or(0, |newest| delta.tick - newest) as f32 * TICK_DT;
+        self.game.merge_tracks(delta.tracks(), elapsed);
+        if let Some(seq) = last_input {
+            self.pending.retain(|pending| pending.seq > seq);
+        }
+        self.reconcile(&snapshot);
+
+        self.snapshots.push_back(snapshot);
+        while self.snapshots.len() > SNAPSHOT_HISTORY {
+            self.snapshots.pop_front();
+        }
+        if newest.is_none() {
+            self.render_tick = delta.tick as f64 - INTERPOLATION_DELAY;
+        }
+    }
+
+    fn receive_world_chunk(&mut self, round: u32, index: usize, count: usize, data: String) {
+        if self.world_round.is_some_and(|held| held >= round) || index >= count {
+            return;
+        }
+        if self.world_chunks.0 != round || self.world_chunks.1.len() != count {
+            self.world_chunks = (round, vec![None; count]);
+        }
+        self.world_chunks.1[index] = Some(data);
+        if self.world_chunks.1.iter().any(Option::is_none) {
+            return;
+        }
+
+        let json: String = self.world_chunks.1.drain(..).flatten().collect();
+        if let Ok(world) = serde_json::from_str::<World>(&json) {
+            self.game.set_world(world);
+            self.world_round = Some(round);
+            self.predicted = None;
+        }
+    }
+
+    fn send_input(&mut self, input: PlayerInput) -> io::Result<()> {
+        // Spectators still report what they hold, but have nothing to drive.
+        if !self.spectate {
+            self.seq = self.seq.wrapping_add(1);
+            self.pending.push_back(SequencedInput {
+                seq: self.seq,
+                input,
+            });
+        }
+        while self.pending.len() > MAX_PENDING_INPUTS {
+            self.pending.pop_front();
+        }
+        if let Some(tank) = &mut self.predicted {
+            self.game.predict_tank(tank, &input, TICK_DT);
+        }
+
+        let skip = self.pending.len().saturating_sub(INPUT_REDUNDANCY);
+        let message = ClientMessage::Input {
+            inputs: self.pending.iter().skip(skip).copied().collect(),
+            ack: self.snapshots.back().map(|snapshot| snapshot.tick()),
+            round: self.world_round,
+        };
+        self.link.send(self.server, &message)
+    }
+
+    /// Restarts prediction from the server's view of our tank and replays
+    /// the inputs it has not applied yet.
+    fn reconcile(&mut self, snapshot: &Snapshot) {
+        let Some(server_tank) = self.tank.and_then(|tank| snapshot.tanks().get(tank)) else {
+            self.predicted = None;
+            return;
+        };
+        if Some(snapshot.round()) != self.world_round {
+            self.predicted = None;
+            return;
+        }
+        let mut tank = server_tank.clone();
+        for pending in &self.pending {
+            self.game.predict_tank(&mut tank, &pending.input, TICK_DT);
+        }
+        self.predicted = Some(tank);
+    }
+
+    /// Loads the replica with the two snapshots around the render clock,
+    /// then overrides our own tank with its prediction.
+    fn present(&mut self) {
+        let Some(newest) = self.snapshots.back().map(|snapshot| snapshot.tick() as f64) else {
+            return;
+        };
+        // Follow the server clock, easing back towards it after jitter and
+        // snapping when far off.
+        let target = newest - INTERPOLATION_DELAY;
+        if (self.render_tick - target).abs() > INTERPOLATION_DELAY * 3.0 {
+            self.render_tick = target;
+        } else {
+            self.render_tick += (target - self.render_tick) * 0.05;
+        }
+
+        let later = self
+            .snapshots
+            .iter()
+            .position(|snapshot| snapshot.tick() as f64 > self.render_tick)
+            .unwrap_or(self.snapshots.len() - 1);
+        let earlier = later.saturating_sub(1);
+        let (from, to) = (&self.snapshots[earlier], &self.snapshots[later]);
+        if Some(to.round()) != self.world_round {
+            self.status = ClientStatus::Loading;
+            return;
+        }
+        self.status = ClientStatus::Playing;
+
+        let span = to.tick().saturating_sub(from.tick());
+        self.blend = if span == 0 {
+            1.0
+        } else {
+            ((self.render_tick - from.tick() as f64) / span as f64).clamp(0.0, 1.0) as f32
+        };
+        self.game.apply_snapshot(to);
+        for (tank, previous) in self.game.tanks_mut().iter_mut().zip(from.tanks()) {
+            if previous.alive && tank.alive {
+                tank.prev_pos = previous.pos;
+                tank.prev_body_angle = previous.body_angle;
+                tank.prev_turret_angle = previous.turret_angle;
+            } else {
+                tank.store_previous();
+            }
+        }
+        let span_time = span as f32 * TICK_DT;
+        for bullet in self.game.bullets_mut() {
+            bullet.prev_pos = vec2_sub(bullet.pos, vec2_scale(bullet.vel, span_time));
+        }
+
+        let Some(predicted) = &self.predicted else {
+            return;
+        };
+        let Some(tank) = self
+            .tank
+            .and_then(|tank| self.game.tanks_mut().get_mut(tank))
+        else {
+            return;
+        };
+        // The prediction runs on its own clock, so bake its blend in and
+        // leave the shared one to the remote entities.
+        let t = self.accumulator / TICK_DT;
+        tank.pos = vec2_lerp(predicted.prev_pos, predicted.pos, t);
+        tank.body_angle = lerp_angle(predicted.prev_body_angle, predicted.body_angle, t);
+        tank.turret_angle = lerp_angle(predicted.prev_turret_angle, predicted.turret_angle, t);
+        tank.store_previous();
+    }
+}
@@ -0,0 +1,123 @@
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::math::GameRng;
+
+/// The largest UDP payload over IPv4.
+const MAX_DATAGRAM: usize = 65_507;
+
+/// Artificial network trouble for testing on one machine. It applies to
+/// packets this end sends, so set it on both ends to affect round trips.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinkConditions {
+    /// One-way delay in seconds.
+    pub latency: f32,
+    /// Extra random delay of up to this many seconds. Packets may arrive
+    /// out of order.
+    pub jitter: f32,
+    /// Fraction of packets dropped, 0 to 1.
+    pub loss: f32,
+}
+
+/// A non-blocking UDP socket that sends one JSON message per datagram.
+pub struct Link {
+    socket: UdpSocket,
+    conditions: LinkConditions,
+    rng: GameRng,
+    delayed: Vec<(Instant, SocketAddr, Vec<u8>)>,
+    buffer: Vec<u8>,
+}
+
+impl Link {
+    pub fn bind(addr: impl ToSocketAddrs, conditions: LinkConditions) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            conditions,
+            rng: GameRng::seed_from_u64(rand::random()),
+            delayed: Vec::new(),
+            buffer: vec![0; MAX_DATAGRAM],
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Allows sending to broadcast addresses.
+    pub fn set_broadcast(&self, broadcast: bool) -> io::Result<()> {
+        self.socket.set_broadcast(broadcast)
+    }
+
+    /// Queues `message` for `to`, subject to the simulated conditions.
+    pub fn send<T: Serialize>(&mut self, to: SocketAddr, message: &T) -> io::Result<()> {
+        let bytes = serde_json::to_vec(message)?;
+        if bytes.len() > MAX_DATAGRAM {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("a {} byte message does not fit in a datagram", bytes.len()),
+            ));
+        }
+        let conditions = self.conditions;
+        if conditions.loss > 0.0 && self.rng.random::<f32>() < conditions.loss {
+            return Ok(());
+        }
+        let delay = conditions.latency + conditions.jitter * self.rng.random::<f32>();
+        if delay <= 0.0 {
+            return self.send_now(to, &bytes);
+        }
+        let due = Instant::now() + Duration::from_secs_f32(delay);
+        self.delayed.push((due, to, bytes));
+        Ok(())
+    }
+
+    /// Sends any delayed packets that are due, then returns the next
+    /// message that arrived, if any. Datagrams that do not parse are
+    /// skipped.
+    pub fn recv<T: DeserializeOwned>(&mut self) -> io::Result<Option<(SocketAddr, T)>> {
+        self.flush()?;
+        loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((len, from)) => {
+                    if let Ok(message) = serde_json::from_slice(&self.buffer[..len]) {
+                        return Ok(Some((from, message)));
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+                // A peer that went away can bounce an ICMP error back to us.
+                Err(err) if err.kind() == io::ErrorKind::ConnectionReset => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Sends the delayed packets that are due.
+    pub fn flush(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+        let mut index = 0;
+        while index < self.delayed.len() {
+            if self.delayed[index].0 <= now {
+                let (_, to, bytes) = self.delayed.swap_remove(index);
+                self.send_now(to, &bytes)?;
+            } else {
+                index += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn send_now(&self, to: SocketAddr, bytes: &[u8]) -> io::Result<()> {
+        match self.socket.send_to(bytes, to) {
+            Ok(_) => Ok(()),
+            // A full send buffer drops the packet, as the network might.
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
@@ -0,0 +1,591 @@
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, ToSocketAddrs};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{GameConfig, MAX_FRAME_TIME, MAX_TANKS_PER_TEAM};
+use crate::entities::Team;
+use crate::game::LobbyPlayer;
+
+use super::link::{Link, LinkConditions};
+use super::protocol::{LobbyMessage, PROTOCOL_VERSION};
+
+/// Seconds between discovery probes.
+const PROBE_INTERVAL: f32 = 1.0;
+/// Seconds a found lobby stays listed after it stops answering.
+const LISTING_TIMEOUT: f32 = 3.5;
+/// Seconds between a member's choices, and between the host's rosters.
+const SEND_INTERVAL: f32 = 0.25;
+/// Seconds of silence before a member is dropped or the host given up on.
+const LOBBY_TIMEOUT: f32 = 5.0;
+
+/// Match options the host picks in the lobby.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LobbySettings {
+    pub map_seed: u64,
+    /// Round length in seconds.
+    pub round_time: f32,
+    /// AI tanks to add to the humans. Teams are always the same size, so
+    /// the AI may get a tank or more on top to even them out.
+    pub bots: usize,
+    /// Teams in the match, from two up to every [`Team`].
+    pub teams: usize,
+}
+
+impl LobbySettings {
+    /// The teams humans can pick from.
+    pub fn playing_teams(&self) -> &'static [Team] {
+        &Team::ALL[..self.teams.clamp(2, Team::ALL.len())]
+    }
+
+    /// `base` with these settings applied, and teams big enough for the
+    /// `humans` on each team, indexed by [`Team::index`], plus the bots.
+    /// Lobby matches are always team games.
+    pub fn config(&self, base: &GameConfig, humans: &[usize]) -> GameConfig {
+        let mut config = base.clone();
+        config.round.time = self.round_time;
+        config.round.teams = self.playing_teams().len();
+        config.round.free_for_all = false;
+        let total = humans.iter().sum::<usize>() + self.bots;
+        config.round.tanks_per_team = humans
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(total.div_ceil(config.round.teams))
+            .clamp(1, MAX_TANKS_PER_TEAM);
+        config
+    }
+}
+
+/// A lobby found on the local network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoundLobby {
+    pub addr: SocketAddr,
+    /// Name of the hosting player.
+    pub host: String,
+    pub players: usize,
+    /// The match is already under way; joining goes straight into it.
+    pub started: bool,
+}
+
+/// Looks for lobbies by broadcasting probes on the local network, and on
+/// loopback so lobbies hosted on this machine turn up too.
+pub struct Discovery {
+    link: Link,
+    port: u16,
+    found: Vec<(u64, FoundLobby, f32)>,
+    probe_timer: f32,
+}
+
+impl Discovery {
+    /// Searches for lobbies listening on `port`.
+    pub fn new(port: u16) -> io::Result<Self> {
+        let link = Link::bind("0.0.0.0:0", LinkConditions::default())?;
+        link.set_broadcast(true)?;
+        Ok(Self {
+            link,
+            port,
+            found: Vec::new(),
+            probe_timer: 0.0,
+        })
+    }
+
+    /// Collects answers and probes again when due.
+    pub fn update(&mut self, frame_dt: f32) -> io::Result<()> {
+        let frame_dt = frame_dt.clamp(0.0, MAX_FRAME_TIME);
+        while let Some((addr, message)) = self.link.recv::<LobbyMessage>()? {
+            let LobbyMessage::Advert {
+                version,
+                id,
+                host,
+                players,
+                started,
+            } = message
+            else {
+                continue;
+            };
+            if version != PROTOCOL_VERSION {
+                continue;
+            }
+            let lobby = FoundLobby {
+                addr,
+                host,
+                players,
+                started,
+            };
+            match self.found.iter_mut().find(|(known, ..)| *known == id) {
+                // Keep the address first heard from so the entry does not
+                // flip between loopback and the LAN.
+                Some((_, found, silence)) => {
+                    *found = FoundLobby {
+                        addr: found.addr,
+                        ..lobby
+                    };
+                    *silence = 0.0;
+                }
+                None => self.found.push((id, lobby, 0.0)),
+            }
+        }
+        for (_, _, silence) in &mut self.found {
+            *silence += frame_dt;
+        }
+        self.found
+            .retain(|(_, _, silence)| *silence < LISTING_TIMEOUT);
+
+        self.probe_timer -= frame_dt;
+        if self.probe_timer <= 0.0 {
+            self.probe_timer = PROBE_INTERVAL;
+            let probe = LobbyMessage::Probe {
+                version: PROTOCOL_VERSION,
+            };
+            self.link
+                .send(SocketAddr::from((Ipv4Addr::LOCALHOST, self.port)), &probe)?;
+            // Without a network to broadcast on, lobbies on this machine are
+            // still found over loopback.
+            let _ = self
+                .link
+                .send(SocketAddr::from((Ipv4Addr::BROADCAST, self.port)), &probe);
+        }
+        Ok(())
+    }
+
+    /// Lobbies that answered recently, in the order they were found.
+    pub fn lobbies(&self) -> impl Iterator<Item = &FoundLobby> {
+        self.found.iter().map(|(_, lobby, _)| lobby)
+    }
+}
+
+struct Member {
+    /// `None` for the host's own line.
+    addr: Option<SocketAddr>,
+    player: LobbyPlayer,
+    silence: f32,
+}
+
+/// Runs a lobby: answers discovery probes, keeps the roster and tells
+/// everyone where to connect once the match starts. The hosting player is
+/// always first on the roster.
+pub struct LobbyHost {
+    link: Link,
+    id: u64,
+    members: Vec<Member>,
+    settings: LobbySettings,
+    send_timer: f32,
+    /// Port of the game server, once the match has started.
+    game_port: Option<u16>,
+}
+
+impl LobbyHost {
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        name: &str,
+        settings: LobbySettings,
+        conditions: LinkConditions,
+    ) -> io::Result<Self> {
+        let name = if name.trim().is_empty() {
+            "host".to_string()
+        } else {
+            name.trim().to_string()
+        };
+        Ok(Self {
+            link: Link::bind(addr, conditions)?,
+            id: rand::random(),
+            members: vec![Member {
+                addr: None,
+                player: LobbyPlayer {
+                    name,
+                    team: Team::Red,
+                    ready: false,
+                },
+                silence: 0.0,
+            }],
+            settings,
+            send_timer: 0.0,
+            game_port: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.link.local_addr()
+    }
+
+    pub fn players(&self) -> Vec<LobbyPlayer> {
+        self.members
+            .iter()
+            .map(|member| member.player.clone())
+            .collect()
+    }
+
+    pub fn settings(&self) -> LobbySettings {
+        self.settings
+    }
+
+    /// Changes the match options; members see them with the next roster.
+    /// Anyone on a team that no longer plays moves to the smallest one
+    /// left.
+    pub fn set_settings(&mut self, settings: LobbySettings) {
+        self.settings = settings;
+        let playing = settings.playing_teams();
+        for index in 0..self.members.len() {
+            if !playing.contains(&self.members[index].player.team) {
+                let team = self.smallest_team();
+                self.members[index].player.team = team;
+            }
+        }
+    }
+
+    /// Sets the hosting player's own team and readiness.
+    pub fn choose(&mut self, team: Team, ready: bool) {
+        let player = &mut self.members[0].player;
+        player.team = team;
+        player.ready = ready;
+    }
+
+    /// Humans on each team, indexed by [`Team::index`].
+    pub fn humans(&self) -> [usize; Team::ALL.len()] {
+        let mut humans = [0; Team::ALL.len()];
+        for member in &self.members {
+            humans[member.player.team.index()] += 1;
+        }
+        humans
+    }
+
+    /// The playing team with the fewest humans, the first on a tie.
+    fn smallest_team(&self) -> Team {
+        let humans = self.humans();
+        let playing = self.settings.playing_teams();
+        playing
+            .iter()
+            .copied()
+            .min_by_key(|team| humans[team.index()])
+            .unwrap_or(Team::Red)
+    }
+
+    /// Whether every human in the lobby, the host included, is ready.
+    pub fn all_ready(&self) -> bool {
+        self.members.iter().all(|member| member.player.ready)
+    }
+
+    /// The tuning for the match: `base` with the lobby's settings applied.
+    pub fn config(&self, base: &GameConfig) -> GameConfig {
+        self.settings.config(base, &self.humans())
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.game_port.is_some()
+    }
+
+    /// Tells every member the match is on, with the game server on `port`
+    /// of this machine. Members who missed it, and anyone who joins later,
+    /// are told when they next get in touch.
+    pub fn start(&mut self, port: u16) -> io::Result<()> {
+        self.game_port = Some(port);
+        self.send_timer = 0.0;
+        self.update(0.0)
+    }
+
+    /// Answers probes and members, drops the silent ones and sends the
+    /// roster when due.
+    pub fn update(&mut self, frame_dt: f32) -> io::Result<()> {
+        let frame_dt = frame_dt.clamp(0.0, MAX_FRAME_TIME);
+        self.receive()?;
+        for member in &mut self.members[1..] {
+            member.silence += frame_dt;
+        }
+        self.members.retain(|member| member.silence < LOBBY_TIMEOUT);
+
+        self.send_timer -= frame_dt;
+        if self.send_timer <= 0.0 {
+            self.send_timer = SEND_INTERVAL;
+            for index in 1..self.members.len() {
+                self.send_to(index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Tells the members the lobby is closing. Best effort: they also give
+    /// up on a silent host.
+    pub fn close(&mut self) {
+        for member in &self.members {
+            if let Some(addr) = member.addr {
+                let _ = self.link.send(addr, &LobbyMessage::Leave);
+            }
+        }
+        let _ = self.link.flush();
+    }
+
+    fn receive(&mut self) -> io::Result<()> {
+        while let Some((addr, message)) = self.link.recv::<LobbyMessage>()? {
+            let known = self
+                .members
+                .iter()
+                .position(|member| member.addr == Some(addr));
+            match (message, known) {
+                (LobbyMessage::Probe { .. }, _) => {
+                    let advert = LobbyMessage::Advert {
+                        version: PROTOCOL_VERSION,
+                        id: self.id,
+                        host: self.members[0].player.name.clone(),
+                        players: self.members.len(),
+                        started: self.is_started(),
+                    };
+                    self.link.send(addr, &advert)?;
+                }
+                (LobbyMessage::Join { version, name }, None) => self.admit(addr, version, name)?,
+                // Our answer was lost; say it again.
+                (LobbyMessage::Join { .. }, Some(index)) => {
+                    self.members[index].silence = 0.0;
+                    self.send_to(index)?;
+                }
+                (LobbyMessage::Choose { team, ready }, Some(index)) => {
+                    let room = self.settings.playing_teams().contains(&team)
+                        && self.humans()[team.index()] < MAX_TANKS_PER_TEAM;
+                    let player = &mut self.members[index].player;
+                    if room {
+                        player.team = team;
+                    }
+                    player.ready = ready;
+                    self.members[index].silence = 0.0;
+                }
+                (LobbyMessage::Leave, Some(index)) => {
+                    self.members.remove(index);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn admit(&mut self, addr: SocketAddr, version: u32, name: String) -> io::Result<()> {
+        if version != PROTOCOL_VERSION {
+            let reason =
+                format!("the lobby speaks protocol {PROTOCOL_VERSION}, you speak {version}");
+            return self.link.send(addr, &LobbyMessage::Reject { reason });
+        }
+        let team = self.smallest_team();
+        if self.humans()[team.index()] >= MAX_TANKS_PER_TEAM {
+            let reason = "the lobby is full".to_string();
+            return self.link.send(addr, &LobbyMessage::Reject { reason });
+        }
+        let name = if name.trim().is_empty() {
+            format!("player{}", self.members.len() + 1)
+        } else {
+            name.trim().to_string()
+        };
+        self.members.push(Member {
+            addr: Some(addr),
+            player: LobbyPlayer {
+                name,
+                team,
+                ready: false,
+            },
+            silence: 0.0,
+        });
+        self.send_to(self.members.len() - 1)
+    }
+
+    /// Sends member `index` the roster, or where to go once started.
+    fn send_to(&mut self, index: usize) -> io::Result<()> {
+        let Some(addr) = self.members[index].addr else {
+            return Ok(());
+        };
+        let message = match self.game_port {
+            Some(port) => LobbyMessage::Start { port },
+            None => LobbyMessage::Roster {
+                you: index,
+                players: self.players(),
+                settings: self.settings,
+            },
+        };
+        self.link.send(addr, &message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LobbyStatus {
+    Joining,
+    /// On the roster, waiting for the host to start.
+    Waiting,
+    /// The match is on, at this game server.
+    Started(SocketAddr),
+    Closed(String),
+}
+
+/// A player's place in someone else's [`LobbyHost`].
+pub struct LobbyGuest {
+    link: Link,
+    host: SocketAddr,
+    name: String,
+    status: LobbyStatus,
+    players: Vec<LobbyPlayer>,
+    you: Option<usize>,
+    settings: Option<LobbySettings>,
+    team: Option<Team>,
+    ready: bool,
+    send_timer: f32,
+    silence: f32,
+}
+
+impl LobbyGuest {
+    pub fn join(
+        host: impl ToSocketAddrs,
+        name: &str,
+        conditions: LinkConditions,
+    ) -> io::Result<Self> {
+        let host = host.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "lobby address did not resolve")
+        })?;
+        let local = if host.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        Ok(Self {
+            link: Link::bind(local, conditions)?,
+            host,
+            name: name.to_string(),
+            status: LobbyStatus::Joining,
+            players: Vec::new(),
+            you: None,
+            settings: None,
+            team: None,
+            ready: false,
+            send_timer: 0.0,
+            silence: 0.0,
+        })
+    }
+
+    pub fn status(&self) -> &LobbyStatus {
+        &self.status
+    }
+
+    pub fn host(&self) -> SocketAddr {
+        self.host
+    }
+
+    /// The roster, showing our own choices as soon as they are made.
+    pub fn players(&self) -> Vec<LobbyPlayer> {
+        let mut players = self.players.clone();
+        if let Some(player) = self.you.and_then(|you| players.get_mut(you)) {
+            player.team = self.team.unwrap_or(player.team);
+            player.ready = self.ready;
+        }
+        players
+    }
+
+    /// Our line in [`LobbyGuest::players`].
+    pub fn you(&self) -> Option<usize> {
+        self.you
+    }
+
+    pub fn settings(&self) -> Option<LobbySettings> {
+        self.settings
+    }
+
+    /// The team we are on, once the host has seated us.
+    pub fn team(&self) -> Option<Team> {
+        self.team
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+
+    /// Picks a team and readiness and tells the host straight away.
+    pub fn choose(&mut self, team: Team, ready: bool) {
+        self.team = Some(team);
+        self.ready = ready;
+        self.send_timer = 0.0;
+    }
+
+    /// Exchanges messages with the host.
+    pub fn update(&mut self, frame_dt: f32) -> io::Result<()> {
+        let frame_dt = frame_dt.clamp(0.0, MAX_FRAME_TIME);
+        if !self.is_open() {
+            return Ok(());
+        }
+        self.receive()?;
+        if !self.is_open() {
+            return Ok(());
+        }
+        self.silence += frame_dt;
+        if self.silence > LOBBY_TIMEOUT {
+            self.status = LobbyStatus::Closed(match self.status {
+                LobbyStatus::Joining => "no answer from the lobby".to_string(),
+                _ => "the host stopped responding".to_string(),
+            });
+            return Ok(());
+        }
+
+        self.send_timer -= frame_dt;
+        if self.send_timer <= 0.0 {
+            self.send_timer = SEND_INTERVAL;
+            let message = match self.team {
+                Some(team) => LobbyMessage::Choose {
+                    team,
+                    ready: self.ready,
+                },
+                None => LobbyMessage::Join {
+                    version: PROTOCOL_VERSION,
+                    name: self.name.clone(),
+                },
+            };
+            self.link.send(self.host, &message)?;
+        }
+        Ok(())
+    }
+
+    /// Gives up our place. Best effort: the host also drops silent members.
+    pub fn leave(&mut self) {
+        let _ = self.link.send(self.host, &LobbyMessage::Leave);
+        let _ = self.link.flush();
+        self.status = LobbyStatus::Closed("left the lobby".to_string());
+    }
+
+    fn is_open(&self) -> bool {
+        matches!(self.status, LobbyStatus::Joining | LobbyStatus::Waiting)
+    }
+
+    fn receive(&mut self) -> io::Result<()> {
+        while let Some((from, message)) = self.link.recv::<LobbyMessage>()? {
+            if from != self.host {
+                continue;
+            }
+            self.silence = 0.0;
+            match message {
+                LobbyMessage::Roster {
+                    you,
+                    players,
+                    settings,
+                } => {
+                    // Take the seat the host gave us until we pick one, or
+                    // when our pick stops playing.
+                    let playing = settings.playing_teams();
+                    if self.team.is_none_or(|team| !playing.contains(&team)) {
+                        self.team = players.get(you).map(|player| player.team);
+                    }
+                    self.status = LobbyStatus::Waiting;
+                    self.you = Some(you);
+                    self.players = players;
+                    self.settings = Some(settings);
+                }
+                LobbyMessage::Start { port } => {
+                    self.status = LobbyStatus::Started(SocketAddr::new(self.host.ip(), port));
+                    return Ok(());
+                }
+                LobbyMessage::Reject { reason } => {
+                    self.status = LobbyStatus::Closed(reason);
+                    return Ok(());
+                }
+                LobbyMessage::Leave => {
+                    self.status = LobbyStatus::Closed("the host closed the lobby".to_string());
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
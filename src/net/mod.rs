@@ -0,0 +1,16 @@
+mod client;
+mod link;
+mod lobby;
+mod protocol;
+mod rollback;
+mod server;
+
+pub use client::{Client, ClientStatus};
+pub use link::{Link, LinkConditions};
+pub use lobby::{Discovery, FoundLobby, LobbyGuest, LobbyHost, LobbySettings, LobbyStatus};
+pub use protocol::{
+    ClientMessage, DEFAULT_PORT, DuelInput, DuelMessage, LobbyMessage, PROTOCOL_VERSION,
+    SequencedInput, ServerMessage,
+};
+pub use rollback::{DuelSession, DuelStats, DuelStatus};
+pub use server::{PlayerInfo, Server, ServerEvent};
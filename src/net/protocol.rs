@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::entities::Team;
+use crate::game::{LobbyPlayer, PlayerInput, SnapshotDelta};
+
+use super::lobby::LobbySettings;
+
+/// Bumped whenever a message changes shape; mismatched peers are turned
+/// away at the handshake.
+pub const PROTOCOL_VERSION: u32 = 4;
+pub const DEFAULT_PORT: u16 = 7777;
+
+/// One tick of a duellist's controls.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DuelInput {
+    pub input: PlayerInput,
+    /// Start the next round once the current one is over.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub start: bool,
+}
+
+/// One tick of controls, numbered so the server can say which it has
+/// applied.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SequencedInput {
+    pub seq: u32,
+    pub input: PlayerInput,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Asks for a tank, on `team` if given, or to watch when `spectate`
+    /// is set. Repeated until answered.
+    Hello {
+        version: u32,
+        name: String,
+        team: Option<Team>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        spectate: bool,
+    },
+    /// The newest inputs the server has not acknowledged, oldest first.
+    Input {
+        inputs: Vec<SequencedInput>,
+        /// Tick of the newest snapshot received, the baseline for the
+        /// next delta.
+        ack: Option<u64>,
+        /// The round whose map the client holds.
+        round: Option<u32>,
+    },
+    Bye,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Welcome {
+        tank: usize,
+        team: Team,
+        config: GameConfig,
+    },
+    /// Accepts a spectator, who gets snapshots but no tank.
+    Spectating {
+        config: GameConfig,
+    },
+    Reject {
+        reason: String,
+    },
+    Snapshot {
+        /// Sequence number of the last input applied to the client's tank.
+        last_input: Option<u32>,
+        delta: SnapshotDelta,
+    },
+    /// One piece of the JSON-encoded map for `round`.
+    WorldChunk {
+        round: u32,
+        index: usize,
+        count: usize,
+        data: String,
+    },
+}
+
+/// Messages between the two peers of a rollback duel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DuelMessage {
+    /// Guest to host, repeated until the match starts.
+    Join {
+        version: u32,
+    },
+    /// Host to guest: how to build the match. Repeated until the guest's
+    /// first inputs arrive.
+    Start {
+        seed: u64,
+        config: Box<GameConfig>,
+    },
+    Reject {
+        reason: String,
+    },
+    Inputs {
+        /// Frame of the first input.
+        first: u32,
+        /// The sender's inputs the receiver has not acknowledged, in order.
+        inputs: Vec<DuelInput>,
+        /// How many of the receiver's inputs the sender holds.
+        received: u32,
+        /// How far the sender thinks it is ahead of the receiver, in frames.
+        advantage: i32,
+        /// The sender's newest confirmed frame and its state's checksum.
+        checksum: Option<(u32, u64)>,
+    },
+    Bye,
+}
+
+/// Messages for finding and filling a LAN lobby.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LobbyMessage {
+    /// Broadcast by players looking for games.
+    Probe {
+        version: u32,
+    },
+    /// A host's answer to a probe.
+    Advert {
+        version: u32,
+        /// Tells apart hosts that answer on more than one address.
+        id: u64,
+        host: String,
+        players: usize,
+        started: bool,
+    },
+    /// Asks for a place in the lobby. Repeated until answered.
+    Join {
+        version: u32,
+        name: String,
+    },
+    /// A member's current choices, repeated as a keepalive.
+    Choose {
+        team: Team,
+        ready: bool,
+    },
+    /// The host's view of the lobby, sent to every member regularly.
+    Roster {
+        /// The receiver's own line in `players`.
+        you: usize,
+        players: Vec<LobbyPlayer>,
+        settings: LobbySettings,
+    },
+    /// The match is on: connect to the game server on this port of the
+    /// host.
+    Start {
+        port: u16,
+    },
+    Reject {
+        reason: String,
+    },
+    Leave,
+}
@@ -0,0 +1,677 @@
+use std::collections::VecDeque;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{GameConfig, MAX_FRAME_TIME, TICK_DT};
+use crate::entities::Team;
+use crate::game::{Game, InputBinding, LocalPlayer, TankInput, TickInput};
+
+use super::link::{Link, LinkConditions};
+use super::protocol::{DuelInput, DuelMessage, PROTOCOL_VERSION};
+
+/// Frames between sampling local input and using it. Hides this much
+/// latency without any rollback.
+const INPUT_DELAY: u32 = 2;
+/// How far the simulation may run past the last input confirmed by the
+/// peer before it waits.
+const MAX_ROLLBACK: u32 = 8;
+/// Frames between confirmed-state checksums.
+const CHECKSUM_INTERVAL: u32 = 30;
+/// Checksums kept to compare against the peer's.
+const CHECKSUM_HISTORY: usize = 256;
+/// Frames between skipped frames while waiting for a slower peer.
+const SYNC_INTERVAL: u32 = 20;
+const JOIN_INTERVAL: f32 = 0.25;
+const PEER_TIMEOUT: f32 = 5.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DuelStatus {
+    /// Waiting for the other peer to join or to start the match.
+    Waiting,
+    Running,
+    /// The peers simulated the same frame differently.
+    Desynced {
+        frame: u32,
+    },
+    Disconnected(String),
+}
+
+/// How much correcting the session has done so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DuelStats {
+    pub frames: u32,
+    /// Times a late remote input disagreed with its prediction.
+    pub rollbacks: u32,
+    /// Frames simulated again after rollbacks.
+    pub resimulated: u32,
+    /// Deepest rollback, in frames.
+    pub max_depth: u32,
+    /// Ticks spent waiting on the peer's input.
+    pub stalls: u32,
+    /// Ticks skipped to let a slower peer catch up.
+    pub skipped: u32,
+}
+
+/// Inputs for a run of frames, forgetting the oldest as they stop being
+/// needed.
+#[derive(Default)]
+struct InputLog {
+    first: u32,
+    inputs: VecDeque<DuelInput>,
+}
+
+impl InputLog {
+    /// One past the newest frame held.
+    fn end(&self) -> u32 {
+        self.first + self.inputs.len() as u32
+    }
+
+    fn get(&self, frame: u32) -> Option<DuelInput> {
+        let index = frame.checked_sub(self.first)?;
+        self.inputs.get(index as usize).copied()
+    }
+
+    fn last(&self) -> Option<DuelInput> {
+        self.inputs.back().copied()
+    }
+
+    fn push(&mut self, input: DuelInput) {
+        self.inputs.push_back(input);
+    }
+
+    fn truncate(&mut self, end: u32) {
+        self.inputs
+            .truncate(end.saturating_sub(self.first) as usize);
+    }
+
+    fn forget_before(&mut self, frame: u32) {
+        while self.first < frame && !self.inputs.is_empty() {
+            self.inputs.pop_front();
+            self.first += 1;
+        }
+    }
+
+    fn range(&self, from: u32) -> Vec<DuelInput> {
+        let skip = from.saturating_sub(self.first) as usize;
+        self.inputs.iter().skip(skip).copied().collect()
+    }
+}
+
+/// One side of a peer-to-peer duel with rollback: both peers run the full
+/// simulation, guess the other's input from their last one, and rewind to
+/// replay any frames that were guessed wrong once the real input arrives.
+/// The host plays Crimson, the guest Azure.
+pub struct DuelSession {
+    link: Link,
+    host: bool,
+    peer: Option<SocketAddr>,
+    status: DuelStatus,
+    /// Seed and config the host starts the match with.
+    start: Option<(u64, GameConfig)>,
+    game: Game,
+    /// The next frame to simulate; `game` is the state at its start.
+    frame: u32,
+    local: InputLog,
+    /// The peer's inputs, contiguous from the start of the match.
+    remote: InputLog,
+    /// The peer input each simulated frame used, guessed or not.
+    used_remote: InputLog,
+    /// `game` as it was at the start of each frame not yet confirmed.
+    states: VecDeque<Game>,
+    states_first: u32,
+    /// Frames whose start state can no longer change.
+    confirmed: u32,
+    /// How many of our inputs the peer holds.
+    peer_received: u32,
+    peer_advantage: i32,
+    since_skip: u32,
+    checksums: VecDeque<(u32, u64)>,
+    peer_checksum: Option<(u32, u64)>,
+    accumulator: f32,
+    silence: f32,
+    join_timer: f32,
+    stats: DuelStats,
+}
+
+impl DuelSession {
+    /// Waits on `addr` for a guest, then plays the match `seed` and
+    /// `config` describe.
+    pub fn host(
+        addr: impl ToSocketAddrs,
+        seed: u64,
+        mut config: GameConfig,
+        conditions: LinkConditions,
+    ) -> io::Result<Self> {
+        // A duel is always Crimson against Azure.
+        config.round.teams = 2;
+        config.round.free_for_all = false;
+        let link = Link::bind(addr, conditions)?;
+        let mut session = Self::new(link, true, None);
+        session.begin(seed, config.clone());
+        session.start = Some((seed, config));
+        session.status = DuelStatus::Waiting;
+        Ok(session)
+    }
+
+    /// Joins the duel hosted at `host`.
+    pub fn join(host: impl ToSocketAddrs, conditions: LinkConditions) -> io::Result<Self> {
+        let host = host.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "host address did not resolve")
+        })?;
+        let local = if host.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let link = Link::bind(local, conditions)?;
+        Ok(Self::new(link, false, Some(host)))
+    }
+
+    fn new(link: Link, host: bool, peer: Option<SocketAddr>) -> Self {
+        Self {
+            link,
+            host,
+            peer,
+            status: DuelStatus::Waiting,
+            start: None,
+            game: Game::new(0),
+            frame: 0,
+            local: InputLog::default(),
+            remote: InputLog::default(),
+            used_remote: InputLog::default(),
+            states: VecDeque::new(),
+            states_first: 0,
+            confirmed: 0,
+            peer_received: 0,
+            peer_advantage: 0,
+            since_skip: 0,
+            checksums: VecDeque::new(),
+            peer_checksum: None,
+            accumulator: 0.0,
+            silence: 0.0,
+            join_timer: 0.0,
+            stats: DuelStats::default(),
+        }
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.link.local_addr()
+    }
+
+    pub fn status(&self) -> &DuelStatus {
+        &self.status
+    }
+
+    /// The side this peer plays.
+    pub fn team(&self) -> Team {
+        if self.host { Team::Red } else { Team::Blue }
+    }
+
+    /// The match as of the newest simulated frame, predictions included.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// The match, for sampling input against its camera.
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Blend to draw the match with.
+    pub fn blend(&self) -> f32 {
+        (self.accumulator / TICK_DT).clamp(0.0, 1.0)
+    }
+
+    pub fn stats(&self) -> DuelStats {
+        DuelStats {
+            frames: self.frame,
+            ..self.stats
+        }
+    }
+
+    /// Checksums of confirmed frames, oldest first.
+    pub fn checksums(&self) -> impl Iterator<Item = (u32, u64)> + '_ {
+        self.checksums.iter().copied()
+    }
+
+    /// Exchanges inputs with the peer, corrects mispredicted frames and
+    /// advances the match by `frame_dt`, taking `input` for each new tick.
+    pub fn update(&mut self, frame_dt: f32, input: DuelInput) -> io::Result<()> {
+        if matches!(
+            self.status,
+            DuelStatus::Disconnected(_) | DuelStatus::Desynced { .. }
+        ) {
+            return Ok(());
+        }
+        let frame_dt = frame_dt.clamp(0.0, MAX_FRAME_TIME);
+        let remote_end = self.remote.end();
+        self.receive()?;
+        if self.status != DuelStatus::Running {
+            return self.wait(frame_dt);
+        }
+        self.silence += frame_dt;
+        if self.silence > PEER_TIMEOUT {
+            self.status = DuelStatus::Disconnected("the peer stopped responding".to_string());
+            return Ok(());
+        }
+
+        self.correct(remote_end);
+        self.accumulator += frame_dt;
+        while self.accumulator >= TICK_DT {
+            if self.should_skip() {
+                self.accumulator -= TICK_DT;
+                self.since_skip = 0;
+                self.stats.skipped += 1;
+                continue;
+            }
+            if self.frame >= self.remote.end() + MAX_ROLLBACK {
+                // Too far ahead of the peer: hold still rather than
+                // guess further.
+                self.accumulator = self.accumulator.min(TICK_DT);
+                self.stats.stalls += 1;
+                break;
+            }
+            self.accumulator -= TICK_DT;
+            self.since_skip += 1;
+            self.local.push(input);
+            self.advance();
+        }
+        self.settle();
+        self.send_inputs()
+    }
+
+    /// Tells the peer we are leaving.
+    pub fn disconnect(&mut self) {
+        if let Some(peer) = self.peer {
+            let _ = self.link.send(peer, &DuelMessage::Bye);
+            let _ = self.link.flush();
+        }
+        self.status = DuelStatus::Disconnected("left the duel".to_string());
+    }
+
+    /// Before the match: the guest asks to join until the host answers.
+    fn wait(&mut self, frame_dt: f32) -> io::Result<()> {
+        let Some(peer) = self.peer.filter(|_| !self.host) else {
+            return Ok(());
+        };
+        self.silence += frame_dt;
+        if self.silence > PEER_TIMEOUT {
+            self.status = DuelStatus::Disconnected("no answer from the host".to_string());
+            return Ok(());
+        }
+        self.join_timer -= frame_dt;
+        if self.join_timer <= 0.0 {
+            self.join_timer = JOIN_INTERVAL;
+            let join = DuelMessage::Join {
+                version: PROTOCOL_VERSION,
+            };
+            self.link.send(peer, &join)?;
+        }
+        Ok(())
+    }
+
+    /// Sets up frame 0 the same way on both peers.
+    fn begin(&mut self, seed: u64, config: GameConfig) {
+        self.game = Game::with_config(seed, config);
+        self.game.set_local_players(vec![LocalPlayer {
+            team: self.team(),
+            binding: InputBinding::Auto,
+        }]);
+        self.game.start_round();
+        for _ in 0..INPUT_DELAY {
+            self.local.push(DuelInput::default());
+        }
+        self.silence = 0.0;
+        self.status = DuelStatus::Running;
+    }
+
+    fn receive(&mut self) -> io::Result<()> {
+        while let Some((from, message)) = self.link.recv::<DuelMessage>()? {
+            if self.peer.is_some_and(|peer| peer != from) {
+                if matches!(message, DuelMessage::Join { .. }) {
+                    let reason = "this duel already has two players".to_string();
+                    self.link.send(from, &DuelMessage::Reject { reason })?;
+                }
+                continue;
+            }
+            self.silence = 0.0;
+            match message {
+                DuelMessage::Join { version } if self.host => {
+                    if version != PROTOCOL_VERSION {
+                        let reason = format!(
+                            "host speaks protocol {PROTOCOL_VERSION}, guest speaks {version}"
+                        );
+                        self.link.send(from, &DuelMessage::Reject { reason })?;
+                        continue;
+                    }
+                    self.peer = Some(from);
+                    self.status = DuelStatus::Running;
+                }
+                DuelMessage::Start { seed, config } if !self.host => {
+                    if self.status == DuelStatus::Waiting {
+                        self.begin(seed, *config);
+                    }
+                }
+                DuelMessage::Reject { reason } => {
+                    self.status = DuelStatus::Disconnected(reason);
+                }
+                DuelMessage::Inputs {
+                    first,
+                    inputs,
+                    received,
+                    advantage,
+                    checksum,
+                } => {
+                    // Only a run that continues where ours ends is useful;
+                    // anything after a gap comes round again.
+                    let end = self.remote.end();
+                    if first <= end {
+                        let skip = (end - first) as usize;
+                        for input in inputs.into_iter().skip(skip) {
+                            self.remote.push(input);
+                        }
+                    }
+                    self.peer_received = self.peer_received.max(received);
+                    self.peer_advantage = advantage;
+                    if checksum > self.peer_checksum {
+                        self.peer_checksum = checksum;
+                    }
+                }
+                DuelMessage::Bye => {
+                    self.status = DuelStatus::Disconnected("the peer left".to_string());
+                }
+                DuelMessage::Join { .. } | DuelMessage::Start { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the peer inputs that arrived for frames from `from` on
+    /// against the guesses made for them, and replays from the first wrong
+    /// guess.
+    fn correct(&mut self, from: u32) {
+        let end = self.remote.end().min(self.frame);
+        let wrong =
+            (from..end).find(|&frame| self.used_remote.get(frame) != self.remote.get(frame));
+        if let Some(frame) = wrong {
+            self.rollback(frame);
+        }
+    }
+
+    /// Records checksums for frames that became final and drops the
+    /// history they no longer need.
+    fn settle(&mut self) {
+        let confirmed = self.remote.end().min(self.frame);
+        for frame in self.confirmed + 1..=confirmed {
+            if frame.is_multiple_of(CHECKSUM_INTERVAL) {
+                let checksum = self.state_at(frame).checksum();
+                self.checksums.push_back((frame, checksum));
+                if self.checksums.len() > CHECKSUM_HISTORY {
+                    self.checksums.pop_front();
+                }
+            }
+        }
+        self.confirmed = self.confirmed.max(confirmed);
+        self.check_desync();
+
+        while self.states_first < confirmed && !self.states.is_empty() {
+            self.states.pop_front();
+            self.states_first += 1;
+        }
+        self.used_remote.forget_before(confirmed);
+        self.remote.forget_before(confirmed.saturating_sub(1));
+        self.local.forget_before(confirmed.min(self.peer_received));
+    }
+
+    fn check_desync(&mut self) {
+        let Some((frame, theirs)) = self.peer_checksum else {
+            return;
+        };
+        let ours = self
+            .checksums
+            .iter()
+            .find(|(checked, _)| *checked == frame)
+            .map(|(_, checksum)| *checksum);
+        if ours.is_some_and(|ours| ours != theirs) {
+            self.status = DuelStatus::Desynced { frame };
+        }
+    }
+
+    /// Rewinds to the start of `frame` and simulates back up to the
+    /// present with the inputs known now.
+    fn rollback(&mut self, frame: u32) {
+        let present = self.frame;
+        let index = (frame - self.states_first) as usize;
+        self.game = self.states[index].clone();
+        self.states.truncate(index);
+        self.used_remote.truncate(frame);
+        self.frame = frame;
+        while self.frame < present {
+            self.advance();
+        }
+
+        let depth = present - frame;
+        self.stats.rollbacks += 1;
+        self.stats.resimulated += depth;
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+    }
+
+    /// Simulates one frame, guessing the peer's input if it has not
+    /// arrived.
+    fn advance(&mut self) {
+        let local = self.local.get(self.frame).unwrap_or_default();
+        let remote = self
+            .remote
+            .get(self.frame)
+            .or_else(|| self.remote.last())
+            .unwrap_or_default();
+        if self.states.is_empty() {
+            self.states_first = self.frame;
+        }
+        self.states.push_back(self.game.clone());
+        self.used_remote.push(remote);
+
+        let (red, blue) = if self.host {
+            (local, remote)
+        } else {
+            (remote, local)
+        };
+        let mut input = TickInput {
+            start_pressed: red.start || blue.start,
+            tanks: Vec::new(),
+        };
+        for (team, duel) in [(Team::Red, red), (Team::Blue, blue)] {
+            let tank = self.game.tanks().iter().position(|tank| tank.team == team);
+            if let Some(tank) = tank {
+                input.tanks.push(TankInput {
+                    tank,
+                    input: duel.input,
+                });
+            }
+        }
+        self.game.update(TICK_DT, &input);
+        self.frame += 1;
+    }
+
+    fn state_at(&self, frame: u32) -> &Game {
+        match frame.checked_sub(self.states_first) {
+            Some(index) if (index as usize) < self.states.len() => &self.states[index as usize],
+            _ => &self.game,
+        }
+    }
+
+    /// Frames we are ahead of the peer, judged from the inputs we hold.
+    fn advantage(&self) -> i32 {
+        self.frame as i32 - (self.remote.end() as i32 - INPUT_DELAY as i32)
+    }
+
+    /// Whether to sit out a tick so a peer running behind can catch up.
+    /// Both sides see the other as behind by the one-way latency, so only
+    /// the difference between the two views counts.
+    fn should_skip(&self) -> bool {
+        self.since_skip >= SYNC_INTERVAL && (self.advantage() - self.peer_advantage) / 2 >= 1
+    }
+
+    fn send_inputs(&mut self) -> io::Result<()> {
+        let Some(peer) = self.peer else {
+            return Ok(());
+        };
+        // Until the guest's inputs show it has started, keep telling it how.
+        let start = self
+            .start
+            .as_ref()
+            .filter(|_| self.remote.end() == 0)
+            .map(|(seed, config)| DuelMessage::Start {
+                seed: *seed,
+                config: Box::new(config.clone()),
+            });
+        if let Some(start) = start {
+            self.link.send(peer, &start)?;
+        }
+        let first = self.peer_received.max(self.local.first);
+        let message = DuelMessage::Inputs {
+            first,
+            inputs: self.local.range(first),
+            received: self.remote.end(),
+            advantage: self.advantage(),
+            checksum: self.checksums.back().copied(),
+        };
+        self.link.send(peer, &message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::PlayerInput;
+
+    use super::*;
+
+    fn input(turn: f32) -> DuelInput {
+        DuelInput {
+            input: PlayerInput {
+                turn,
+                movement: 1.0,
+                ..PlayerInput::default()
+            },
+            start: false,
+        }
+    }
+
+    fn log(first: u32, turns: &[f32]) -> InputLog {
+        let mut log = InputLog {
+            first,
+            ..InputLog::default()
+        };
+        for &turn in turns {
+            log.push(input(turn));
+        }
+        log
+    }
+
+    #[test]
+    fn input_log_truncates_to_a_frame() {
+        let mut log = log(10, &[0.0, 0.1, 0.2, 0.3]);
+        log.truncate(12);
+        assert_eq!(log.end(), 12);
+        assert_eq!(log.last(), Some(input(0.1)));
+        // Before the log starts empties it; past the end leaves it be.
+        log.truncate(20);
+        assert_eq!(log.end(), 12);
+        log.truncate(5);
+        assert_eq!(log.end(), 10);
+        assert_eq!(log.get(10), None);
+    }
+
+    #[test]
+    fn input_log_forgets_old_frames() {
+        let mut log = log(10, &[0.0, 0.1, 0.2, 0.3]);
+        log.forget_before(12);
+        assert_eq!(log.first, 12);
+        assert_eq!(log.get(11), None);
+        assert_eq!(log.get(12), Some(input(0.2)));
+        assert_eq!(log.end(), 14);
+        // Never past what it holds.
+        log.forget_before(30);
+        assert_eq!(log.first, 14);
+        assert_eq!(log.end(), 14);
+    }
+
+    #[test]
+    fn input_log_ranges_from_a_frame() {
+        let log = log(10, &[0.0, 0.1, 0.2, 0.3]);
+        assert_eq!(log.range(12), [input(0.2), input(0.3)]);
+        assert_eq!(log.range(0), log.range(10));
+        assert!(log.range(14).is_empty());
+    }
+
+    /// A host session that has started its match but has no peer.
+    fn session() -> DuelSession {
+        let link = Link::bind("127.0.0.1:0", LinkConditions::default()).unwrap();
+        let mut session = DuelSession::new(link, true, None);
+        session.begin(3, GameConfig::default());
+        session
+    }
+
+    fn play(session: &mut DuelSession, frames: u32) {
+        for _ in 0..frames {
+            session.local.push(input(0.0));
+            session.advance();
+        }
+    }
+
+    #[test]
+    fn rollback_replays_late_inputs_and_counts_its_depth() {
+        // Idle for two frames, as guessed, then turning.
+        let (still, left, right) = (DuelInput::default(), input(-1.0), input(1.0));
+        let remote = [still, still, right, right, right, right, right, left];
+        let mut expected = session();
+        for &sent in &remote {
+            expected.remote.push(sent);
+        }
+        play(&mut expected, 8);
+
+        // The peer's turn at frame 2 arrives after frame 6 was guessed.
+        let mut session = session();
+        play(&mut session, 6);
+        for &sent in &remote[..6] {
+            session.remote.push(sent);
+        }
+        session.correct(0);
+        assert_eq!(session.frame, 6);
+        let stats = session.stats();
+        assert_eq!(
+            (stats.rollbacks, stats.resimulated, stats.max_depth),
+            (1, 4, 4)
+        );
+
+        // Frame 6 guessed right from the last input, frame 7 did not.
+        play(&mut session, 2);
+        for &sent in &remote[6..] {
+            session.remote.push(sent);
+        }
+        session.correct(6);
+        let stats = session.stats();
+        assert_eq!(
+            (stats.rollbacks, stats.resimulated, stats.max_depth),
+            (2, 5, 4)
+        );
+        assert_eq!(session.game.checksum(), expected.game.checksum());
+    }
+
+    #[test]
+    fn right_guesses_need_no_rollback() {
+        let mut session = session();
+        play(&mut session, 5);
+        for _ in 0..5 {
+            session.remote.push(DuelInput::default());
+        }
+        session.correct(0);
+        assert_eq!(session.stats().rollbacks, 0);
+    }
+}
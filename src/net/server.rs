@@ -0,0 +1,566 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{GameConfig, TICK_DT, TICK_RATE};
+use crate::entities::Team;
+use crate::game::{Game, PlayerInput, Snapshot, TankInput, TickInput};
+
+use super::link::{Link, LinkConditions};
+use super::protocol::{ClientMessage, PROTOCOL_VERSION, SequencedInput, ServerMessage};
+
+/// Ticks between snapshots.
+const SNAPSHOT_INTERVAL: u64 = 2;
+/// Snapshots kept as delta baselines.
+const SNAPSHOT_HISTORY: usize = 64;
+/// Inputs queued past this are dropped, which bounds the latency a burst
+/// of late packets can add.
+const MAX_QUEUED_INPUTS: usize = 6;
+/// Seconds of silence before a client is dropped.
+const CLIENT_TIMEOUT: f32 = 5.0;
+/// Seconds the round-over screen shows before the next round deploys.
+const ROUND_BREAK: f32 = 6.0;
+const WORLD_CHUNK_SIZE: usize = 8 * 1024;
+/// Ticks between resends of the map to clients that have not confirmed it.
+const WORLD_RESEND: u64 = 30;
+
+/// Something worth logging that happened during [`Server::tick`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServerEvent {
+    Joined {
+        name: String,
+        tank: usize,
+        team: Team,
+    },
+    Watching {
+        name: String,
+    },
+    Left {
+        name: String,
+        reason: String,
+    },
+    RoundStarted {
+        round: u32,
+        /// The map's seed, when it came from the rotation or was chosen.
+        seed: Option<u64>,
+    },
+    RoundOver {
+        round: u32,
+        /// Kills or captures, as the mode scores them.
+        scores: Vec<u32>,
+        winner: Option<Team>,
+    },
+}
+
+impl fmt::Display for ServerEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerEvent::Joined { name, tank, team } => {
+                write!(f, "{name} joined {} in tank {tank}", team.name())
+            }
+            ServerEvent::Watching { name } => write!(f, "{name} is spectating"),
+            ServerEvent::Left { name, reason } => write!(f, "{name} left ({reason})"),
+            ServerEvent::RoundStarted { round, seed } => match seed {
+                Some(seed) => write!(f, "round {round} started on map {seed}"),
+                None => write!(f, "round {round} started"),
+            },
+            ServerEvent::RoundOver {
+                round,
+                scores,
+                winner,
+            } => {
+                let result =
+                    winner.map_or("draw".to_string(), |team| format!("{} wins", team.name()));
+                write!(f, "round {round}: {}, {result}", Team::scoreline(scores))
+            }
+        }
+    }
+}
+
+/// A connected human, for status displays. Spectators have no tank or
+/// team.
+#[derive(Clone, Debug)]
+pub struct PlayerInfo {
+    pub name: String,
+    pub addr: SocketAddr,
+    pub tank: Option<usize>,
+    pub team: Option<Team>,
+}
+
+struct RemotePlayer {
+    addr: SocketAddr,
+    name: String,
+    /// `None` for spectators, along with `tank`.
+    team: Option<Team>,
+    tank: Option<usize>,
+    queue: VecDeque<SequencedInput>,
+    newest_seq: Option<u32>,
+    applied_seq: Option<u32>,
+    input: PlayerInput,
+    ack: Option<u64>,
+    world_round: Option<u32>,
+    world_sent: Option<u64>,
+    silence: f32,
+}
+
+impl RemotePlayer {
+    /// A newly joined player, in `seat`'s team and tank or spectating.
+    fn new(addr: SocketAddr, name: String, seat: Option<(Team, usize)>) -> Self {
+        Self {
+            addr,
+            name,
+            team: seat.map(|(team, _)| team),
+            tank: seat.map(|(_, tank)| tank),
+            queue: VecDeque::new(),
+            newest_seq: None,
+            applied_seq: None,
+            input: PlayerInput::default(),
+            ack: None,
+            world_round: None,
+            world_sent: None,
+            silence: 0.0,
+        }
+    }
+}
+
+/// Runs the authoritative simulation for remote clients. Humans take
+/// tanks as they join, on the team with the fewest humans, or just watch;
+/// the AI drives the rest.
+pub struct Server {
+    link: Link,
+    game: Game,
+    tick: u64,
+    players: Vec<RemotePlayer>,
+    history: VecDeque<Snapshot>,
+    world_chunks: (u32, Vec<String>),
+    round_break: f32,
+    rotation: Vec<u64>,
+    rotation_next: usize,
+    map_seed: Option<u64>,
+    events: Vec<ServerEvent>,
+}
+
+impl Server {
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        config: GameConfig,
+        seed: u64,
+        conditions: LinkConditions,
+    ) -> io::Result<Self> {
+        let mut game = Game::with_config(seed, config);
+        game.set_ai_only(true);
+        let mut server = Self {
+            link: Link::bind(addr, conditions)?,
+            game,
+            tick: 0,
+            players: Vec::new(),
+            history: VecDeque::new(),
+            world_chunks: (0, Vec::new()),
+            round_break: ROUND_BREAK,
+            rotation: Vec::new(),
+            rotation_next: 0,
+            map_seed: None,
+            events: Vec::new(),
+        };
+        server.restart_round(Some(seed));
+        Ok(server)
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.link.local_addr()
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Seed of the current map, if it is known.
+    pub fn map_seed(&self) -> Option<u64> {
+        self.map_seed
+    }
+
+    /// Seeds for the maps of the following rounds, used in turn and then
+    /// repeated. Without any, each round's map follows from the last.
+    pub fn set_rotation(&mut self, seeds: Vec<u64>) {
+        self.rotation = seeds;
+        self.rotation_next = 0;
+    }
+
+    /// The rotation's seeds in the order they will be played.
+    pub fn rotation(&self) -> Vec<u64> {
+        let mut seeds = self.rotation.clone();
+        seeds.rotate_left(self.rotation_next);
+        seeds
+    }
+
+    /// Swaps in new tuning values and sends them to every client. Map and
+    /// team size apply from the next round.
+    pub fn set_config(&mut self, config: GameConfig) -> io::Result<()> {
+        self.game.set_config(config);
+        for index in 0..self.players.len() {
+            self.welcome(index)?;
+        }
+        Ok(())
+    }
+
+    /// Abandons the current round and deploys a new one on the map from
+    /// `seed`, or the next one in the rotation.
+    pub fn restart_round(&mut self, seed: Option<u64>) {
+        let seed = seed.or_else(|| self.next_seed());
+        if let Some(seed) = seed {
+            self.game.reseed(seed);
+        }
+        self.map_seed = seed;
+        self.game.start_round();
+        self.round_break = ROUND_BREAK;
+        self.events.push(ServerEvent::RoundStarted {
+            round: self.game.round(),
+            seed,
+        });
+        self.reseat();
+    }
+
+    fn next_seed(&mut self) -> Option<u64> {
+        let seed = *self.rotation.get(self.rotation_next)?;
+        self.rotation_next = (self.rotation_next + 1) % self.rotation.len();
+        Some(seed)
+    }
+
+    /// Removes player `index`, in [`Server::players`] order, and tells
+    /// their client why. Returns false if there is no such player.
+    pub fn kick(&mut self, index: usize, reason: &str) -> io::Result<bool> {
+        if index >= self.players.len() {
+            return Ok(false);
+        }
+        let player = self.players.remove(index);
+        let reason = reason.to_string();
+        let message = ServerMessage::Reject {
+            reason: reason.clone(),
+        };
+        self.events.push(ServerEvent::Left {
+            name: player.name,
+            reason: format!("kicked: {reason}"),
+        });
+        self.link.send(player.addr, &message)?;
+        Ok(true)
+    }
+
+    pub fn players(&self) -> Vec<PlayerInfo> {
+        self.players
+            .iter()
+            .map(|player| PlayerInfo {
+                name: player.name.clone(),
+                addr: player.addr,
+                tank: player.tank,
+                team: player.team,
+            })
+            .collect()
+    }
+
+    /// Ticks at [`TICK_RATE`] until `each_tick` returns false. It is handed
+    /// the server and whatever happened during the tick.
+    pub fn run(
+        &mut self,
+        mut each_tick: impl FnMut(&mut Server, Vec<ServerEvent>) -> bool,
+    ) -> io::Result<()> {
+        let tick_length = Duration::from_secs_f64(1.0 / TICK_RATE as f64);
+        let mut next = Instant::now();
+        loop {
+            let events = self.tick()?;
+            if !each_tick(self, events) {
+                return Ok(());
+            }
+            next += tick_length;
+            let now = Instant::now();
+            match next.checked_duration_since(now) {
+                Some(wait) => thread::sleep(wait),
+                // Too far behind to catch up: drop the missed ticks.
+                None if now - next > tick_length * TICK_RATE => next = now,
+                None => {}
+            }
+        }
+    }
+
+    /// Reads client packets, advances the game one tick and sends
+    /// snapshots when due. Returns what happened since the last call.
+    pub fn tick(&mut self) -> io::Result<Vec<ServerEvent>> {
+        self.receive()?;
+        let events = &mut self.events;
+        self.players.retain_mut(|player| {
+            player.silence += TICK_DT;
+            let alive = player.silence < CLIENT_TIMEOUT;
+            if !alive {
+                events.push(ServerEvent::Left {
+                    name: player.name.clone(),
+                    reason: "timed out".to_string(),
+                });
+            }
+            alive
+        });
+
+        let mut input = TickInput::default();
+        for player in &mut self.players {
+            if let Some(next) = player.queue.pop_front() {
+                player.input = next.input;
+                player.applied_seq = Some(next.seq);
+            }
+            if let Some(tank) = player.tank {
+                input.tanks.push(TankInput {
+                    tank,
+                    input: player.input,
+                });
+            }
+        }
+        if self.game.is_round_over() {
+            self.round_break -= TICK_DT;
+            if self.round_break <= 0.0 {
+                self.restart_round(None);
+            }
+        }
+
+        let was_playing = self.game.is_playing();
+        self.game.update(TICK_DT, &input);
+        if was_playing && self.game.is_round_over() {
+            self.events.push(ServerEvent::RoundOver {
+                round: self.game.round(),
+                scores: self.game.scores().to_vec(),
+                winner: self.game.last_winner(),
+            });
+        }
+
+        self.tick += 1;
+        if self.tick.is_multiple_of(SNAPSHOT_INTERVAL) {
+            self.broadcast()?;
+        }
+        Ok(mem::take(&mut self.events))
+    }
+
+    fn receive(&mut self) -> io::Result<()> {
+        while let Some((addr, message)) = self.link.recv::<ClientMessage>()? {
+            let known = self.players.iter().position(|player| player.addr == addr);
+            match (message, known) {
+                (
+                    ClientMessage::Hello {
+                        version,
+                        name,
+                        team,
+                        spectate,
+                    },
+                    None,
+                ) => self.join(addr, version, name, team, spectate)?,
+                // Our welcome was lost; say it again.
+                (ClientMessage::Hello { .. }, Some(index)) => self.welcome(index)?,
+                (ClientMessage::Input { inputs, ack, round }, Some(index)) => {
+                    let player = &mut self.players[index];
+                    player.silence = 0.0;
+                    player.ack = ack.max(player.ack);
+                    player.world_round = round;
+                    for input in inputs {
+                        if player.newest_seq.is_none_or(|newest| input.seq > newest) {
+                            player.newest_seq = Some(input.seq);
+                            player.queue.push_back(input);
+                        }
+                    }
+                    while player.queue.len() > MAX_QUEUED_INPUTS {
+                        player.queue.pop_front();
+                    }
+                }
+                (ClientMessage::Bye, Some(index)) => {
+                    let player = self.players.remove(index);
+                    self.events.push(ServerEvent::Left {
+                        name: player.name,
+                        reason: "disconnected".to_string(),
+                    });
+                }
+                (_, None) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn join(
+        &mut self,
+        addr: SocketAddr,
+        version: u32,
+        name: String,
+        team: Option<Team>,
+        spectate: bool,
+    ) -> io::Result<()> {
+        if version != PROTOCOL_VERSION {
+            let reason =
+                format!("server speaks protocol {PROTOCOL_VERSION}, client speaks {version}");
+            return self.link.send(addr, &ServerMessage::Reject { reason });
+        }
+        if spectate {
+            let name = if name.trim().is_empty() {
+                "spectator".to_string()
+            } else {
+                name.trim().to_string()
+            };
+            self.events
+                .push(ServerEvent::Watching { name: name.clone() });
+            self.players.push(RemotePlayer::new(addr, name, None));
+            return self.welcome(self.players.len() - 1);
+        }
+        let team = team.unwrap_or_else(|| {
+            let humans = |team| self.players.iter().filter(|p| p.team == Some(team)).count();
+            // The team with the fewest humans among those with a free tank.
+            self.game
+                .world()
+                .teams()
+                .min_by_key(|&team| (self.free_tank(team).is_none(), humans(team)))
+                .unwrap_or(Team::Red)
+        });
+        let Some(tank) = self.free_tank(team) else {
+            let reason = format!("{} has no free tanks", team.name());
+            return self.link.send(addr, &ServerMessage::Reject { reason });
+        };
+
+        let name = if name.trim().is_empty() {
+            format!("player{}", tank + 1)
+        } else {
+            name.trim().to_string()
+        };
+        self.events.push(ServerEvent::Joined {
+            name: name.clone(),
+            tank,
+            team,
+        });
+        self.players
+            .push(RemotePlayer::new(addr, name, Some((team, tank))));
+        self.welcome(self.players.len() - 1)
+    }
+
+    fn welcome(&mut self, index: usize) -> io::Result<()> {
+        let player = &self.players[index];
+        let config = self.game.config().clone();
+        let message = match (player.tank, player.team) {
+            (Some(tank), Some(team)) => ServerMessage::Welcome { tank, team, config },
+            _ => ServerMessage::Spectating { config },
+        };
+        self.link.send(player.addr, &message)
+    }
+
+    fn free_tank(&self, team: Team) -> Option<usize> {
+        self.game
+            .tanks()
+            .iter()
+            .enumerate()
+            .position(|(index, tank)| {
+                tank.team == team && !self.players.iter().any(|player| player.tank == Some(index))
+            })
+    }
+
+    /// Finds new tanks for players whose old one went away with a change
+    /// of team size, dropping those left without one.
+    fn reseat(&mut self) {
+        let mut index = 0;
+        while index < self.players.len() {
+            let (Some(tank), Some(team)) = (self.players[index].tank, self.players[index].team)
+            else {
+                index += 1;
+                continue;
+            };
+            let tanks = self.game.tanks();
+            let taken = |candidate: usize| {
+                self.players[..index]
+                    .iter()
+                    .any(|other| other.tank == Some(candidate))
+            };
+            let valid = tanks.get(tank).is_some_and(|tank| tank.team == team);
+            if valid && !taken(tank) {
+                index += 1;
+                continue;
+            }
+            let free = (0..tanks.len())
+                .find(|&candidate| tanks[candidate].team == team && !taken(candidate));
+            match free {
+                Some(tank) => {
+                    self.players[index].tank = Some(tank);
+                    // The client learns its new tank from a fresh welcome.
+                    let _ = self.welcome(index);
+                    index += 1;
+                }
+                None => {
+                    let player = self.players.remove(index);
+                    let reason = format!("{} has no free tanks", team.name());
+                    let message = ServerMessage::Reject {
+                        reason: reason.clone(),
+                    };
+                    let _ = self.link.send(player.addr, &message);
+                    self.events.push(ServerEvent::Left {
+                        name: player.name,
+                        reason,
+                    });
+                }
+            }
+        }
+    }
+
+    fn broadcast(&mut self) -> io::Result<()> {
+        let snapshot = self.game.snapshot(self.tick);
+        let round = self.game.round();
+        if self.world_chunks.0 != round || self.world_chunks.1.is_empty() {
+            let world = serde_json::to_string(self.game.world())?;
+            self.world_chunks = (round, split_chunks(&world, WORLD_CHUNK_SIZE));
+        }
+
+        for index in 0..self.players.len() {
+            let player = &self.players[index];
+            let addr = player.addr;
+            let needs_world = player.world_round != Some(round)
+                && player
+                    .world_sent
+                    .is_none_or(|sent| self.tick.saturating_sub(sent) >= WORLD_RESEND);
+            if needs_world {
+                let count = self.world_chunks.1.len();
+                for (chunk, data) in self.world_chunks.1.iter().enumerate() {
+                    let message = ServerMessage::WorldChunk {
+                        round,
+                        index: chunk,
+                        count,
+                        data: data.clone(),
+                    };
+                    self.link.send(addr, &message)?;
+                }
+                self.players[index].world_sent = Some(self.tick);
+            }
+
+            let player = &self.players[index];
+            let baseline = player
+                .ack
+                .and_then(|ack| self.history.iter().find(|old| old.tick() == ack));
+            let message = ServerMessage::Snapshot {
+                last_input: player.applied_seq,
+                delta: snapshot.delta(baseline, self.game.tracks()),
+            };
+            self.link.send(addr, &message)?;
+        }
+
+        self.history.push_back(snapshot);
+        while self.history.len() > SNAPSHOT_HISTORY {
+            self.history.pop_front();
+        }
+        Ok(())
+    }
+}
+
+/// Splits `data` into pieces of at most `size` bytes on character
+/// boundaries.
+fn split_chunks(data: &str, size: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let mut end = size.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        chunks.push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+    chunks
+}
@@ -0,0 +1,175 @@
+#[cfg(feature = "graphics")]
+mod viewer;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::game::{Game, LocalPlayer, TickInput};
+
+#[cfg(feature = "graphics")]
+pub use viewer::ReplayViewer;
+
+pub const REPLAY_FORMAT_VERSION: u32 = 2;
+pub const REPLAY_EXTENSION: &str = "tankreplay";
+
+/// A recorded session: the seed, config and local players the `Game` was
+/// created with plus every tick of input it consumed. Identical inputs in a
+/// row are stored as one run. Recordings that begin from a loaded save carry
+/// that state as a keyframe, and saves loaded later on and config hot
+/// reloads are stored at the tick they happened.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub format_version: u32,
+    pub game_version: String,
+    pub seed: u64,
+    pub config: GameConfig,
+    pub players: Vec<LocalPlayer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start_state: Option<Box<Game>>,
+    inputs: Vec<InputRun>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    config_changes: Vec<ConfigChange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    keyframes: Vec<Keyframe>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct InputRun {
+    ticks: u32,
+    input: TickInput,
+}
+
+/// A config swapped in before the input of `tick` was applied.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ConfigChange {
+    tick: usize,
+    config: GameConfig,
+}
+
+/// A state play jumped to before the input of `tick`, e.g. a quickload.
+#[derive(Clone, Serialize, Deserialize)]
+struct Keyframe {
+    tick: usize,
+    state: Box<Game>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, config: &GameConfig, players: &[LocalPlayer]) -> Self {
+        Self {
+            format_version: REPLAY_FORMAT_VERSION,
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            seed,
+            config: config.clone(),
+            players: players.to_vec(),
+            start_state: None,
+            inputs: Vec::new(),
+            config_changes: Vec::new(),
+            keyframes: Vec::new(),
+        }
+    }
+
+    /// Starts a recording from an existing mid-session state.
+    pub fn from_state(seed: u64, game: &Game) -> Self {
+        Self {
+            start_state: Some(Box::new(game.clone())),
+            ..Self::new(seed, game.config(), game.local_players())
+        }
+    }
+
+    /// The state playback starts from.
+    pub fn initial_game(&self) -> Game {
+        match &self.start_state {
+            Some(game) => game.as_ref().clone(),
+            None => {
+                let mut game = Game::with_config(self.seed, self.config.clone());
+                game.set_local_players(self.players.clone());
+                game
+            }
+        }
+    }
+
+    /// Notes that the config changed before the next recorded tick.
+    pub fn record_config(&mut self, config: &GameConfig) {
+        let tick = self.tick_count();
+        self.config_changes.retain(|change| change.tick != tick);
+        self.config_changes.push(ConfigChange {
+            tick,
+            config: config.clone(),
+        });
+    }
+
+    /// The config that takes effect right before the input of `tick`.
+    pub fn config_change_at(&self, tick: usize) -> Option<&GameConfig> {
+        self.config_changes
+            .iter()
+            .find(|change| change.tick == tick)
+            .map(|change| &change.config)
+    }
+
+    /// Notes that play jumped to `game` before the next recorded tick.
+    pub fn record_state(&mut self, game: &Game) {
+        let tick = self.tick_count();
+        self.keyframes.retain(|keyframe| keyframe.tick != tick);
+        self.keyframes.push(Keyframe {
+            tick,
+            state: Box::new(game.clone()),
+        });
+    }
+
+    /// The state play jumps to right before the input of `tick`.
+    pub fn state_at(&self, tick: usize) -> Option<&Game> {
+        self.keyframes
+            .iter()
+            .find(|keyframe| keyframe.tick == tick)
+            .map(|keyframe| keyframe.state.as_ref())
+    }
+
+    pub fn record(&mut self, input: &TickInput) {
+        match self.inputs.last_mut() {
+            Some(run) if run.input == *input => run.ticks += 1,
+            _ => self.inputs.push(InputRun {
+                ticks: 1,
+                input: input.clone(),
+            }),
+        }
+    }
+
+    pub fn tick_count(&self) -> usize {
+        self.inputs.iter().map(|run| run.ticks as usize).sum()
+    }
+
+    /// The recorded inputs expanded back to one entry per tick.
+    pub fn inputs(&self) -> impl Iterator<Item = TickInput> + '_ {
+        self.inputs
+            .iter()
+            .flat_map(|run| std::iter::repeat_n(run.input.clone(), run.ticks as usize))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_string(self)?;
+        fs::write(path, json)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        let replay: Replay = serde_json::from_str(&json)?;
+        if replay.format_version != REPLAY_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported replay format {} (expected {})",
+                    replay.format_version, REPLAY_FORMAT_VERSION
+                ),
+            ));
+        }
+        Ok(replay)
+    }
+
+    pub fn matches_game_version(&self) -> bool {
+        self.game_version == env!("CARGO_PKG_VERSION")
+    }
+}
@@ -0,0 +1,288 @@
+use raylib::prelude::{
+    Color, KeyboardKey, MouseButton, RaylibDraw, RaylibHandle, Rectangle, Vector2,
+};
+
+use crate::assets::Assets;
+use crate::config::{TICK_DT, TICK_RATE};
+use crate::game::{CameraView, FixedTimestep, Game, TickInput};
+use crate::math::{point_in_bounds, vec2, vec2_add, vec2_scale};
+
+use super::Replay;
+
+const SNAPSHOT_INTERVAL: usize = 5 * TICK_RATE as usize;
+const SPEEDS: [f32; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const NORMAL_SPEED: usize = 2;
+const SEEK_STEP_SECONDS: f32 = 5.0;
+const PAN_SPEED: f32 = 900.0;
+const MIN_ZOOM: f32 = 0.1;
+const MAX_ZOOM: f32 = 2.0;
+const PANEL_HEIGHT: i32 = 64;
+
+/// Plays a [`Replay`] back with pause, single-tick stepping, variable speed
+/// and seeking. Seeks restore the nearest earlier snapshot and re-simulate
+/// the remaining ticks, so any point in the recording is reachable.
+pub struct ReplayViewer {
+    replay: Replay,
+    inputs: Vec<TickInput>,
+    game: Game,
+    tick: usize,
+    snapshots: Vec<Game>,
+    timestep: FixedTimestep,
+    paused: bool,
+    speed_index: usize,
+    free_camera: Option<(Vector2, f32)>,
+}
+
+impl ReplayViewer {
+    pub fn new(replay: &Replay) -> Self {
+        let game = replay.initial_game();
+        Self {
+            replay: replay.clone(),
+            inputs: replay.inputs().collect(),
+            snapshots: vec![game.clone()],
+            game,
+            tick: 0,
+            timestep: FixedTimestep::new(),
+            paused: false,
+            speed_index: NORMAL_SPEED,
+            free_camera: None,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn update(&mut self, rl: &RaylibHandle, screen_width: i32, screen_height: i32) {
+        let frame_dt = rl.get_frame_time();
+        self.handle_controls(rl, frame_dt, screen_width, screen_height);
+        if self.paused {
+            return;
+        }
+
+        let mut ticks = 0;
+        let speed = SPEEDS[self.speed_index];
+        self.timestep
+            .advance(frame_dt * speed, TickInput::default(), |_| ticks += 1);
+        for _ in 0..ticks {
+            if !self.step() {
+                self.paused = true;
+                break;
+            }
+        }
+    }
+
+    pub fn draw<D: RaylibDraw>(
+        &self,
+        d: &mut D,
+        assets: &Assets,
+        screen_width: i32,
+        screen_height: i32,
+    ) {
+        let blend = if self.paused {
+            1.0
+        } else {
+            self.timestep.blend()
+        };
+        let view = match self.free_camera {
+            Some((center, zoom)) => CameraView::Free { center, zoom },
+            None => CameraView::Player,
+        };
+        self.game
+            .draw(d, assets, screen_width, screen_height, blend, view);
+        self.draw_panel(d, screen_width, screen_height);
+    }
+
+    fn handle_controls(
+        &mut self,
+        rl: &RaylibHandle,
+        frame_dt: f32,
+        screen_width: i32,
+        screen_height: i32,
+    ) {
+        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            self.paused = !self.paused;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_PERIOD) {
+            self.paused = true;
+            self.step();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_COMMA) {
+            self.paused = true;
+            self.seek(self.tick.saturating_sub(1));
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.speed_index = (self.speed_index + 1).min(SPEEDS.len() - 1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.speed_index = self.speed_index.saturating_sub(1);
+        }
+
+        let seek_step = (SEEK_STEP_SECONDS * TICK_RATE as f32) as usize;
+        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+            self.seek(self.tick + seek_step);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+            self.seek(self.tick.saturating_sub(seek_step));
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_HOME) {
+            self.seek(0);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_END) {
+            self.seek(self.inputs.len());
+        }
+
+        let timeline = timeline_rect(screen_width, screen_height);
+        let grab_area = Rectangle {
+            x: timeline.x,
+            y: timeline.y - 8.0,
+            width: timeline.width,
+            height: timeline.height + 16.0,
+        };
+        let mouse = rl.get_mouse_position();
+        if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT)
+            && point_in_bounds(mouse, &grab_area)
+        {
+            let fraction = ((mouse.x - timeline.x) / timeline.width).clamp(0.0, 1.0);
+            self.seek((fraction * self.inputs.len() as f32) as usize);
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_F) {
+            self.free_camera = match self.free_camera {
+                Some(_) => None,
+                None => Some((
+                    self.game.player_focus(Some(0), 1.0),
+                    CameraView::DEFAULT_ZOOM,
+                )),
+            };
+        }
+        if let Some((center, zoom)) = &mut self.free_camera {
+            let mut pan = vec2(0.0, 0.0);
+            if rl.is_key_down(KeyboardKey::KEY_A) {
+                pan.x -= 1.0;
+            }
+            if rl.is_key_down(KeyboardKey::KEY_D) {
+                pan.x += 1.0;
+            }
+            if rl.is_key_down(KeyboardKey::KEY_W) {
+                pan.y -= 1.0;
+            }
+            if rl.is_key_down(KeyboardKey::KEY_S) {
+                pan.y += 1.0;
+            }
+            *center = vec2_add(*center, vec2_scale(pan, PAN_SPEED * frame_dt / *zoom));
+            if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT) {
+                let drag = rl.get_mouse_delta();
+                *center = vec2_add(*center, vec2_scale(drag, -1.0 / *zoom));
+            }
+            let wheel = rl.get_mouse_wheel_move();
+            if wheel.abs() > 0.0 {
+                *zoom = (*zoom * 1.1f32.powf(wheel)).clamp(MIN_ZOOM, MAX_ZOOM);
+            }
+        }
+    }
+
+    /// Advances one recorded tick. Returns `false` once the recording ends.
+    fn step(&mut self) -> bool {
+        let Some(input) = self.inputs.get(self.tick).cloned() else {
+            return false;
+        };
+        if let Some(config) = self.replay.config_change_at(self.tick) {
+            self.game.set_config(config.clone());
+        }
+        if let Some(state) = self.replay.state_at(self.tick) {
+            self.game = state.clone();
+        }
+        self.game.update(TICK_DT, &input);
+        self.tick += 1;
+        if self.tick == self.snapshots.len() * SNAPSHOT_INTERVAL {
+            self.snapshots.push(self.game.clone());
+        }
+        true
+    }
+
+    fn seek(&mut self, target: usize) {
+        let target = target.min(self.inputs.len());
+        let index = (target / SNAPSHOT_INTERVAL).min(self.snapshots.len() - 1);
+        let snapshot_tick = index * SNAPSHOT_INTERVAL;
+        if target < self.tick || snapshot_tick > self.tick {
+            self.game = self.snapshots[index].clone();
+            self.tick = snapshot_tick;
+        }
+        while self.tick < target && self.step() {}
+    }
+
+    fn draw_panel<D: RaylibDraw>(&self, d: &mut D, screen_width: i32, screen_height: i32) {
+        let top = screen_height - PANEL_HEIGHT;
+        d.draw_rectangle(
+            0,
+            top,
+            screen_width,
+            PANEL_HEIGHT,
+            Color::new(20, 24, 28, 220),
+        );
+
+        let timeline = timeline_rect(screen_width, screen_height);
+        d.draw_rectangle_rec(timeline, Color::new(70, 74, 80, 255));
+        let progress = if self.inputs.is_empty() {
+            0.0
+        } else {
+            self.tick as f32 / self.inputs.len() as f32
+        };
+        d.draw_rectangle_rec(
+            Rectangle {
+                width: timeline.width * progress,
+                ..timeline
+            },
+            Color::new(240, 200, 110, 255),
+        );
+        for index in 0..self.snapshots.len() {
+            let x = timeline.x
+                + timeline.width * (index * SNAPSHOT_INTERVAL) as f32
+                    / self.inputs.len().max(1) as f32;
+            d.draw_rectangle(
+                x as i32,
+                (timeline.y + timeline.height) as i32,
+                1,
+                4,
+                Color::new(200, 200, 200, 160),
+            );
+        }
+
+        let mut status = format!(
+            "{} / {}   {}x",
+            format_ticks(self.tick),
+            format_ticks(self.inputs.len()),
+            SPEEDS[self.speed_index]
+        );
+        if self.paused {
+            status.push_str("   PAUSED");
+        }
+        if self.free_camera.is_some() {
+            status.push_str("   FREE CAMERA");
+        }
+        d.draw_text(&status, 20, top + 8, 20, Color::new(240, 240, 240, 255));
+        d.draw_text(
+            "Space pause • ,/. step • Left/Right seek • Up/Down speed • F free camera (WASD, wheel, RMB drag)",
+            20,
+            top + 42,
+            14,
+            Color::new(200, 200, 200, 220),
+        );
+    }
+}
+
+fn timeline_rect(screen_width: i32, screen_height: i32) -> Rectangle {
+    Rectangle {
+        x: 20.0,
+        y: (screen_height - PANEL_HEIGHT + 32) as f32,
+        width: (screen_width - 40) as f32,
+        height: 6.0,
+    }
+}
+
+fn format_ticks(ticks: usize) -> String {
+    let seconds = ticks as f32 * TICK_DT;
+    let minutes = (seconds / 60.0).floor() as u32;
+    format!("{minutes}:{:04.1}", seconds - minutes as f32 * 60.0)
+}
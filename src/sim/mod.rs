@@ -0,0 +1,167 @@
+mod report;
+
+use serde::Serialize;
+
+use crate::config::{GameConfig, GameMode, MapSide, TICK_DT};
+use crate::entities::{PowerupKind, Team};
+use crate::game::{Game, GameEvent, TickInput};
+
+pub use report::{BatchReport, BatchSummary, PowerupSummary, SideSummary, TeamSummary};
+
+pub const POWERUP_KINDS: [PowerupKind; 3] = [
+    PowerupKind::Invincible,
+    PowerupKind::RapidRange,
+    PowerupKind::Heal,
+];
+
+/// What happened in one AI-vs-AI round.
+#[derive(Clone, Debug, Serialize)]
+pub struct RoundStats {
+    pub seed: u64,
+    pub red_side: MapSide,
+    pub winner: Option<Team>,
+    /// Kills per team, indexed by [`Team::index`].
+    pub kills: Vec<u32>,
+    /// Flags captured per team in capture-the-flag rounds, empty otherwise.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub captures: Vec<u32>,
+    /// Powerups collected per team, indexed like [`POWERUP_KINDS`].
+    pub pickups: Vec<[u32; 3]>,
+    /// Seconds from a tank's first hit to its death, one entry per kill.
+    pub time_to_kill: Vec<f32>,
+}
+
+impl RoundStats {
+    /// The side the winner started on. Only two-team rounds have sides.
+    pub fn winner_side(&self) -> Option<MapSide> {
+        if self.kills.len() != 2 {
+            return None;
+        }
+        match self.winner? {
+            Team::Red => Some(self.red_side),
+            _ => Some(self.red_side.opposite()),
+        }
+    }
+
+    pub fn mean_time_to_kill(&self) -> Option<f32> {
+        mean(&self.time_to_kill)
+    }
+}
+
+/// Plays `rounds` rounds starting at `first_seed`. Consecutive rounds share a
+/// seed with the spawn sides swapped, so map-side bias can be told apart from
+/// team bias.
+pub fn simulate_batch(first_seed: u64, rounds: u32, config: &GameConfig) -> Vec<RoundStats> {
+    (0..rounds)
+        .map(|round| {
+            let mut config = config.clone();
+            if round % 2 == 1 {
+                config.map.red_side = config.map.red_side.opposite();
+            }
+            simulate_round(first_seed.wrapping_add(round as u64 / 2), config)
+        })
+        .collect()
+}
+
+/// Plays a single round with every tank under AI control.
+pub fn simulate_round(seed: u64, config: GameConfig) -> RoundStats {
+    let red_side = config.map.red_side;
+    let mut game = Game::with_config(seed, config);
+    game.set_ai_only(true);
+    game.start_round();
+
+    let mut stats = RoundStats {
+        seed,
+        red_side,
+        winner: None,
+        kills: Vec::new(),
+        captures: Vec::new(),
+        pickups: vec![[0; 3]; game.team_kills().len()],
+        time_to_kill: Vec::new(),
+    };
+    let mut first_hit = vec![None; game.tanks().len()];
+    let mut elapsed = 0.0;
+    while game.is_playing() {
+        game.update(TICK_DT, &TickInput::default());
+        elapsed += TICK_DT;
+        for event in game.events() {
+            match *event {
+                GameEvent::Damage { victim, .. } => {
+                    first_hit[victim].get_or_insert(elapsed);
+                }
+                GameEvent::Kill { victim, .. } => {
+                    if let Some(hit) = first_hit[victim].take() {
+                        stats.time_to_kill.push(elapsed - hit);
+                    }
+                }
+                GameEvent::Pickup { tank, kind } => {
+                    if kind == PowerupKind::Heal {
+                        first_hit[tank] = None;
+                    }
+                    let team = game.tanks()[tank].team.index();
+                    stats.pickups[team][kind_index(kind)] += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    stats.kills = game.team_kills().to_vec();
+    if game.mode() == GameMode::CaptureTheFlag {
+        stats.captures = game.scores().to_vec();
+    }
+    stats.winner = game.last_winner();
+    stats
+}
+
+fn kind_index(kind: PowerupKind) -> usize {
+    POWERUP_KINDS
+        .iter()
+        .position(|other| *other == kind)
+        .unwrap_or(0)
+}
+
+fn mean(values: &[f32]) -> Option<f32> {
+    (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quick_config() -> GameConfig {
+        let mut config = GameConfig::default();
+        config.round.intro_time = 0.0;
+        config.round.countdown = 0.0;
+        config.round.time = 20.0;
+        config
+    }
+
+    #[test]
+    fn a_batch_replays_identically_from_its_seed() {
+        let config = quick_config();
+        let first = simulate_batch(3, 4, &config);
+        let second = simulate_batch(3, 4, &config);
+        assert_eq!(
+            serde_json::to_string(&first).unwrap(),
+            serde_json::to_string(&second).unwrap()
+        );
+    }
+
+    #[test]
+    fn paired_rounds_share_a_seed_with_sides_swapped() {
+        let config = quick_config();
+        let rounds = simulate_batch(10, 4, &config);
+        let seeds: Vec<u64> = rounds.iter().map(|round| round.seed).collect();
+        assert_eq!(seeds, [10, 10, 11, 11]);
+        for pair in rounds.chunks(2) {
+            assert_eq!(pair[0].red_side, config.map.red_side);
+            assert_eq!(pair[1].red_side, config.map.red_side.opposite());
+        }
+        for round in &rounds {
+            assert_eq!(round.kills.len(), 2);
+            assert_eq!(round.pickups.len(), 2);
+            assert!(round.captures.is_empty());
+        }
+    }
+}
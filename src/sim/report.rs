@@ -0,0 +1,397 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::config::MapSide;
+use crate::entities::{PowerupKind, Team};
+
+use super::{POWERUP_KINDS, RoundStats, mean};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TeamSummary {
+    pub team: Team,
+    pub wins: u32,
+    pub win_rate: f32,
+    pub avg_kills: f32,
+    /// Only when the rounds were capture the flag.
+    pub avg_captures: Option<f32>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SideSummary {
+    pub left_wins: u32,
+    pub right_wins: u32,
+    /// Share of decisive two-team rounds won from the left spawn zone.
+    pub left_win_rate: Option<f32>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PowerupSummary {
+    pub kind: PowerupKind,
+    pub pickups: u32,
+    pub avg_per_round: f32,
+    /// Decisive rounds where one team collected more of this kind.
+    pub advantage_rounds: u32,
+    pub advantage_wins: u32,
+    /// How often that team went on to win.
+    pub advantage_win_rate: Option<f32>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchSummary {
+    pub rounds: usize,
+    pub stalemates: u32,
+    pub teams: Vec<TeamSummary>,
+    pub sides: SideSummary,
+    pub powerups: Vec<PowerupSummary>,
+    pub avg_time_to_kill: Option<f32>,
+}
+
+/// Per-round results plus the aggregate, as written by `--format json`.
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchReport {
+    pub summary: BatchSummary,
+    pub rounds: Vec<RoundStats>,
+}
+
+impl BatchReport {
+    pub fn new(rounds: Vec<RoundStats>) -> Self {
+        Self {
+            summary: BatchSummary::new(&rounds),
+            rounds,
+        }
+    }
+
+    /// One row per round.
+    pub fn to_csv(&self) -> String {
+        let teams = &Team::ALL[..self.summary.teams.len()];
+        let mut out = String::from("seed,red_side,winner,winner_side");
+        let ctf = self.rounds.iter().any(|round| !round.captures.is_empty());
+        for &team in teams {
+            write!(out, ",{}_kills", team_key(team)).unwrap();
+        }
+        if ctf {
+            for &team in teams {
+                write!(out, ",{}_captures", team_key(team)).unwrap();
+            }
+        }
+        for &team in teams {
+            for kind in POWERUP_KINDS {
+                write!(out, ",{}_{}", team_key(team), kind_key(kind)).unwrap();
+            }
+        }
+        out.push_str(",avg_time_to_kill\n");
+
+        for round in &self.rounds {
+            write!(
+                out,
+                "{},{},{},{}",
+                round.seed,
+                side_key(round.red_side),
+                round.winner.map_or("", team_key),
+                round.winner_side().map_or("", side_key),
+            )
+            .unwrap();
+            for kills in &round.kills {
+                write!(out, ",{kills}").unwrap();
+            }
+            if ctf {
+                for team in 0..teams.len() {
+                    let captures = round.captures.get(team).copied().unwrap_or(0);
+                    write!(out, ",{captures}").unwrap();
+                }
+            }
+            for team in &round.pickups {
+                for count in team {
+                    write!(out, ",{count}").unwrap();
+                }
+            }
+            match round.mean_time_to_kill() {
+                Some(ttk) => writeln!(out, ",{ttk:.3}").unwrap(),
+                None => out.push_str(",\n"),
+            }
+        }
+        out
+    }
+
+    pub fn to_text(&self) -> String {
+        let summary = &self.summary;
+        let mut out = String::new();
+        writeln!(
+            out,
+            "{} rounds, {} stalemates",
+            summary.rounds, summary.stalemates
+        )
+        .unwrap();
+        for team in &summary.teams {
+            write!(
+                out,
+                "{:<8} wins {:>4} ({:>5.1}%)  avg kills {:.2}",
+                team.team.name(),
+                team.wins,
+                team.win_rate * 100.0,
+                team.avg_kills
+            )
+            .unwrap();
+            match team.avg_captures {
+                Some(captures) => writeln!(out, "  avg captures {captures:.2}").unwrap(),
+                None => out.push('\n'),
+            }
+        }
+        // Sides only mean something when two teams face each other.
+        if summary.teams.len() == 2 {
+            writeln!(
+                out,
+                "left side wins {}, right side wins {} (left {})",
+                summary.sides.left_wins,
+                summary.sides.right_wins,
+                percent(summary.sides.left_win_rate)
+            )
+            .unwrap();
+        }
+        for powerup in &summary.powerups {
+            writeln!(
+                out,
+                "{:<11} pickups {:>5} ({:.2}/round)  leader won {} of {} rounds ({})",
+                kind_key(powerup.kind),
+                powerup.pickups,
+                powerup.avg_per_round,
+                powerup.advantage_wins,
+                powerup.advantage_rounds,
+                percent(powerup.advantage_win_rate)
+            )
+            .unwrap();
+        }
+        match summary.avg_time_to_kill {
+            Some(ttk) => writeln!(out, "avg time to kill {ttk:.2}s").unwrap(),
+            None => out.push_str("avg time to kill n/a\n"),
+        }
+        out
+    }
+}
+
+impl BatchSummary {
+    pub fn new(rounds: &[RoundStats]) -> Self {
+        let count = rounds.len().max(1) as f32;
+        let team_count = rounds.iter().map(|round| round.kills.len()).max();
+        let teams = Team::ALL[..team_count.unwrap_or(2)]
+            .iter()
+            .map(|&team| {
+                let wins = rounds
+                    .iter()
+                    .filter(|round| round.winner == Some(team))
+                    .count() as u32;
+                let kills: u32 = rounds
+                    .iter()
+                    .filter_map(|round| round.kills.get(team.index()))
+                    .sum();
+                let captures: u32 = rounds
+                    .iter()
+                    .filter_map(|round| round.captures.get(team.index()))
+                    .sum();
+                let ctf = rounds.iter().any(|round| !round.captures.is_empty());
+                TeamSummary {
+                    team,
+                    wins,
+                    win_rate: wins as f32 / count,
+                    avg_kills: kills as f32 / count,
+                    avg_captures: ctf.then_some(captures as f32 / count),
+                }
+            })
+            .collect();
+
+        let side_wins = |side| {
+            rounds
+                .iter()
+                .filter(|round| round.winner_side() == Some(side))
+                .count() as u32
+        };
+        let left_wins = side_wins(MapSide::Left);
+        let right_wins = side_wins(MapSide::Right);
+        let stalemates = rounds.iter().filter(|round| round.winner.is_none()).count() as u32;
+
+        let powerups = POWERUP_KINDS
+            .iter()
+            .enumerate()
+            .map(|(index, &kind)| powerup_summary(rounds, index, kind, count))
+            .collect();
+
+        let all_kills: Vec<f32> = rounds
+            .iter()
+            .flat_map(|round| round.time_to_kill.iter().copied())
+            .collect();
+
+        Self {
+            rounds: rounds.len(),
+            stalemates,
+            teams,
+            sides: SideSummary {
+                left_wins,
+                right_wins,
+                left_win_rate: ratio(left_wins, left_wins + right_wins),
+            },
+            powerups,
+            avg_time_to_kill: mean(&all_kills),
+        }
+    }
+}
+
+fn powerup_summary(
+    rounds: &[RoundStats],
+    index: usize,
+    kind: PowerupKind,
+    count: f32,
+) -> PowerupSummary {
+    let pickups: u32 = rounds
+        .iter()
+        .flat_map(|round| round.pickups.iter().map(|team| team[index]))
+        .sum();
+    let mut advantage_rounds = 0;
+    let mut advantage_wins = 0;
+    for round in rounds {
+        let Some(winner) = round.winner else {
+            continue;
+        };
+        let counts: Vec<u32> = round.pickups.iter().map(|team| team[index]).collect();
+        let Some(leader) = Team::leader(&counts) else {
+            continue;
+        };
+        advantage_rounds += 1;
+        if leader == winner {
+            advantage_wins += 1;
+        }
+    }
+    PowerupSummary {
+        kind,
+        pickups,
+        avg_per_round: pickups as f32 / count,
+        advantage_rounds,
+        advantage_wins,
+        advantage_win_rate: ratio(advantage_wins, advantage_rounds),
+    }
+}
+
+fn ratio(part: u32, total: u32) -> Option<f32> {
+    (total > 0).then(|| part as f32 / total as f32)
+}
+
+fn percent(rate: Option<f32>) -> String {
+    rate.map_or_else(|| "n/a".to_string(), |rate| format!("{:.1}%", rate * 100.0))
+}
+
+fn team_key(team: Team) -> &'static str {
+    match team {
+        Team::Red => "red",
+        Team::Blue => "blue",
+        Team::Green => "green",
+        Team::Beige => "beige",
+        Team::Black => "black",
+    }
+}
+
+fn side_key(side: MapSide) -> &'static str {
+    match side {
+        MapSide::Left => "left",
+        MapSide::Right => "right",
+    }
+}
+
+fn kind_key(kind: PowerupKind) -> &'static str {
+    match kind {
+        PowerupKind::Invincible => "invincible",
+        PowerupKind::RapidRange => "rapid_range",
+        PowerupKind::Heal => "heal",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(seed: u64, red_side: MapSide, winner: Option<Team>, kills: [u32; 2]) -> RoundStats {
+        RoundStats {
+            seed,
+            red_side,
+            winner,
+            kills: kills.to_vec(),
+            captures: Vec::new(),
+            pickups: vec![[1, 0, 2], [0, 0, 1]],
+            time_to_kill: vec![2.0, 4.0],
+        }
+    }
+
+    fn batch() -> BatchReport {
+        BatchReport::new(vec![
+            round(1, MapSide::Left, Some(Team::Red), [5, 2]),
+            round(1, MapSide::Right, Some(Team::Red), [4, 3]),
+            round(2, MapSide::Left, None, [3, 3]),
+        ])
+    }
+
+    #[test]
+    fn the_summary_adds_up_the_rounds() {
+        let summary = batch().summary;
+        assert_eq!(summary.rounds, 3);
+        assert_eq!(summary.stalemates, 1);
+
+        let red = &summary.teams[0];
+        assert_eq!((red.team, red.wins), (Team::Red, 2));
+        assert_eq!(red.avg_kills, 4.0);
+        assert_eq!(red.avg_captures, None);
+        let blue = &summary.teams[1];
+        assert_eq!((blue.team, blue.wins), (Team::Blue, 0));
+        assert_eq!(blue.avg_kills, 8.0 / 3.0);
+
+        assert_eq!(summary.sides.left_wins, 1);
+        assert_eq!(summary.sides.right_wins, 1);
+        assert_eq!(summary.sides.left_win_rate, Some(0.5));
+
+        let invincible = &summary.powerups[0];
+        assert_eq!(invincible.pickups, 3);
+        assert_eq!(invincible.advantage_rounds, 2);
+        assert_eq!(invincible.advantage_win_rate, Some(1.0));
+        let rapid = &summary.powerups[1];
+        assert_eq!(rapid.advantage_rounds, 0);
+        assert_eq!(rapid.advantage_win_rate, None);
+
+        assert_eq!(summary.avg_time_to_kill, Some(3.0));
+    }
+
+    #[test]
+    fn the_csv_has_a_row_per_round() {
+        let csv = batch().to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "seed,red_side,winner,winner_side,red_kills,blue_kills,\
+             red_invincible,red_rapid_range,red_heal,\
+             blue_invincible,blue_rapid_range,blue_heal,avg_time_to_kill"
+        );
+        assert_eq!(lines[1], "1,left,red,left,5,2,1,0,2,0,0,1,3.000");
+        assert_eq!(lines[2], "1,right,red,right,4,3,1,0,2,0,0,1,3.000");
+        assert_eq!(lines[3], "2,left,,,3,3,1,0,2,0,0,1,3.000");
+        assert_eq!(lines.len(), 4);
+        let columns = lines[0].split(',').count();
+        assert!(lines.iter().all(|line| line.split(',').count() == columns));
+    }
+
+    #[test]
+    fn capture_columns_appear_only_for_flag_rounds() {
+        let mut flag = round(4, MapSide::Left, Some(Team::Blue), [1, 2]);
+        flag.captures = vec![0, 3];
+        let report = BatchReport::new(vec![flag]);
+        assert_eq!(report.summary.teams[1].avg_captures, Some(3.0));
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .contains(",red_captures,blue_captures,")
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            "4,left,blue,right,1,2,0,3,1,0,2,0,0,1,3.000"
+        );
+    }
+}
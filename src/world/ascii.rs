@@ -0,0 +1,76 @@
+use crate::config::TILE_SIZE;
+use crate::entities::Team;
+use crate::math::vec2;
+
+use super::{ObstacleKind, TileKind, World};
+
+impl World {
+    /// A one-character-per-tile picture of the map for terminals and bug
+    /// reports. Obstacles draw over spawn zones, which draw over terrain.
+    pub fn to_ascii(&self) -> String {
+        let mut grid: Vec<Vec<char>> = (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| {
+                        let center =
+                            vec2((x as f32 + 0.5) * TILE_SIZE, (y as f32 + 0.5) * TILE_SIZE);
+                        match self.spawn_zones.iter().find(|zone| zone.contains(center)) {
+                            Some(zone) => zone_char(zone.team),
+                            None => tile_char(self.tile_kind(x, y)),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for obstacle in &self.obstacles {
+            let x = (obstacle.pos.x / TILE_SIZE) as i32;
+            let y = (obstacle.pos.y / TILE_SIZE) as i32;
+            if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
+                grid[y as usize][x as usize] = obstacle_char(obstacle.kind);
+            }
+        }
+
+        let mut out = String::with_capacity(((self.width + 1) * self.height) as usize);
+        for row in grid {
+            out.extend(row);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn zone_char(team: Team) -> char {
+    match team {
+        Team::Red => 'r',
+        Team::Blue => 'b',
+        Team::Green => 'g',
+        Team::Beige => 'e',
+        Team::Black => 'k',
+    }
+}
+
+fn tile_char(kind: TileKind) -> char {
+    match kind {
+        TileKind::Grass => '.',
+        TileKind::Dirt => ',',
+        TileKind::Sand => '~',
+    }
+}
+
+fn obstacle_char(kind: ObstacleKind) -> char {
+    match kind {
+        ObstacleKind::TreeSmall => 't',
+        ObstacleKind::TreeLarge => 'T',
+        ObstacleKind::SandbagBrown | ObstacleKind::SandbagBeige => '#',
+        ObstacleKind::Oil => 'o',
+        ObstacleKind::BarrelRedUp
+        | ObstacleKind::BarrelRedSide
+        | ObstacleKind::BarrelGreyUp
+        | ObstacleKind::BarrelGreySide
+        | ObstacleKind::BarrelGreyRust
+        | ObstacleKind::BarrelGreenUp
+        | ObstacleKind::BarrelGreenSide
+        | ObstacleKind::BarrelGreenSideDamaged => '0',
+    }
+}
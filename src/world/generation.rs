@@ -1,19 +1,23 @@
-use rand::rngs::SmallRng;
-use raylib::prelude::Rectangle;
+use std::f32::consts::{PI, TAU};
 
-use crate::config::{MAP_HEIGHT, MAP_WIDTH, TILE_SIZE};
+use crate::config::{MapConfig, MapSide, TILE_SIZE};
 use crate::entities::Team;
+use crate::math::{GameRng, Rectangle};
 
 use super::obstacles;
 use super::tiles;
-use super::{SpawnZone, World};
+use super::{NavGrid, SpawnZone, World};
 
-pub(super) fn generate_world(rng: &mut SmallRng) -> World {
-    let width = MAP_WIDTH;
-    let height = MAP_HEIGHT;
+pub(super) fn generate_world(rng: &mut GameRng, map: &MapConfig, teams: &[Team]) -> World {
+    let width = map.width;
+    let height = map.height;
 
     let mut tiles = tiles::generate_tiles(rng, width, height);
-    let spawn_zones = spawn_zones(width, height);
+    let mut spawn_zones = match teams {
+        [red, blue] => facing_zones(width, height, map.red_side, *red, *blue),
+        _ => ringed_zones(width, height, map.red_side, teams),
+    };
+    spawn_zones.sort_by_key(|zone| zone.team.index());
     tiles::paint_spawn_zones(&mut tiles, width, &spawn_zones);
 
     let mut world = World {
@@ -22,16 +26,29 @@ pub(super) fn generate_world(rng: &mut SmallRng) -> World {
         tiles,
         obstacles: Vec::new(),
         spawn_zones,
+        nav: NavGrid::default(),
     };
     obstacles::generate_obstacles(&mut world, rng);
+    world.nav = NavGrid::build(&world);
     world
 }
 
-fn spawn_zones(width: i32, height: i32) -> [SpawnZone; 2] {
+/// Two zones facing each other across the middle of the map.
+fn facing_zones(
+    width: i32,
+    height: i32,
+    red_side: MapSide,
+    red: Team,
+    blue: Team,
+) -> Vec<SpawnZone> {
     let zone_w = 7;
     let zone_h = 8;
     let zone_y = (height - zone_h) / 2;
 
+    let (left_team, right_team) = match red_side {
+        MapSide::Left => (red, blue),
+        MapSide::Right => (blue, red),
+    };
     let left_zone = SpawnZone {
         rect: Rectangle {
             x: 2.0 * TILE_SIZE,
@@ -39,7 +56,7 @@ fn spawn_zones(width: i32, height: i32) -> [SpawnZone; 2] {
             width: zone_w as f32 * TILE_SIZE,
             height: zone_h as f32 * TILE_SIZE,
         },
-        team: Team::Red,
+        team: left_team,
     };
     let right_zone = SpawnZone {
         rect: Rectangle {
@@ -48,8 +65,139 @@ fn spawn_zones(width: i32, height: i32) -> [SpawnZone; 2] {
             width: zone_w as f32 * TILE_SIZE,
             height: zone_h as f32 * TILE_SIZE,
         },
-        team: Team::Blue,
+        team: right_team,
     };
 
-    [left_zone, right_zone]
+    vec![left_zone, right_zone]
+}
+
+/// Zones spaced evenly around an ellipse inside the map edge, the first
+/// team on `red_side` and the rest following clockwise, or anticlockwise
+/// when Crimson starts on the right.
+fn ringed_zones(width: i32, height: i32, red_side: MapSide, teams: &[Team]) -> Vec<SpawnZone> {
+    let zone_size = 6;
+    let margin = 2;
+    let radius_x = (width - zone_size) as f32 * 0.5 - margin as f32;
+    let radius_y = (height - zone_size) as f32 * 0.5 - margin as f32;
+    let (start, turn) = match red_side {
+        MapSide::Left => (PI, 1.0),
+        MapSide::Right => (0.0, -1.0),
+    };
+    teams
+        .iter()
+        .enumerate()
+        .map(|(slot, &team)| {
+            let angle = start + turn * TAU * slot as f32 / teams.len() as f32;
+            let center_x = width as f32 * 0.5 + radius_x * angle.cos();
+            let center_y = height as f32 * 0.5 + radius_y * angle.sin();
+            let x = (center_x - zone_size as f32 * 0.5).round();
+            let y = (center_y - zone_size as f32 * 0.5).round();
+            SpawnZone {
+                rect: Rectangle {
+                    x: x * TILE_SIZE,
+                    y: y * TILE_SIZE,
+                    width: zone_size as f32 * TILE_SIZE,
+                    height: zone_size as f32 * TILE_SIZE,
+                },
+                team,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use crate::config::MIN_MULTI_TEAM_MAP;
+    use crate::math::{vec2, vec2_distance};
+
+    use super::*;
+
+    #[test]
+    fn every_zone_on_the_smallest_ringed_map_is_open_and_reachable() {
+        let (width, height) = MIN_MULTI_TEAM_MAP;
+        for teams in 3..=Team::ALL.len() {
+            let teams = &Team::ALL[..teams];
+            for red_side in [MapSide::Left, MapSide::Right] {
+                for seed in 0..8 {
+                    let map = MapConfig {
+                        width,
+                        height,
+                        red_side,
+                    };
+                    let world = generate_world(&mut GameRng::seed_from_u64(seed), &map, teams);
+                    let case = format!("{} teams, {red_side:?}, seed {seed}", teams.len());
+                    check_zones(&world, teams, &case);
+                }
+            }
+        }
+    }
+
+    fn check_zones(world: &World, teams: &[Team], case: &str) {
+        let bounds = world.world_bounds();
+        let nav = world.nav();
+        assert_eq!(world.spawn_zones.len(), teams.len(), "{case}");
+        for (slot, zone) in world.spawn_zones.iter().enumerate() {
+            let rect = zone.rect;
+            assert!(
+                rect.x >= 0.0
+                    && rect.y >= 0.0
+                    && rect.x + rect.width <= bounds.width
+                    && rect.y + rect.height <= bounds.height,
+                "{case}: {:?} zone off the map",
+                zone.team
+            );
+            for other in &world.spawn_zones[slot + 1..] {
+                let apart = rect.x + rect.width <= other.rect.x
+                    || other.rect.x + other.rect.width <= rect.x
+                    || rect.y + rect.height <= other.rect.y
+                    || other.rect.y + other.rect.height <= rect.y;
+                assert!(
+                    apart,
+                    "{case}: {:?} and {:?} overlap",
+                    zone.team, other.team
+                );
+            }
+
+            // Open: obstacles stay outside the zone, so nothing may block it
+            // more than a tile in from its edge.
+            let (left, top) = nav.cell_at(vec2(rect.x + TILE_SIZE, rect.y + TILE_SIZE));
+            let (right, bottom) = nav.cell_at(vec2(
+                rect.x + rect.width - TILE_SIZE,
+                rect.y + rect.height - TILE_SIZE,
+            ));
+            for y in top..=bottom {
+                for x in left..=right {
+                    assert!(
+                        nav.is_walkable(zone.team, x, y),
+                        "{case}: cell ({x}, {y}) in the {:?} zone is blocked",
+                        zone.team
+                    );
+                }
+            }
+
+            // Reachable: a route leads from the zone to the middle of the map
+            // and up to every other zone.
+            let from = zone.center();
+            let to_middle = nav.find_path(zone.team, from, world.center());
+            assert!(
+                to_middle
+                    .last()
+                    .is_some_and(|end| vec2_distance(*end, world.center()) < TILE_SIZE),
+                "{case}: {:?} zone cut off from the middle",
+                zone.team
+            );
+            for other in &world.spawn_zones {
+                if other.team != zone.team {
+                    assert!(
+                        !nav.find_path(zone.team, from, other.center()).is_empty(),
+                        "{case}: no route from {:?} to {:?}",
+                        zone.team,
+                        other.team
+                    );
+                }
+            }
+        }
+    }
 }
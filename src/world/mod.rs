@@ -1,43 +1,104 @@
+mod ascii;
 mod generation;
+mod nav;
 mod obstacles;
 mod tiles;
 
-use rand::{Rng, rngs::SmallRng};
-use raylib::prelude::{Rectangle, Vector2};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::config::TILE_SIZE;
+use crate::config::{MapConfig, TILE_SIZE};
 use crate::entities::Team;
-use crate::math::vec2;
+use crate::math::{GameRng, Rectangle, Vector2, distance_to_segment, vec2};
 
+pub use nav::{NAV_CELL_SIZE, NavGrid};
 pub use obstacles::{Obstacle, ObstacleKind};
 pub use tiles::TileKind;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct SpawnZone {
     pub rect: Rectangle,
     pub team: Team,
 }
 
 impl SpawnZone {
+    pub fn center(&self) -> Vector2 {
+        vec2(
+            self.rect.x + self.rect.width * 0.5,
+            self.rect.y + self.rect.height * 0.5,
+        )
+    }
+
     pub fn contains(&self, pos: Vector2) -> bool {
         pos.x >= self.rect.x
             && pos.x <= self.rect.x + self.rect.width
             && pos.y >= self.rect.y
             && pos.y <= self.rect.y + self.rect.height
     }
+
+    /// Distance from `pos` to the zone's edge, 0 inside it.
+    pub fn distance_to(&self, pos: Vector2) -> f32 {
+        let rect = self.rect;
+        let dx = (rect.x - pos.x).max(pos.x - (rect.x + rect.width)).max(0.0);
+        let dy = (rect.y - pos.y)
+            .max(pos.y - (rect.y + rect.height))
+            .max(0.0);
+        (dx * dx + dy * dy).sqrt()
+    }
 }
 
+/// The navigation grid is derived from the layout, so it is rebuilt on
+/// load rather than stored.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "WorldLayout")]
 pub struct World {
     pub width: i32,
     pub height: i32,
     pub tiles: Vec<TileKind>,
     pub obstacles: Vec<Obstacle>,
-    pub spawn_zones: [SpawnZone; 2],
+    /// One per team, in team order.
+    pub spawn_zones: Vec<SpawnZone>,
+    #[serde(skip)]
+    nav: NavGrid,
+}
+
+#[derive(Deserialize)]
+struct WorldLayout {
+    width: i32,
+    height: i32,
+    tiles: Vec<TileKind>,
+    obstacles: Vec<Obstacle>,
+    spawn_zones: Vec<SpawnZone>,
+}
+
+impl From<WorldLayout> for World {
+    fn from(layout: WorldLayout) -> Self {
+        let mut world = World {
+            width: layout.width,
+            height: layout.height,
+            tiles: layout.tiles,
+            obstacles: layout.obstacles,
+            spawn_zones: layout.spawn_zones,
+            nav: NavGrid::default(),
+        };
+        world.nav = NavGrid::build(&world);
+        world
+    }
 }
 
 impl World {
-    pub fn new(rng: &mut SmallRng) -> Self {
-        generation::generate_world(rng)
+    /// Generates a map with a spawn zone for each of `teams`.
+    pub fn new(rng: &mut GameRng, map: &MapConfig, teams: &[Team]) -> Self {
+        generation::generate_world(rng, map, teams)
+    }
+
+    /// The teams with a spawn zone, i.e. those playing on this map.
+    pub fn teams(&self) -> impl Iterator<Item = Team> + '_ {
+        self.spawn_zones.iter().map(|zone| zone.team)
+    }
+
+    pub fn nav(&self) -> &NavGrid {
+        &self.nav
     }
 
     pub fn index(&self, x: i32, y: i32) -> usize {
@@ -57,25 +118,67 @@ impl World {
         }
     }
 
-    pub fn random_point_in_zone(&self, team: Team, rng: &mut SmallRng) -> Vector2 {
-        let zone = self
-            .spawn_zones
+    pub fn center(&self) -> Vector2 {
+        vec2(
+            self.width as f32 * TILE_SIZE * 0.5,
+            self.height as f32 * TILE_SIZE * 0.5,
+        )
+    }
+
+    pub fn spawn_zone(&self, team: Team) -> &SpawnZone {
+        self.spawn_zones
             .iter()
             .find(|zone| zone.team == team)
-            .expect("missing spawn zone");
+            .expect("missing spawn zone")
+    }
+
+    pub fn random_point_in_zone(&self, team: Team, rng: &mut GameRng) -> Vector2 {
+        let zone = self.spawn_zone(team);
         let margin = TILE_SIZE * 0.4;
         let x = rng.random_range(zone.rect.x + margin..zone.rect.x + zone.rect.width - margin);
         let y = rng.random_range(zone.rect.y + margin..zone.rect.y + zone.rect.height - margin);
         vec2(x, y)
     }
 
+    /// Whether a circle of radius `clearance` can travel from `from` to `to`
+    /// without touching an obstacle, e.g. a bullet or a tank's line of fire.
+    pub fn line_of_sight(&self, from: Vector2, to: Vector2, clearance: f32) -> bool {
+        self.obstacles.iter().all(|obstacle| {
+            distance_to_segment(obstacle.pos, from, to) >= obstacle.radius + clearance
+        })
+    }
+
     pub fn is_inside_spawn_zone(&self, pos: Vector2) -> bool {
         self.spawn_zones.iter().any(|zone| zone.contains(pos))
     }
 
     pub fn is_inside_enemy_zone(&self, team: Team, pos: Vector2) -> bool {
+        self.enemy_zone_at(team, pos).is_some()
+    }
+
+    /// The spawn zone of another team that `pos` is in, if any.
+    pub fn enemy_zone_at(&self, team: Team, pos: Vector2) -> Option<&SpawnZone> {
         self.spawn_zones
             .iter()
-            .any(|zone| zone.team != team && zone.contains(pos))
+            .find(|zone| zone.team != team && zone.contains(pos))
+    }
+}
+
+#[cfg(test)]
+impl World {
+    /// A grass map with nothing on it but `obstacles` and `spawn_zones`.
+    pub(crate) fn open(
+        width: i32,
+        height: i32,
+        obstacles: Vec<Obstacle>,
+        spawn_zones: Vec<SpawnZone>,
+    ) -> Self {
+        World::from(WorldLayout {
+            width,
+            height,
+            tiles: vec![TileKind::Grass; (width * height) as usize],
+            obstacles,
+            spawn_zones,
+        })
     }
 }
@@ -0,0 +1,348 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::config::{TANK_RADIUS, TILE_SIZE};
+use crate::entities::Team;
+use crate::math::{Vector2, vec2, vec2_distance};
+
+use super::World;
+
+pub const NAV_CELL_SIZE: f32 = TILE_SIZE * 0.5;
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+const GOAL_SEARCH_RADIUS: i32 = 6;
+const UNVISITED: u32 = u32::MAX;
+
+/// Walkability of the map for tanks, sampled at half-tile resolution. A cell
+/// is blocked when a tank centred on it would overlap an obstacle or the map
+/// edge; spawn zones are tracked separately since only the enemy's is off
+/// limits.
+#[derive(Clone, Debug, Default)]
+pub struct NavGrid {
+    width: i32,
+    height: i32,
+    blocked: Vec<bool>,
+    zone: Vec<Option<Team>>,
+}
+
+impl NavGrid {
+    pub fn build(world: &World) -> Self {
+        let bounds = world.world_bounds();
+        let width = (bounds.width / NAV_CELL_SIZE).ceil() as i32;
+        let height = (bounds.height / NAV_CELL_SIZE).ceil() as i32;
+        let mut blocked = Vec::with_capacity((width * height) as usize);
+        let mut zone = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let center = cell_center(x, y);
+                let off_map = center.x < bounds.x + TANK_RADIUS
+                    || center.y < bounds.y + TANK_RADIUS
+                    || center.x > bounds.x + bounds.width - TANK_RADIUS
+                    || center.y > bounds.y + bounds.height - TANK_RADIUS;
+                let near_obstacle = world.obstacles.iter().any(|obstacle| {
+                    vec2_distance(center, obstacle.pos) < obstacle.radius + TANK_RADIUS
+                });
+                blocked.push(off_map || near_obstacle);
+                zone.push(
+                    world
+                        .spawn_zones
+                        .iter()
+                        .find(|zone| {
+                            center.x > zone.rect.x - TANK_RADIUS
+                                && center.x < zone.rect.x + zone.rect.width + TANK_RADIUS
+                                && center.y > zone.rect.y - TANK_RADIUS
+                                && center.y < zone.rect.y + zone.rect.height + TANK_RADIUS
+                        })
+                        .map(|zone| zone.team),
+                );
+            }
+        }
+        Self {
+            width,
+            height,
+            blocked,
+            zone,
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn cell_at(&self, pos: Vector2) -> (i32, i32) {
+        (
+            ((pos.x / NAV_CELL_SIZE) as i32).clamp(0, (self.width - 1).max(0)),
+            ((pos.y / NAV_CELL_SIZE) as i32).clamp(0, (self.height - 1).max(0)),
+        )
+    }
+
+    pub fn is_walkable(&self, team: Team, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return false;
+        }
+        let index = self.index(x, y);
+        !self.blocked[index] && self.zone[index].is_none_or(|zone| zone == team)
+    }
+
+    /// Shortest route for a tank of `team` from `from` to `to`, as waypoints
+    /// ending at the goal. Straight runs are collapsed to their end points.
+    /// When `to` is unreachable the path ends at the closest walkable cell
+    /// near it; an empty path means no route exists.
+    pub fn find_path(&self, team: Team, from: Vector2, to: Vector2) -> Vec<Vector2> {
+        let start = self.cell_at(from);
+        let requested = self.cell_at(to);
+        let Some(goal) = self.nearest_walkable(team, requested) else {
+            return Vec::new();
+        };
+
+        let start_index = self.index(start.0, start.1);
+        let goal_index = self.index(goal.0, goal.1);
+        let mut cost = vec![UNVISITED; self.blocked.len()];
+        let mut came_from = vec![UNVISITED; self.blocked.len()];
+        let mut open = BinaryHeap::new();
+        cost[start_index] = 0;
+        open.push(Reverse((self.heuristic(start, goal), start_index as u32)));
+
+        while let Some(Reverse((estimate, index))) = open.pop() {
+            let index = index as usize;
+            if index == goal_index {
+                break;
+            }
+            let (x, y) = self.coords(index);
+            if estimate > cost[index] + self.heuristic((x, y), goal) {
+                continue;
+            }
+            for (dx, dy) in NEIGHBORS {
+                let (nx, ny) = (x + dx, y + dy);
+                if !self.is_walkable(team, nx, ny) {
+                    continue;
+                }
+                let diagonal = dx != 0 && dy != 0;
+                if diagonal
+                    && !(self.is_walkable(team, x + dx, y) && self.is_walkable(team, x, y + dy))
+                {
+                    continue;
+                }
+                let step = if diagonal {
+                    DIAGONAL_COST
+                } else {
+                    STRAIGHT_COST
+                };
+                let next = self.index(nx, ny);
+                let next_cost = cost[index] + step;
+                if next_cost < cost[next] {
+                    cost[next] = next_cost;
+                    came_from[next] = index as u32;
+                    open.push(Reverse((
+                        next_cost + self.heuristic((nx, ny), goal),
+                        next as u32,
+                    )));
+                }
+            }
+        }
+
+        if cost[goal_index] == UNVISITED {
+            return Vec::new();
+        }
+
+        let mut cells = vec![goal];
+        let mut index = goal_index;
+        while came_from[index] != UNVISITED {
+            index = came_from[index] as usize;
+            cells.push(self.coords(index));
+        }
+        cells.reverse();
+
+        let mut path: Vec<Vector2> = cells
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(i, cell)| {
+                // Keep turning points only.
+                cells.get(i + 1).is_none_or(|next| {
+                    let prev = cells[i - 1];
+                    (cell.0 - prev.0, cell.1 - prev.1) != (next.0 - cell.0, next.1 - cell.1)
+                })
+            })
+            .map(|(_, cell)| cell_center(cell.0, cell.1))
+            .collect();
+        if goal == requested {
+            match path.last_mut() {
+                Some(last) => *last = to,
+                None => path.push(to),
+            }
+        }
+        path
+    }
+
+    fn nearest_walkable(&self, team: Team, cell: (i32, i32)) -> Option<(i32, i32)> {
+        (0..=GOAL_SEARCH_RADIUS).find_map(|radius| {
+            let mut best = None;
+            let mut best_dist = i32::MAX;
+            for y in cell.1 - radius..=cell.1 + radius {
+                for x in cell.0 - radius..=cell.0 + radius {
+                    let ring = (x - cell.0).abs().max((y - cell.1).abs()) == radius;
+                    let dist = (x - cell.0).pow(2) + (y - cell.1).pow(2);
+                    if ring && dist < best_dist && self.is_walkable(team, x, y) {
+                        best = Some((x, y));
+                        best_dist = dist;
+                    }
+                }
+            }
+            best
+        })
+    }
+
+    fn heuristic(&self, from: (i32, i32), to: (i32, i32)) -> u32 {
+        let dx = (from.0 - to.0).unsigned_abs();
+        let dy = (from.1 - to.1).unsigned_abs();
+        STRAIGHT_COST * dx.max(dy) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dy)
+    }
+
+    fn index(&self, x: i32, y: i32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    fn coords(&self, index: usize) -> (i32, i32) {
+        (index as i32 % self.width, index as i32 / self.width)
+    }
+}
+
+const NEIGHBORS: [(i32, i32); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+fn cell_center(x: i32, y: i32) -> Vector2 {
+    vec2(
+        (x as f32 + 0.5) * NAV_CELL_SIZE,
+        (y as f32 + 0.5) * NAV_CELL_SIZE,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::Rectangle;
+
+    use super::super::{Obstacle, ObstacleKind, SpawnZone};
+    use super::*;
+
+    const OBSTACLE_RADIUS: f32 = 20.0;
+
+    /// An open 12x8 tile map with obstacles centred on the given nav cells.
+    fn world(blocked: &[(i32, i32)], spawn_zones: Vec<SpawnZone>) -> World {
+        let obstacles = blocked
+            .iter()
+            .map(|&(x, y)| Obstacle {
+                kind: ObstacleKind::BarrelRedUp,
+                pos: cell_center(x, y),
+                radius: OBSTACLE_RADIUS,
+            })
+            .collect();
+        World::open(12, 8, obstacles, spawn_zones)
+    }
+
+    fn length(from: Vector2, path: &[Vector2]) -> f32 {
+        std::iter::once(&from)
+            .chain(path)
+            .zip(path)
+            .map(|(a, b)| vec2_distance(*a, *b))
+            .sum()
+    }
+
+    #[test]
+    fn routes_around_a_wall() {
+        // A wall down column 10 with a gap only at the bottom.
+        let wall: Vec<_> = (0..14).map(|y| (10, y)).collect();
+        let world = world(&wall, Vec::new());
+        let (from, to) = (cell_center(4, 4), cell_center(16, 4));
+
+        let path = world.nav().find_path(Team::Red, from, to);
+        assert_eq!(path.last(), Some(&to));
+        assert!(path.iter().any(|point| point.y > cell_center(0, 13).y));
+        let mut leg_start = from;
+        for &point in &path {
+            assert!(world.line_of_sight(leg_start, point, 0.0));
+            leg_start = point;
+        }
+        assert!(length(from, &path) > vec2_distance(from, to) * 2.0);
+    }
+
+    #[test]
+    fn does_not_cut_corners() {
+        // The only diagonal step from (5, 5) to (6, 6) squeezes between two
+        // obstacles touching at a corner.
+        let world = world(&[(6, 5), (5, 6)], Vec::new());
+        let nav = world.nav();
+        assert!(nav.is_walkable(Team::Red, 5, 5));
+        assert!(nav.is_walkable(Team::Red, 6, 6));
+        assert!(!nav.is_walkable(Team::Red, 6, 5));
+        assert!(!nav.is_walkable(Team::Red, 5, 6));
+
+        let (from, to) = (cell_center(5, 5), cell_center(6, 6));
+        let path = nav.find_path(Team::Red, from, to);
+        assert_eq!(path.last(), Some(&to));
+        assert!(path.len() > 1, "took the diagonal: {path:?}");
+        assert!(length(from, &path) > vec2_distance(from, to) * 2.0);
+    }
+
+    #[test]
+    fn enemy_spawn_zones_are_off_limits() {
+        let zone = SpawnZone {
+            rect: Rectangle {
+                x: 4.0 * TILE_SIZE,
+                y: 0.0,
+                width: 4.0 * TILE_SIZE,
+                height: 6.0 * TILE_SIZE,
+            },
+            team: Team::Blue,
+        };
+        let world = world(&[], vec![zone]);
+        let nav = world.nav();
+        let (x, y) = nav.cell_at(zone.center());
+        assert!(nav.is_walkable(Team::Blue, x, y));
+        assert!(!nav.is_walkable(Team::Red, x, y));
+
+        // Straight through the zone is shortest, so Azure goes that way and
+        // Crimson has to go round underneath.
+        let from = vec2(2.0 * TILE_SIZE, 3.0 * TILE_SIZE);
+        let to = vec2(10.0 * TILE_SIZE, 3.0 * TILE_SIZE);
+        assert_eq!(nav.find_path(Team::Blue, from, to), [to]);
+        let path = nav.find_path(Team::Red, from, to);
+        assert_eq!(path.last(), Some(&to));
+        assert!(path.iter().all(|point| !zone.contains(*point)));
+        assert!(path.iter().any(|point| point.y > zone.rect.height));
+
+        // A goal inside the zone is swapped for the nearest cell outside it.
+        let inside = nav.find_path(Team::Red, from, zone.center());
+        let end = *inside.last().expect("stops short of the zone");
+        assert!(zone.distance_to(end) > 0.0);
+    }
+
+    #[test]
+    fn nearest_walkable_steps_out_of_an_obstacle() {
+        let world = world(&[(8, 8)], Vec::new());
+        let nav = world.nav();
+        assert!(!nav.is_walkable(Team::Red, 8, 8));
+
+        let (x, y) = nav
+            .nearest_walkable(Team::Red, (8, 8))
+            .expect("the cells around it are open");
+        assert_eq!((x - 8).abs() + (y - 8).abs(), 1);
+        assert_eq!(nav.nearest_walkable(Team::Red, (3, 3)), Some((3, 3)));
+
+        let path = nav.find_path(Team::Red, cell_center(2, 8), cell_center(8, 8));
+        assert_eq!(path.last(), Some(&cell_center(x, y)));
+    }
+}
@@ -1,11 +1,11 @@
-use rand::{Rng, rngs::SmallRng};
-use raylib::prelude::Vector2;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::math::{vec2, vec2_distance};
+use crate::math::{GameRng, Vector2, vec2, vec2_distance};
 
 use super::World;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum ObstacleKind {
     TreeSmall,
     TreeLarge,
@@ -22,14 +22,14 @@ pub enum ObstacleKind {
     BarrelGreenSideDamaged,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Obstacle {
     pub kind: ObstacleKind,
     pub pos: Vector2,
     pub radius: f32,
 }
 
-pub(super) fn generate_obstacles(world: &mut World, rng: &mut SmallRng) {
+pub(super) fn generate_obstacles(world: &mut World, rng: &mut GameRng) {
     let all_kinds = [
         ObstacleKind::TreeSmall,
         ObstacleKind::TreeLarge,
@@ -74,7 +74,7 @@ pub(super) fn generate_obstacles(world: &mut World, rng: &mut SmallRng) {
 fn find_open_obstacle_position(
     world: &World,
     kind: ObstacleKind,
-    rng: &mut SmallRng,
+    rng: &mut GameRng,
     attempts: usize,
 ) -> Option<Vector2> {
     let bounds = world.world_bounds();
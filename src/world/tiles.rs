@@ -1,18 +1,20 @@
-use rand::{Rng, rngs::SmallRng};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::config::TILE_SIZE;
 use crate::entities::Team;
+use crate::math::GameRng;
 
 use super::SpawnZone;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum TileKind {
     Grass,
     Dirt,
     Sand,
 }
 
-pub(super) fn generate_tiles(rng: &mut SmallRng, width: i32, height: i32) -> Vec<TileKind> {
+pub(super) fn generate_tiles(rng: &mut GameRng, width: i32, height: i32) -> Vec<TileKind> {
     let mut values = vec![0.0f32; (width * height) as usize];
     for value in &mut values {
         *value = rng.random_range(0.0..1.0);
@@ -56,15 +58,16 @@ pub(super) fn generate_tiles(rng: &mut SmallRng, width: i32, height: i32) -> Vec
     tiles
 }
 
-pub(super) fn paint_spawn_zones(tiles: &mut [TileKind], width: i32, zones: &[SpawnZone; 2]) {
+pub(super) fn paint_spawn_zones(tiles: &mut [TileKind], width: i32, zones: &[SpawnZone]) {
     for zone in zones {
         let start_x = (zone.rect.x / TILE_SIZE) as i32;
         let start_y = (zone.rect.y / TILE_SIZE) as i32;
         let end_x = ((zone.rect.x + zone.rect.width) / TILE_SIZE) as i32;
         let end_y = ((zone.rect.y + zone.rect.height) / TILE_SIZE) as i32;
         let tile_kind = match zone.team {
-            Team::Red => TileKind::Grass,
-            Team::Blue => TileKind::Sand,
+            Team::Red | Team::Green => TileKind::Grass,
+            Team::Blue | Team::Beige => TileKind::Sand,
+            Team::Black => TileKind::Dirt,
         };
 
         for y in start_y..end_y {
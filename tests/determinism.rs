@@ -0,0 +1,121 @@
+use tanks::config::{GameConfig, TICK_DT};
+use tanks::game::{FixedTimestep, Game, PlayerInput, TankInput, TickInput};
+
+/// A scripted driver for tank 0: weaves, sweeps the turret and fires in
+/// bursts, so the round sees human input as well as the AI.
+fn scripted_input(tick: u32) -> TickInput {
+    let t = tick as f32 * TICK_DT;
+    TickInput {
+        start_pressed: tick == 0,
+        tanks: vec![TankInput {
+            tank: 0,
+            input: PlayerInput {
+                turn: (t * 0.7).sin(),
+                movement: if (tick / 90) % 3 == 2 { -0.5 } else { 1.0 },
+                aim_angle: Some(t * 1.3),
+                wants_fire: (tick / 20).is_multiple_of(2),
+            },
+        }],
+    }
+}
+
+#[test]
+fn same_seed_and_inputs_replay_bit_for_bit() {
+    let config = GameConfig::default();
+    let mut first = Game::with_config(1234, config.clone());
+    let mut second = Game::with_config(1234, config);
+    assert_eq!(first.checksum(), second.checksum());
+
+    for tick in 0..4000 {
+        let input = scripted_input(tick);
+        first.update(TICK_DT, &input);
+        second.update(TICK_DT, &input);
+        if tick.is_multiple_of(500) {
+            assert_eq!(
+                first.checksum(),
+                second.checksum(),
+                "diverged by tick {tick}"
+            );
+        }
+    }
+    assert!(
+        first.team_kills().iter().sum::<u32>() > 0,
+        "nothing happened"
+    );
+    assert_eq!(first.checksum(), second.checksum());
+    assert_eq!(first.team_kills(), second.team_kills());
+}
+
+#[test]
+fn different_seeds_play_different_rounds() {
+    let config = GameConfig::default();
+    let mut first = Game::with_config(1, config.clone());
+    let mut second = Game::with_config(2, config);
+    for tick in 0..600 {
+        let input = scripted_input(tick);
+        first.update(TICK_DT, &input);
+        second.update(TICK_DT, &input);
+    }
+    assert_ne!(first.checksum(), second.checksum());
+}
+
+/// Feeds `frames` to a fresh timestep and counts the ticks it runs, and
+/// how many of them saw the start press sent with the first frame.
+fn run_frames(frames: &[f32]) -> (usize, usize) {
+    let mut timestep = FixedTimestep::new();
+    let (mut ticks, mut starts) = (0, 0);
+    for (index, &frame) in frames.iter().enumerate() {
+        let input = TickInput {
+            start_pressed: index == 0,
+            ..TickInput::default()
+        };
+        timestep.advance(frame, input, |step| {
+            ticks += 1;
+            starts += step.start_pressed as usize;
+        });
+    }
+    (ticks, starts)
+}
+
+#[test]
+fn tick_count_does_not_depend_on_frame_lengths() {
+    // Ten and a half seconds of play, so rounding cannot tip the last tick
+    // either way.
+    let total = 630.5 * TICK_DT;
+    let even = vec![TICK_DT; 630].into_iter().chain([TICK_DT * 0.5]);
+    let fast = vec![total / 1500.0; 1500];
+    let slow = vec![total / 210.0; 210];
+    let ragged: Vec<f32> = [0.003, 0.021, 0.0167, 0.05, 0.0009, 0.033]
+        .into_iter()
+        .cycle()
+        .scan(0.0, |elapsed, frame| {
+            let frame = f32::min(frame, total - *elapsed);
+            *elapsed += frame;
+            (frame > 0.0).then_some(frame)
+        })
+        .collect();
+
+    let expected = run_frames(&even.collect::<Vec<_>>());
+    assert_eq!(expected, (630, 1));
+    assert_eq!(run_frames(&fast), expected);
+    assert_eq!(run_frames(&slow), expected);
+    assert_eq!(run_frames(&ragged), expected);
+}
+
+#[test]
+fn a_start_press_waits_for_the_next_tick() {
+    let mut timestep = FixedTimestep::new();
+    let mut starts = Vec::new();
+    let pressed = TickInput {
+        start_pressed: true,
+        ..TickInput::default()
+    };
+    // Too short to run a tick: the press is held, not dropped.
+    timestep.advance(TICK_DT * 0.4, pressed, |step| {
+        starts.push(step.start_pressed)
+    });
+    timestep.advance(TICK_DT * 2.0, TickInput::default(), |step| {
+        starts.push(step.start_pressed)
+    });
+    assert_eq!(starts, [true, false]);
+}
@@ -0,0 +1,19 @@
+use std::process::Command;
+
+/// Two real peer processes over a simulated bad link: they must finish
+/// without stalling out or desyncing.
+#[test]
+fn duel_test_survives_a_lossy_link() {
+    let output = Command::new(env!("CARGO_BIN_EXE_tanks"))
+        .args(["duel", "test", "--seconds", "5"])
+        .args(["--latency", "60", "--jitter", "20", "--loss", "0.05"])
+        .output()
+        .expect("tanks runs");
+    assert!(
+        output.status.success(),
+        "duel test failed ({}):\n{}{}",
+        output.status,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
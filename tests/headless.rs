@@ -0,0 +1,153 @@
+use tanks::config::{GameConfig, TICK_DT};
+use tanks::entities::{Tank, Team};
+use tanks::game::{Game, GameEvent, PlayerInput, TankInput, TickInput};
+use tanks::math::{vec2, vec2_distance};
+
+/// A short round with no countdown or powerups, ready to play.
+fn quiet_game(seed: u64) -> Game {
+    let mut config = GameConfig::default();
+    config.round.countdown = 0.0;
+    config.round.time = 6.0;
+    config.powerup.max_count = 0;
+    let mut game = Game::with_config(seed, config);
+    game.start_round();
+    game
+}
+
+/// The same input for every tank, so none of them is left to the AI.
+fn drive_all(game: &Game, input: PlayerInput, driven: &[(usize, PlayerInput)]) -> TickInput {
+    TickInput {
+        start_pressed: false,
+        tanks: (0..game.tanks().len())
+            .map(|tank| TankInput {
+                tank,
+                input: driven
+                    .iter()
+                    .find(|(index, _)| *index == tank)
+                    .map_or(input, |(_, input)| *input),
+            })
+            .collect(),
+    }
+}
+
+fn first_of(game: &Game, team: Team) -> usize {
+    game.tanks()
+        .iter()
+        .position(|tank| tank.team == team)
+        .expect("every team fields a tank")
+}
+
+#[test]
+fn idle_input_holds_tanks_still() {
+    let mut game = quiet_game(7);
+    let start: Vec<_> = game.tanks().iter().map(|tank| tank.pos).collect();
+    let idle = drive_all(&game, PlayerInput::default(), &[]);
+    for _ in 0..60 {
+        game.update(TICK_DT, &idle);
+    }
+    let now: Vec<_> = game.tanks().iter().map(|tank| tank.pos).collect();
+    assert_eq!(start, now);
+    assert!(game.bullets().is_empty());
+}
+
+#[test]
+fn movement_input_drives_the_tank_forward() {
+    let mut game = quiet_game(7);
+    let tank = first_of(&game, Team::Red);
+    let start = game.tanks()[tank].pos;
+    let forward = PlayerInput {
+        movement: 1.0,
+        ..PlayerInput::default()
+    };
+    let input = drive_all(&game, PlayerInput::default(), &[(tank, forward)]);
+    for _ in 0..30 {
+        game.update(TICK_DT, &input);
+    }
+    let moved = vec2_distance(start, game.tanks()[tank].pos);
+    assert!(moved > 20.0, "only moved {moved}");
+}
+
+/// Finds a clear lane in the middle of the map and lines `shooter` up on
+/// `target` across it, to its east, with every other tank out of the
+/// round.
+fn stage_duel(game: &mut Game, shooter: usize, target: usize) {
+    let world = game.world();
+    let middle = world.center();
+    let (from, to) = (0..world.height)
+        .flat_map(|row| [row, -row])
+        .map(|row| {
+            let y = middle.y + row as f32 * 32.0;
+            (vec2(middle.x - 150.0, y), vec2(middle.x + 150.0, y))
+        })
+        .find(|&(from, to)| {
+            world.line_of_sight(from, to, 40.0)
+                && !world.is_inside_spawn_zone(from)
+                && !world.is_inside_spawn_zone(to)
+        })
+        .expect("the middle of the map has a clear lane");
+
+    let place = |tank: &mut Tank, pos| {
+        tank.pos = pos;
+        tank.prev_pos = pos;
+        tank.invincible_timer = 0.0;
+    };
+    for (index, tank) in game.tanks_mut().iter_mut().enumerate() {
+        if index == shooter {
+            place(tank, from);
+        } else if index == target {
+            place(tank, to);
+        } else {
+            tank.alive = false;
+            tank.respawn_timer = f32::MAX;
+        }
+    }
+}
+
+#[test]
+fn a_scripted_kill_scores_and_wins_the_round() {
+    let mut game = quiet_game(11);
+    let shooter = first_of(&game, Team::Red);
+    let target = first_of(&game, Team::Blue);
+    stage_duel(&mut game, shooter, target);
+    let fire = PlayerInput {
+        aim_angle: Some(0.0),
+        wants_fire: true,
+        ..PlayerInput::default()
+    };
+    let input = drive_all(&game, PlayerInput::default(), &[(shooter, fire)]);
+
+    let mut killed = None;
+    while game.is_playing() {
+        game.update(TICK_DT, &input);
+        for event in game.events() {
+            if let GameEvent::Kill {
+                victim,
+                killer,
+                shooter,
+            } = *event
+            {
+                killed = Some((victim, killer, shooter));
+            }
+        }
+    }
+
+    assert_eq!(killed, Some((target, Team::Red, Some(shooter))));
+    assert_eq!(game.team_kills(), [1, 0]);
+    assert!(game.is_round_over());
+    assert_eq!(game.last_winner(), Some(Team::Red));
+}
+
+#[test]
+fn a_round_without_kills_is_a_stalemate() {
+    let mut game = quiet_game(3);
+    let idle = drive_all(&game, PlayerInput::default(), &[]);
+    let mut ticks = 0;
+    while game.is_playing() {
+        game.update(TICK_DT, &idle);
+        ticks += 1;
+    }
+    // Six seconds of play, plus the tick that notices the clock ran out.
+    assert!((360..=362).contains(&ticks), "round lasted {ticks} ticks");
+    assert_eq!(game.team_kills(), [0, 0]);
+    assert_eq!(game.last_winner(), None);
+}
@@ -0,0 +1,76 @@
+use std::net::SocketAddr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tanks::entities::Team;
+use tanks::net::{Discovery, LinkConditions, LobbyGuest, LobbyHost, LobbySettings, LobbyStatus};
+
+const FRAME: Duration = Duration::from_millis(10);
+
+/// Runs `step` a frame at a time until it reports it is done, failing
+/// after a few seconds.
+fn until(what: &str, mut step: impl FnMut(f32) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        thread::sleep(FRAME);
+        if step(FRAME.as_secs_f32()) {
+            return;
+        }
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+    }
+}
+
+#[test]
+fn a_guest_finds_joins_and_follows_a_lobby_into_the_match() {
+    let settings = LobbySettings {
+        map_seed: 5,
+        round_time: 60.0,
+        bots: 2,
+        teams: 2,
+    };
+    let mut host = LobbyHost::bind("127.0.0.1:0", "ana", settings, LinkConditions::default())
+        .expect("lobby binds");
+    let port = host.local_addr().unwrap().port();
+
+    let mut discovery = Discovery::new(port).expect("discovery binds");
+    until("the lobby to be found", |dt| {
+        host.update(dt).unwrap();
+        discovery.update(dt).unwrap();
+        discovery.lobbies().next().is_some()
+    });
+    let found = discovery.lobbies().next().unwrap().clone();
+    assert_eq!(found.host, "ana");
+    assert_eq!(found.players, 1);
+    assert!(!found.started);
+
+    let mut guest =
+        LobbyGuest::join(found.addr, "ben", LinkConditions::default()).expect("guest binds");
+    until("the guest to be seated", |dt| {
+        host.update(dt).unwrap();
+        guest.update(dt).unwrap();
+        guest.status() == &LobbyStatus::Waiting
+    });
+    assert_eq!(host.players().len(), 2);
+    assert_eq!(guest.settings(), Some(settings));
+
+    host.choose(Team::Red, true);
+    assert!(!host.all_ready());
+    guest.choose(Team::Blue, true);
+    until("everyone to be ready", |dt| {
+        host.update(dt).unwrap();
+        guest.update(dt).unwrap();
+        host.all_ready()
+    });
+    assert_eq!(host.humans()[Team::Blue.index()], 1);
+
+    host.start(7777).unwrap();
+    until("the guest to hear the match started", |dt| {
+        host.update(dt).unwrap();
+        guest.update(dt).unwrap();
+        matches!(guest.status(), LobbyStatus::Started(_))
+    });
+    let LobbyStatus::Started(server) = *guest.status() else {
+        unreachable!()
+    };
+    assert_eq!(server, SocketAddr::new(found.addr.ip(), 7777));
+}
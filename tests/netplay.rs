@@ -0,0 +1,110 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tanks::config::{GameConfig, TICK_DT};
+use tanks::entities::Team;
+use tanks::game::PlayerInput;
+use tanks::math::vec2_distance;
+use tanks::net::{Client, ClientStatus, LinkConditions, Server};
+
+/// A localhost link about as bad as a long-distance connection.
+const LOSSY: LinkConditions = LinkConditions {
+    latency: 0.06,
+    jitter: 0.02,
+    loss: 0.05,
+};
+
+/// A server and one client, ticked together in real time.
+struct Session {
+    server: Server,
+    client: Client,
+    ticks: f32,
+    last: Instant,
+}
+
+impl Session {
+    fn new() -> Self {
+        let mut config = GameConfig::default();
+        config.round.intro_time = 0.0;
+        config.round.countdown = 0.0;
+        config.powerup.max_count = 0;
+        let server = Server::bind("127.0.0.1:0", config, 9, LOSSY).unwrap();
+        let client = Client::connect(
+            server.local_addr().unwrap(),
+            "tester",
+            Some(Team::Red),
+            LOSSY,
+        )
+        .unwrap();
+        Self {
+            server,
+            client,
+            ticks: 0.0,
+            last: Instant::now(),
+        }
+    }
+
+    /// Sleeps for about a tick, then runs the server for however many
+    /// ticks have come due and the client for the time that passed.
+    fn step(&mut self, input: PlayerInput) {
+        thread::sleep(Duration::from_secs_f32(TICK_DT / 2.0));
+        let elapsed = self.last.elapsed().as_secs_f32();
+        self.last = Instant::now();
+        self.ticks += elapsed;
+        while self.ticks >= TICK_DT {
+            self.ticks -= TICK_DT;
+            self.server.tick().unwrap();
+        }
+        self.client.update(elapsed, input).unwrap();
+    }
+
+    /// How far the client's predicted tank is from the server's.
+    fn drift(&self) -> f32 {
+        let tank = self.client.tank().expect("seated");
+        vec2_distance(
+            self.client.game().tanks()[tank].pos,
+            self.server.game().tanks()[tank].pos,
+        )
+    }
+}
+
+#[test]
+fn prediction_tracks_the_server_over_a_lossy_link() {
+    let mut session = Session::new();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while session.client.status() != &ClientStatus::Playing {
+        assert!(
+            Instant::now() < deadline,
+            "never started playing: {:?}",
+            session.client.status()
+        );
+        session.step(PlayerInput::default());
+    }
+    let tank = session.client.tank().expect("seated");
+    let start = session.server.game().tanks()[tank].pos;
+
+    // Driving, the prediction runs ahead of the server by about the time
+    // inputs take to arrive.
+    let drive = PlayerInput {
+        turn: 0.3,
+        movement: 1.0,
+        ..PlayerInput::default()
+    };
+    let until = Instant::now() + Duration::from_secs(2);
+    while Instant::now() < until {
+        session.step(drive);
+        let drift = session.drift();
+        assert!(drift < 48.0, "prediction {drift} away while driving");
+    }
+    let moved = vec2_distance(start, session.server.game().tanks()[tank].pos);
+    assert!(moved > 100.0, "server tank only moved {moved}");
+
+    // Once stopped, both settle on the same spot.
+    let until = Instant::now() + Duration::from_secs(1);
+    while Instant::now() < until {
+        session.step(PlayerInput::default());
+    }
+    let drift = session.drift();
+    assert!(drift < 4.0, "prediction {drift} away after stopping");
+    assert_eq!(session.client.status(), &ClientStatus::Playing);
+}